@@ -1,10 +1,13 @@
 //! Aggregate metrics, statistical tests, and summary report generation.
 
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use crate::batch::CorpusEntry;
+use crate::profile::Profile;
 use crate::report::ComparisonReport;
+use crate::runner::RunResult;
 
 /// Aggregated results from a batch run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,8 +30,62 @@ pub struct AggregateReport {
     pub by_language: HashMap<String, MetricsSummary>,
     /// Breakdown by codebase size
     pub by_size: HashMap<String, MetricsSummary>,
+    /// Breakdown by issue label (an issue counts toward each of its labels)
+    #[serde(default)]
+    pub by_label: HashMap<String, MetricsSummary>,
     /// Per-issue results
     pub per_issue: Vec<IssueResult>,
+    /// Issues where FMM used fewer tool calls
+    #[serde(default)]
+    pub fmm_wins: u32,
+    /// Issues where control used fewer tool calls
+    #[serde(default)]
+    pub control_wins: u32,
+    /// Issues with equal tool calls
+    #[serde(default)]
+    pub ties: u32,
+    /// Also report tool calls normalized by issue difficulty (`--normalize`)
+    #[serde(default)]
+    pub normalize: bool,
+    /// Tool calls per unit of difficulty (set when `normalize` is on)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalized_tool_calls: Option<PairedMetric>,
+    /// Stage timings summed across this batch's issues (`--profile`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+    /// Also export the paired samples behind `summary` (`--export-raw-pairs`)
+    #[serde(default)]
+    pub export_raw_pairs: bool,
+    /// Paired samples behind `summary`, one per per-issue row (set when
+    /// `export_raw_pairs` is on)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub raw_pairs: Vec<MetricPair>,
+    /// Issues that were attempted but produced no report (clone, setup, or
+    /// run failures); counted in `issues_total` but not `issues_completed`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<FailedIssue>,
+    /// Issues skipped before running for a thin body (`--skip-empty-body`);
+    /// not counted in `issues_total`, and `--only-failed` leaves them alone
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<FailedIssue>,
+    /// Fraction of FMM runs that actually read a sidecar or called an fmm
+    /// MCP tool (None when no row records adoption)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fmm_adoption_rate: Option<f64>,
+    /// Estimated spend to finish the issues a budget stop left unattempted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_projection: Option<CostProjection>,
+    /// The batch hit its `--max-wall-secs` deadline; results are partial
+    #[serde(default)]
+    pub deadline_truncated: bool,
+    /// Wall-clock time of the batch runs behind this report, summed across
+    /// merged re-runs
+    #[serde(default)]
+    pub wall_ms: u64,
+    /// Fresh-run spend behind `wall_ms` (None if any merged report predates
+    /// spend tracking)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spent: Option<Spend>,
 }
 
 /// Summary of paired metrics across runs.
@@ -42,19 +99,313 @@ pub struct MetricsSummary {
     pub read_calls: PairedMetric,
 }
 
+/// One paired observation (control vs fmm) behind the summaries, exported
+/// with `--export-raw-pairs` for re-analysis. Tokens, duration, and reads are
+/// None for rows from aggregates written before they were recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricPair {
+    pub control_tools: f64,
+    pub fmm_tools: f64,
+    #[serde(default)]
+    pub control_tokens: Option<f64>,
+    #[serde(default)]
+    pub fmm_tokens: Option<f64>,
+    pub control_cost: f64,
+    pub fmm_cost: f64,
+    #[serde(default)]
+    pub control_duration: Option<f64>,
+    #[serde(default)]
+    pub fmm_duration: Option<f64>,
+    #[serde(default)]
+    pub control_reads: Option<f64>,
+    #[serde(default)]
+    pub fmm_reads: Option<f64>,
+}
+
 /// A paired metric (control vs fmm) with mean, delta, and optional p-value.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PairedMetric {
     pub control_mean: f64,
     pub fmm_mean: f64,
+    /// FMM's change relative to control, signed so positive favors FMM: a
+    /// reduction when `lower_is_better`, else an improvement
     pub delta_pct: f64,
+    /// Whether FMM doing less is the win (tool calls, cost); false for
+    /// metrics like test-pass rate
+    #[serde(default = "default_lower_is_better")]
+    pub lower_is_better: bool,
     pub control_std: f64,
     pub fmm_std: f64,
+    /// Standard error of the control mean (std / sqrt(n)); 0 when n <= 1
+    #[serde(default)]
+    pub control_sem: f64,
+    /// Standard error of the FMM mean; 0 when n <= 1
+    #[serde(default)]
+    pub fmm_sem: f64,
     pub p_value: Option<f64>,
 }
 
+fn default_lower_is_better() -> bool {
+    true
+}
+
+impl Default for PairedMetric {
+    fn default() -> Self {
+        Self {
+            control_mean: 0.0,
+            fmm_mean: 0.0,
+            delta_pct: 0.0,
+            lower_is_better: true,
+            control_std: 0.0,
+            fmm_std: 0.0,
+            control_sem: 0.0,
+            fmm_sem: 0.0,
+            p_value: None,
+        }
+    }
+}
+
+impl PairedMetric {
+    /// What a positive `delta_pct` means for this metric.
+    pub fn delta_label(&self) -> &'static str {
+        if self.lower_is_better {
+            "reduction"
+        } else {
+            "improvement"
+        }
+    }
+}
+
+/// An attempted issue that produced no report, and why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedIssue {
+    pub id: String,
+    pub reason: String,
+}
+
+/// Projected cost of finishing a batch that stopped on its budget.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostProjection {
+    /// Filtered corpus issues never attempted
+    pub remaining_issues: usize,
+    /// Mean cost of the issues completed so far
+    pub mean_issue_cost: f64,
+    pub estimated_cost: f64,
+}
+
+impl CostProjection {
+    /// Extrapolate `spent` over `completed` issues to `remaining` ones.
+    ///
+    /// None when nothing remains or nothing completed to average over.
+    pub fn from_spend(spent: f64, completed: usize, remaining: usize) -> Option<Self> {
+        if remaining == 0 || completed == 0 {
+            return None;
+        }
+        let mean_issue_cost = spent / completed as f64;
+        Some(Self {
+            remaining_issues: remaining,
+            mean_issue_cost,
+            estimated_cost: mean_issue_cost * remaining as f64,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "Estimated ${:.2} to complete the remaining {} issues.",
+            self.estimated_cost, self.remaining_issues
+        )
+    }
+}
+
+/// What a job actually spent on fresh runs: every run counts once (repeats
+/// aren't averaged away) and cache hits don't count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spend {
+    pub cost_usd: f64,
+    pub tool_calls: u64,
+}
+
+impl Spend {
+    /// Count one fresh run.
+    pub fn add_run(&mut self, run: &RunResult) {
+        self.cost_usd += run.total_cost_usd;
+        self.tool_calls += u64::from(run.tool_calls);
+    }
+
+    /// Sum two spends; unknown (reports saved before spend was tracked) if
+    /// either is.
+    fn combine(a: Option<Spend>, b: Option<Spend>) -> Option<Spend> {
+        let (a, b) = (a?, b?);
+        Some(Spend {
+            cost_usd: a.cost_usd + b.cost_usd,
+            tool_calls: a.tool_calls + b.tool_calls,
+        })
+    }
+
+    /// Spend per wall-clock hour/minute.
+    pub fn rate(&self, wall_ms: u64) -> Option<SpendRate> {
+        SpendRate::from_totals(self.cost_usd, self.tool_calls, wall_ms)
+    }
+}
+
+/// Spend and tool-call throughput over a run's wall-clock time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendRate {
+    pub cost_per_hour: f64,
+    pub tool_calls_per_minute: f64,
+}
+
+impl SpendRate {
+    /// None when no wall-clock time was recorded (e.g. reports saved before
+    /// it was tracked).
+    pub fn from_totals(cost: f64, tool_calls: u64, wall_ms: u64) -> Option<Self> {
+        if wall_ms == 0 {
+            return None;
+        }
+        let minutes = wall_ms as f64 / 60_000.0;
+        Some(Self {
+            cost_per_hour: cost / (minutes / 60.0),
+            tool_calls_per_minute: tool_calls as f64 / minutes,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "${:.2}/hour, {:.1} tool calls/minute",
+            self.cost_per_hour, self.tool_calls_per_minute
+        )
+    }
+}
+
+/// How one value moved between two aggregate reports.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValueDelta {
+    pub before: f64,
+    pub after: f64,
+    /// `after - before`
+    pub change: f64,
+}
+
+impl ValueDelta {
+    fn new(before: f64, after: f64) -> Self {
+        Self {
+            before,
+            after,
+            change: after - before,
+        }
+    }
+}
+
+/// How a paired metric's means (and FMM's delta) moved between two aggregates.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub control_mean: ValueDelta,
+    pub fmm_mean: ValueDelta,
+    /// FMM's delta percentage (positive favors FMM in both reports)
+    pub delta_pct: ValueDelta,
+}
+
+impl MetricDelta {
+    fn new(before: &PairedMetric, after: &PairedMetric) -> Self {
+        Self {
+            control_mean: ValueDelta::new(before.control_mean, after.control_mean),
+            fmm_mean: ValueDelta::new(before.fmm_mean, after.fmm_mean),
+            delta_pct: ValueDelta::new(before.delta_pct, after.delta_pct),
+        }
+    }
+}
+
+/// Metric and win-rate deltas over a set of issues both aggregates share.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SummaryDelta {
+    /// Issues compared (present in both aggregates)
+    pub issues: usize,
+    pub tool_calls: MetricDelta,
+    pub tokens: MetricDelta,
+    pub cost: MetricDelta,
+    pub duration: MetricDelta,
+    pub read_calls: MetricDelta,
+    /// Fraction of per-issue rows where FMM used fewer tool calls
+    pub fmm_win_rate: ValueDelta,
+}
+
+/// An issue present in only one of two compared aggregates.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UnmatchedIssue {
+    pub language: String,
+    pub id: String,
+}
+
+/// Structured comparison of two aggregate reports (e.g. this week vs last),
+/// written by `aggregate-diff`.
+///
+/// Issues are matched by language and id; deltas cover only the matched
+/// issues so both sides measure the same work.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateDiff {
+    pub overall: SummaryDelta,
+    pub by_language: BTreeMap<String, SummaryDelta>,
+    /// Issues only the earlier aggregate has
+    pub only_before: Vec<UnmatchedIssue>,
+    /// Issues only the later aggregate has
+    pub only_after: Vec<UnmatchedIssue>,
+}
+
+impl AggregateDiff {
+    /// Compare `after` against `before`.
+    pub fn between(before: &AggregateReport, after: &AggregateReport) -> Self {
+        let keys = |agg: &AggregateReport| -> BTreeSet<UnmatchedIssue> {
+            agg.per_issue
+                .iter()
+                .map(|r| UnmatchedIssue {
+                    language: r.language.clone(),
+                    id: r.id.clone(),
+                })
+                .collect()
+        };
+        let before_keys = keys(before);
+        let after_keys = keys(after);
+        let shared = |agg: &AggregateReport, language: Option<&str>| -> Vec<IssueResult> {
+            agg.per_issue
+                .iter()
+                .filter(|r| language.is_none() || language == Some(r.language.as_str()))
+                .filter(|r| {
+                    let key = UnmatchedIssue {
+                        language: r.language.clone(),
+                        id: r.id.clone(),
+                    };
+                    before_keys.contains(&key) && after_keys.contains(&key)
+                })
+                .cloned()
+                .collect()
+        };
+
+        let languages: BTreeSet<&String> = before_keys
+            .intersection(&after_keys)
+            .map(|k| &k.language)
+            .collect();
+        let by_language = languages
+            .into_iter()
+            .map(|lang| {
+                let delta = summary_delta(
+                    &shared(before, Some(lang.as_str())),
+                    &shared(after, Some(lang.as_str())),
+                );
+                (lang.clone(), delta)
+            })
+            .collect();
+
+        Self {
+            overall: summary_delta(&shared(before, None), &shared(after, None)),
+            by_language,
+            only_before: before_keys.difference(&after_keys).cloned().collect(),
+            only_after: after_keys.difference(&before_keys).cloned().collect(),
+        }
+    }
+}
+
 /// Result for a single issue.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IssueResult {
     pub id: String,
     pub language: String,
@@ -66,6 +417,154 @@ pub struct IssueResult {
     pub control_grade: String,
     pub fmm_grade: String,
     pub delta_pct: f64,
+    /// Tokens, duration, and read calls are None in aggregates written
+    /// before they were recorded, so merged legacy rows don't count as zeros
+    #[serde(default)]
+    pub control_tokens: Option<f64>,
+    #[serde(default)]
+    pub fmm_tokens: Option<f64>,
+    #[serde(default)]
+    pub control_duration_ms: Option<f64>,
+    #[serde(default)]
+    pub fmm_duration_ms: Option<f64>,
+    #[serde(default)]
+    pub control_read_calls: Option<f64>,
+    #[serde(default)]
+    pub fmm_read_calls: Option<f64>,
+    /// Labels of the issue (for the by-label breakdown)
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Difficulty denominator for normalization (see `difficulty`)
+    #[serde(default)]
+    pub difficulty: f64,
+    /// Whether the FMM run read a sidecar or called an fmm MCP tool (None in
+    /// aggregates written before this was recorded)
+    #[serde(default)]
+    pub fmm_adopted: Option<bool>,
+}
+
+/// One histogram bin of per-issue tool-call reductions: `[lower, upper)` percent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeltaBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+}
+
+/// Default bin width (percentage points) for the delta histogram.
+pub const DELTA_BIN_WIDTH: f64 = 10.0;
+
+impl IssueResult {
+    /// Control tool calls per unit of difficulty.
+    pub fn control_tool_calls_normalized(&self) -> f64 {
+        normalize_by_difficulty(self.control_tool_calls, self.difficulty)
+    }
+
+    /// FMM tool calls per unit of difficulty.
+    pub fn fmm_tool_calls_normalized(&self) -> f64 {
+        normalize_by_difficulty(self.fmm_tool_calls, self.difficulty)
+    }
+}
+
+/// Difficulty of a corpus entry: its estimated files to touch, or — when
+/// that's unknown (0) — a weight from its complexity.
+pub fn difficulty(entry: &CorpusEntry) -> f64 {
+    if entry.estimated_files > 0 {
+        return entry.estimated_files as f64;
+    }
+    match entry.complexity.to_lowercase().as_str() {
+        "simple" => 1.0,
+        "complex" => 4.0,
+        _ => 2.0,
+    }
+}
+
+/// Divide a metric by difficulty; a non-positive difficulty leaves it raw.
+fn normalize_by_difficulty(value: f64, difficulty: f64) -> f64 {
+    if difficulty > 0.0 {
+        value / difficulty
+    } else {
+        value
+    }
+}
+
+/// Run-to-run spread of one metric across repeated runs of a single condition.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricSpread {
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Coefficient of variation (std_dev / mean); 0 when the mean is 0
+    pub cov: f64,
+}
+
+/// Intrinsic variance of the control condition (`--compare-baselines`).
+///
+/// An observed FMM delta smaller than the control's own CoV is within noise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineReport {
+    pub task_id: String,
+    /// Runs the spread covers (errored runs excluded)
+    pub runs: usize,
+    /// Runs that errored before doing any work (see `RunResult::is_errored`);
+    /// their zero metrics would inflate the spread
+    #[serde(default)]
+    pub errored: usize,
+    pub tool_calls: MetricSpread,
+    pub cost: MetricSpread,
+    pub total_cost: f64,
+}
+
+impl BaselineReport {
+    /// Summarise repeated control runs of the same task. Errored runs are
+    /// counted and their cost kept in `total_cost`, but not in the spread.
+    pub fn from_runs(task_id: &str, runs: &[RunResult]) -> Self {
+        let (errored, ok): (Vec<&RunResult>, Vec<&RunResult>) =
+            runs.iter().partition(|r| r.is_errored());
+        let tools: Vec<f64> = ok.iter().map(|r| r.tool_calls as f64).collect();
+        let cost: Vec<f64> = ok.iter().map(|r| r.total_cost_usd).collect();
+        Self {
+            task_id: task_id.to_string(),
+            runs: ok.len(),
+            errored: errored.len(),
+            tool_calls: metric_spread(&tools),
+            cost: metric_spread(&cost),
+            total_cost: runs.iter().map(|r| r.total_cost_usd).sum(),
+        }
+    }
+
+    /// Generate markdown summary of the baseline variance.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        md.push_str(&format!("# Control Baseline: {}\n\n", self.task_id));
+        md.push_str(&format!(
+            "**Runs:** {} | **Total cost:** ${:.2}\n\n",
+            self.runs, self.total_cost
+        ));
+        if self.errored > 0 {
+            md.push_str(&format!(
+                "*{} errored run(s) left out of the spread.*\n\n",
+                self.errored
+            ));
+        }
+        md.push_str("| Metric | Mean | Std | CoV |\n");
+        md.push_str("|--------|------|-----|-----|\n");
+        md.push_str(&format!(
+            "| Tool calls | {:.1} | {:.1} | {:.1}% |\n",
+            self.tool_calls.mean,
+            self.tool_calls.std_dev,
+            self.tool_calls.cov * 100.0
+        ));
+        md.push_str(&format!(
+            "| Cost | ${:.4} | ${:.4} | {:.1}% |\n\n",
+            self.cost.mean,
+            self.cost.std_dev,
+            self.cost.cov * 100.0
+        ));
+        md.push_str(
+            "FMM deltas smaller than these CoVs are indistinguishable from run-to-run noise.\n",
+        );
+        md
+    }
 }
 
 impl AggregateReport {
@@ -79,46 +578,21 @@ impl AggregateReport {
         runs_per_issue: u32,
         issues_attempted: usize,
     ) -> Self {
-        let issues_total = issues_attempted;
-
-        let mut all_pairs: Vec<MetricPair> = vec![];
-        let mut by_lang: HashMap<String, Vec<MetricPair>> = HashMap::new();
-        let mut by_size: HashMap<String, Vec<MetricPair>> = HashMap::new();
         let mut per_issue: Vec<IssueResult> = vec![];
-        let mut total_cost = 0.0f64;
         let mut languages: Vec<String> = vec![];
+        let spent = reports
+            .iter()
+            .try_fold(Spend::default(), |acc, (_, report)| {
+                Spend::combine(Some(acc), report.spent)
+            });
 
         for (entry, report) in &reports {
             if !languages.contains(&entry.language) {
                 languages.push(entry.language.clone());
             }
 
-            for task in &report.task_results {
-                let pair = MetricPair {
-                    control_tools: task.control.tool_calls as f64,
-                    fmm_tools: task.fmm.tool_calls as f64,
-                    control_tokens: (task.control.input_tokens + task.control.output_tokens) as f64,
-                    fmm_tokens: (task.fmm.input_tokens + task.fmm.output_tokens) as f64,
-                    control_cost: task.control.total_cost_usd,
-                    fmm_cost: task.fmm.total_cost_usd,
-                    control_duration: task.control.duration_ms as f64,
-                    fmm_duration: task.fmm.duration_ms as f64,
-                    control_reads: task.control.read_calls as f64,
-                    fmm_reads: task.fmm.read_calls as f64,
-                };
-
-                total_cost += pair.control_cost + pair.fmm_cost;
-
-                all_pairs.push(pair.clone());
-                by_lang
-                    .entry(entry.language.clone())
-                    .or_default()
-                    .push(pair.clone());
-                by_size
-                    .entry(entry.size.clone())
-                    .or_default()
-                    .push(pair.clone());
-
+            // Tasks an issue's summary excluded stay out of the aggregate too
+            for task in report.summarized_tasks() {
                 let control_grade = task
                     .control_eval
                     .as_ref()
@@ -130,8 +604,10 @@ impl AggregateReport {
                     .map(|e| e.grade.clone())
                     .unwrap_or_else(|| "-".to_string());
 
-                let delta = if pair.control_tools > 0.0 {
-                    ((pair.control_tools - pair.fmm_tools) / pair.control_tools) * 100.0
+                let control_tools = task.control.tool_calls as f64;
+                let fmm_tools = task.fmm.tool_calls as f64;
+                let delta = if control_tools > 0.0 {
+                    ((control_tools - fmm_tools) / control_tools) * 100.0
                 } else {
                     0.0
                 };
@@ -140,45 +616,311 @@ impl AggregateReport {
                     id: entry.id.clone(),
                     language: entry.language.clone(),
                     size: entry.size.clone(),
-                    control_tool_calls: pair.control_tools,
-                    fmm_tool_calls: pair.fmm_tools,
-                    control_cost: pair.control_cost,
-                    fmm_cost: pair.fmm_cost,
+                    control_tool_calls: control_tools,
+                    fmm_tool_calls: fmm_tools,
+                    control_cost: task.control.total_cost_usd,
+                    fmm_cost: task.fmm.total_cost_usd,
                     control_grade,
                     fmm_grade,
                     delta_pct: delta,
+                    control_tokens: Some(
+                        (task.control.input_tokens + task.control.output_tokens) as f64,
+                    ),
+                    fmm_tokens: Some((task.fmm.input_tokens + task.fmm.output_tokens) as f64),
+                    control_duration_ms: Some(task.control.duration_ms as f64),
+                    fmm_duration_ms: Some(task.fmm.duration_ms as f64),
+                    control_read_calls: Some(task.control.read_calls as f64),
+                    fmm_read_calls: Some(task.fmm.read_calls as f64),
+                    labels: report.labels.clone(),
+                    difficulty: difficulty(entry),
+                    fmm_adopted: Some(
+                        task.fmm.fmm_usage.sidecars_read > 0
+                            || task.fmm.fmm_usage.mcp_tool_calls > 0,
+                    ),
                 });
             }
         }
 
-        let summary = compute_summary(&all_pairs);
-        let by_language: HashMap<String, MetricsSummary> = by_lang
-            .into_iter()
-            .map(|(k, v)| (k, compute_summary(&v)))
-            .collect();
-        let by_size_map: HashMap<String, MetricsSummary> = by_size
-            .into_iter()
-            .map(|(k, v)| (k, compute_summary(&v)))
-            .collect();
-
         languages.sort();
 
-        Self {
+        let mut aggregate = Self {
             model: model.to_string(),
             runs_per_issue,
-            issues_total,
+            issues_total: issues_attempted,
             issues_completed: reports.len(),
-            total_cost,
+            total_cost: 0.0,
             languages,
-            summary,
-            by_language,
-            by_size: by_size_map,
+            summary: MetricsSummary::default(),
+            by_language: HashMap::new(),
+            by_size: HashMap::new(),
+            by_label: HashMap::new(),
             per_issue,
+            fmm_wins: 0,
+            control_wins: 0,
+            ties: 0,
+            normalize: false,
+            normalized_tool_calls: None,
+            profile: None,
+            export_raw_pairs: false,
+            raw_pairs: vec![],
+            fmm_adoption_rate: None,
+            failures: vec![],
+            skipped: vec![],
+            cost_projection: None,
+            deadline_truncated: false,
+            wall_ms: 0,
+            spent,
+        };
+        aggregate.total_cost = aggregate
+            .per_issue
+            .iter()
+            .map(|r| r.control_cost + r.fmm_cost)
+            .sum();
+        aggregate.recompute();
+        aggregate
+    }
+
+    /// Issues from a prior run that should be re-run: no result at all, or
+    /// either condition graded F or left ungraded (evaluation errored).
+    /// Issues skipped for a thin body stay skipped.
+    pub fn failed_entries(&self, corpus: &[CorpusEntry]) -> Vec<CorpusEntry> {
+        corpus
+            .iter()
+            .filter(|entry| !self.skipped.iter().any(|s| s.id == entry.id))
+            .filter(|entry| {
+                let rows: Vec<&IssueResult> =
+                    self.per_issue.iter().filter(|r| r.id == entry.id).collect();
+                rows.is_empty()
+                    || rows.iter().any(|r| {
+                        [&r.control_grade, &r.fmm_grade]
+                            .iter()
+                            .any(|g| g.as_str() == "F" || g.as_str() == "-")
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Merge a re-run into this (prior) aggregate.
+    ///
+    /// Re-run issues replace their prior rows; summaries are recomputed over
+    /// the merged per-issue results.
+    pub fn merge(mut self, rerun: AggregateReport) -> AggregateReport {
+        let replaced: Vec<&str> = rerun.per_issue.iter().map(|r| r.id.as_str()).collect();
+        self.per_issue
+            .retain(|r| !replaced.contains(&r.id.as_str()));
+
+        // A re-run issue's latest outcome replaces any earlier failure
+        let latest = |id: &str| {
+            replaced.contains(&id)
+                || rerun.failures.iter().any(|r| r.id == id)
+                || rerun.skipped.iter().any(|r| r.id == id)
+        };
+        self.failures.retain(|f| !latest(&f.id));
+        self.skipped.retain(|s| !latest(&s.id));
+        self.failures.extend(rerun.failures);
+        self.skipped.extend(rerun.skipped);
+        // Only the latest batch knows what it left unattempted
+        self.cost_projection = rerun.cost_projection;
+        self.deadline_truncated = rerun.deadline_truncated;
+        self.per_issue.extend(rerun.per_issue);
+
+        self.total_cost += rerun.total_cost;
+        self.wall_ms += rerun.wall_ms;
+        self.spent = Spend::combine(self.spent, rerun.spent);
+        for lang in rerun.languages {
+            if !self.languages.contains(&lang) {
+                self.languages.push(lang);
+            }
         }
+        self.languages.sort();
+
+        let mut completed: Vec<&str> = self.per_issue.iter().map(|r| r.id.as_str()).collect();
+        completed.sort();
+        completed.dedup();
+        self.issues_completed = completed.len();
+
+        self.recompute();
+        self
+    }
+
+    /// Check that a batch with `model` and `runs_per_issue` can be appended:
+    /// mixing models or run counts would make the summary meaningless.
+    pub fn ensure_appendable(&self, model: &str, runs_per_issue: u32) -> Result<()> {
+        if self.model != model || self.runs_per_issue != runs_per_issue {
+            anyhow::bail!(
+                "Cannot append a {} x{} batch to a {} x{} aggregate",
+                model,
+                runs_per_issue,
+                self.model,
+                self.runs_per_issue
+            );
+        }
+        Ok(())
+    }
+
+    /// Append another batch (e.g. a different language slice) to this one.
+    ///
+    /// Like [`merge`](Self::merge), issues present in both take the newer
+    /// batch's rows, but the corpus sizes add up (overlapping issues counted
+    /// once) since the batches cover different corpora.
+    pub fn append(self, batch: AggregateReport) -> Result<AggregateReport> {
+        self.ensure_appendable(&batch.model, batch.runs_per_issue)?;
+        let mut overlap: Vec<&str> = batch
+            .per_issue
+            .iter()
+            .filter(|r| self.per_issue.iter().any(|p| p.id == r.id))
+            .map(|r| r.id.as_str())
+            .collect();
+        overlap.sort();
+        overlap.dedup();
+        let issues_total = (self.issues_total + batch.issues_total).saturating_sub(overlap.len());
+
+        let mut merged = self.merge(batch);
+        merged.issues_total = issues_total;
+        Ok(merged)
+    }
+
+    /// Enable the difficulty-normalized tool-call view.
+    pub fn normalized(mut self) -> Self {
+        self.normalize = true;
+        self.recompute();
+        self
+    }
+
+    /// Keep the paired samples behind the summary in the report.
+    pub fn with_raw_pairs(mut self) -> Self {
+        self.export_raw_pairs = true;
+        self.recompute();
+        self
+    }
+
+    /// Recompute summaries, breakdowns, and win tallies from `per_issue`.
+    fn recompute(&mut self) {
+        let mut by_lang: HashMap<String, Vec<MetricPair>> = HashMap::new();
+        let mut by_size: HashMap<String, Vec<MetricPair>> = HashMap::new();
+        let mut by_label: HashMap<String, Vec<MetricPair>> = HashMap::new();
+        let mut all_pairs: Vec<MetricPair> = vec![];
+
+        for r in &self.per_issue {
+            let pair = MetricPair::from(r);
+            all_pairs.push(pair.clone());
+            by_lang
+                .entry(r.language.clone())
+                .or_default()
+                .push(pair.clone());
+            by_size
+                .entry(r.size.clone())
+                .or_default()
+                .push(pair.clone());
+            for label in &r.labels {
+                by_label
+                    .entry(label.clone())
+                    .or_default()
+                    .push(pair.clone());
+            }
+        }
+
+        let summarize = |groups: HashMap<String, Vec<MetricPair>>| {
+            groups
+                .into_iter()
+                .map(|(k, v)| (k, compute_summary(&v)))
+                .collect()
+        };
+
+        self.summary = compute_summary(&all_pairs);
+        self.raw_pairs = if self.export_raw_pairs {
+            all_pairs
+        } else {
+            vec![]
+        };
+        self.by_language = summarize(by_lang);
+        self.by_size = summarize(by_size);
+        self.by_label = summarize(by_label);
+        (self.fmm_wins, self.control_wins, self.ties) = tally_wins(&self.per_issue);
+        self.fmm_adoption_rate = adoption_rate(&self.per_issue);
+
+        self.normalized_tool_calls = self.normalize.then(|| {
+            let control: Vec<f64> = self
+                .per_issue
+                .iter()
+                .map(|r| r.control_tool_calls_normalized())
+                .collect();
+            let fmm: Vec<f64> = self
+                .per_issue
+                .iter()
+                .map(|r| r.fmm_tool_calls_normalized())
+                .collect();
+            paired_metric(&control, &fmm)
+        });
+    }
+
+    /// Fresh-run cost and tool calls (see `Spend`) per wall-clock hour/minute
+    /// of batch time.
+    pub fn spend_rate(&self) -> Option<SpendRate> {
+        self.spent?.rate(self.wall_ms)
+    }
+
+    /// Headline win/loss/tie tally, e.g. "FMM won 7/10 issues (2 lost, 1 tied)".
+    pub fn win_line(&self) -> String {
+        format!(
+            "FMM won {}/{} issues ({} lost, {} tied)",
+            self.fmm_wins,
+            self.fmm_wins + self.control_wins + self.ties,
+            self.control_wins,
+            self.ties
+        )
+    }
+
+    /// Bucket per-issue `delta_pct` into `bin_width`-wide bins aligned to zero,
+    /// so regressions (negative deltas) never share a bin with improvements.
+    ///
+    /// Bins span the lowest to highest delta, empty bins included.
+    pub fn delta_histogram(&self, bin_width: f64) -> Vec<DeltaBin> {
+        if self.per_issue.is_empty() || bin_width <= 0.0 {
+            return vec![];
+        }
+        let bin_of = |d: f64| (d / bin_width).floor() as i64;
+        let (lo, hi) = self
+            .per_issue
+            .iter()
+            .map(|r| bin_of(r.delta_pct))
+            .fold((i64::MAX, i64::MIN), |(lo, hi), b| (lo.min(b), hi.max(b)));
+
+        let mut bins: Vec<DeltaBin> = (lo..=hi)
+            .map(|b| DeltaBin {
+                lower: b as f64 * bin_width,
+                upper: (b + 1) as f64 * bin_width,
+                count: 0,
+            })
+            .collect();
+        for r in &self.per_issue {
+            bins[(bin_of(r.delta_pct) - lo) as usize].count += 1;
+        }
+        bins
+    }
+
+    /// The delta histogram as CSV (`lower_pct,upper_pct,count`).
+    pub fn delta_histogram_csv(&self, bin_width: f64) -> String {
+        let mut csv = String::from("lower_pct,upper_pct,count\n");
+        for bin in self.delta_histogram(bin_width) {
+            csv.push_str(&format!("{},{},{}\n", bin.lower, bin.upper, bin.count));
+        }
+        csv
     }
 
     /// Render as markdown.
     pub fn to_markdown(&self) -> String {
+        self.to_markdown_for_languages(&[])
+    }
+
+    /// Render as markdown with the language and per-issue breakdowns limited
+    /// to `languages` (case-insensitive; empty shows all). The summary still
+    /// covers the full corpus.
+    pub fn to_markdown_for_languages(&self, languages: &[String]) -> String {
+        let shown = |lang: &str| {
+            languages.is_empty() || languages.iter().any(|l| l.eq_ignore_ascii_case(lang))
+        };
         let mut md = String::new();
 
         md.push_str("# fmm A/B Benchmark Results\n\n");
@@ -192,24 +934,58 @@ impl AggregateReport {
             self.model, self.runs_per_issue
         ));
         md.push_str(&format!("**Total cost:** ${:.2}\n\n", self.total_cost));
+        if !self.failures.is_empty() {
+            md.push_str(&format!(
+                "**Completed:** {} / {} issues ({} failed, see Failures)\n\n",
+                self.issues_completed,
+                self.issues_total,
+                self.failures.len()
+            ));
+        }
+        if self.deadline_truncated {
+            md.push_str("*Stopped at the wall-clock deadline (`--max-wall-secs`); results are partial.*\n\n");
+        }
+        if let Some(ref projection) = self.cost_projection {
+            md.push_str(&format!("**{}**\n\n", projection.describe()));
+        }
+        md.push_str(&format!("**{}**\n\n", self.win_line()));
+        if let Some(rate) = self.fmm_adoption_rate {
+            md.push_str(&format!(
+                "**FMM treatment adopted in {:.0}% of runs**\n\n",
+                rate * 100.0
+            ));
+        }
 
         // Summary table
         md.push_str("## Summary\n\n");
-        md.push_str("| Metric | Control (avg) | FMM (avg) | Delta | p-value |\n");
-        md.push_str("|--------|--------------|-----------|-------|---------|\n");
+        md.push_str("| Metric | Control (avg ± SEM) | FMM (avg ± SEM) | Delta | p-value |\n");
+        md.push_str("|--------|--------------------|-----------------|-------|---------|\n");
         format_metric_row(&mut md, "Tool calls", &self.summary.tool_calls, false);
         format_metric_row(&mut md, "Tokens (k)", &self.summary.tokens, true);
         format_metric_row(&mut md, "Cost ($)", &self.summary.cost, false);
         format_metric_row(&mut md, "Duration (ms)", &self.summary.duration, false);
         format_metric_row(&mut md, "Read calls", &self.summary.read_calls, false);
+        if let Some(ref normalized) = self.normalized_tool_calls {
+            format_metric_row(&mut md, "Tool calls / difficulty", normalized, false);
+        }
         md.push('\n');
+        if !languages.is_empty() {
+            md.push_str(&format!(
+                "*Breakdowns below limited to: {}.*\n\n",
+                languages.join(", ")
+            ));
+        }
 
         // By language
         if !self.by_language.is_empty() {
             md.push_str("## By Language\n\n");
             md.push_str("| Language | N | Ctrl Tools | FMM Tools | Delta |\n");
             md.push_str("|----------|---|-----------|-----------|-------|\n");
-            let mut langs: Vec<_> = self.by_language.iter().collect();
+            let mut langs: Vec<_> = self
+                .by_language
+                .iter()
+                .filter(|(lang, _)| shown(lang))
+                .collect();
             langs.sort_by_key(|(k, _)| (*k).clone());
             for (lang, s) in &langs {
                 md.push_str(&format!(
@@ -244,6 +1020,62 @@ impl AggregateReport {
             md.push('\n');
         }
 
+        // By label
+        if !self.by_label.is_empty() {
+            md.push_str("## By Label\n\n");
+            md.push_str("| Label | N | Ctrl Tools | FMM Tools | Delta |\n");
+            md.push_str("|-------|---|-----------|-----------|-------|\n");
+            let mut labels: Vec<_> = self.by_label.iter().collect();
+            labels.sort_by_key(|(k, _)| (*k).clone());
+            for (label, s) in &labels {
+                md.push_str(&format!(
+                    "| {} | {} | {:.1} | {:.1} | {:.1}% |\n",
+                    label,
+                    s.n,
+                    s.tool_calls.control_mean,
+                    s.tool_calls.fmm_mean,
+                    s.tool_calls.delta_pct
+                ));
+            }
+            md.push('\n');
+        }
+
+        // Normalized per-issue view
+        if self.normalize {
+            md.push_str("## Normalized by Difficulty\n\n");
+            md.push_str("Tool calls per estimated file (or complexity weight when unknown).\n\n");
+            md.push_str("| Issue | Difficulty | Ctrl / Diff | FMM / Diff |\n");
+            md.push_str("|-------|------------|-------------|------------|\n");
+            for r in self.per_issue.iter().filter(|r| shown(&r.language)) {
+                md.push_str(&format!(
+                    "| {} | {:.0} | {:.1} | {:.1} |\n",
+                    r.id,
+                    r.difficulty,
+                    r.control_tool_calls_normalized(),
+                    r.fmm_tool_calls_normalized()
+                ));
+            }
+            md.push('\n');
+        }
+
+        // Distribution of per-issue deltas
+        let bins = self.delta_histogram(DELTA_BIN_WIDTH);
+        if !bins.is_empty() {
+            md.push_str("## Tool Call Reduction Distribution\n\n");
+            md.push_str("Issues per reduction bin (negative = FMM used more tool calls).\n\n");
+            md.push_str("```\n");
+            for bin in &bins {
+                md.push_str(&format!(
+                    "[{:>5.0}%, {:>5.0}%) {:>3} {}\n",
+                    bin.lower,
+                    bin.upper,
+                    bin.count,
+                    "#".repeat(bin.count)
+                ));
+            }
+            md.push_str("```\n\n");
+        }
+
         // Per-issue results
         md.push_str("## Per-Issue Results\n\n");
         md.push_str(
@@ -252,7 +1084,7 @@ impl AggregateReport {
         md.push_str(
             "|-------|----------|-----------|-----------|-------|------------|----------|\n",
         );
-        for r in &self.per_issue {
+        for r in self.per_issue.iter().filter(|r| shown(&r.language)) {
             md.push_str(&format!(
                 "| {} | {} | {:.0} | {:.0} | {:.1}% | {} | {} |\n",
                 r.id,
@@ -265,24 +1097,53 @@ impl AggregateReport {
             ));
         }
 
+        if !self.failures.is_empty() {
+            md.push_str("\n## Failures\n\n");
+            md.push_str("| Issue | Reason |\n");
+            md.push_str("|-------|--------|\n");
+            for f in &self.failures {
+                md.push_str(&format!(
+                    "| {} | {} |\n",
+                    f.id,
+                    f.reason.replace('|', "\\|").replace('\n', " ")
+                ));
+            }
+        }
+
+        if !self.skipped.is_empty() {
+            md.push_str("\n## Skipped\n\n");
+            md.push_str("| Issue | Reason |\n");
+            md.push_str("|-------|--------|\n");
+            for s in &self.skipped {
+                md.push_str(&format!(
+                    "| {} | {} |\n",
+                    s.id,
+                    s.reason.replace('|', "\\|").replace('\n', " ")
+                ));
+            }
+        }
+
         md
     }
 }
 
 // ── internal ────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
-struct MetricPair {
-    control_tools: f64,
-    fmm_tools: f64,
-    control_tokens: f64,
-    fmm_tokens: f64,
-    control_cost: f64,
-    fmm_cost: f64,
-    control_duration: f64,
-    fmm_duration: f64,
-    control_reads: f64,
-    fmm_reads: f64,
+impl From<&IssueResult> for MetricPair {
+    fn from(r: &IssueResult) -> Self {
+        Self {
+            control_tools: r.control_tool_calls,
+            fmm_tools: r.fmm_tool_calls,
+            control_tokens: r.control_tokens,
+            fmm_tokens: r.fmm_tokens,
+            control_cost: r.control_cost,
+            fmm_cost: r.fmm_cost,
+            control_duration: r.control_duration_ms,
+            fmm_duration: r.fmm_duration_ms,
+            control_reads: r.control_read_calls,
+            fmm_reads: r.fmm_read_calls,
+        }
+    }
 }
 
 fn compute_summary(pairs: &[MetricPair]) -> MetricsSummary {
@@ -293,14 +1154,21 @@ fn compute_summary(pairs: &[MetricPair]) -> MetricsSummary {
     let n = pairs.len();
     let ctrl_tools: Vec<f64> = pairs.iter().map(|p| p.control_tools).collect();
     let fmm_tools: Vec<f64> = pairs.iter().map(|p| p.fmm_tools).collect();
-    let ctrl_tokens: Vec<f64> = pairs.iter().map(|p| p.control_tokens).collect();
-    let fmm_tokens: Vec<f64> = pairs.iter().map(|p| p.fmm_tokens).collect();
     let ctrl_cost: Vec<f64> = pairs.iter().map(|p| p.control_cost).collect();
     let fmm_cost: Vec<f64> = pairs.iter().map(|p| p.fmm_cost).collect();
-    let ctrl_dur: Vec<f64> = pairs.iter().map(|p| p.control_duration).collect();
-    let fmm_dur: Vec<f64> = pairs.iter().map(|p| p.fmm_duration).collect();
-    let ctrl_reads: Vec<f64> = pairs.iter().map(|p| p.control_reads).collect();
-    let fmm_reads: Vec<f64> = pairs.iter().map(|p| p.fmm_reads).collect();
+    // Legacy rows lack these; skip them rather than average in zeros
+    let recorded = |field: fn(&MetricPair) -> (Option<f64>, Option<f64>)| -> (Vec<f64>, Vec<f64>) {
+        pairs
+            .iter()
+            .filter_map(|p| {
+                let (control, fmm) = field(p);
+                control.zip(fmm)
+            })
+            .unzip()
+    };
+    let (ctrl_tokens, fmm_tokens) = recorded(|p| (p.control_tokens, p.fmm_tokens));
+    let (ctrl_dur, fmm_dur) = recorded(|p| (p.control_duration, p.fmm_duration));
+    let (ctrl_reads, fmm_reads) = recorded(|p| (p.control_reads, p.fmm_reads));
 
     MetricsSummary {
         n,
@@ -312,11 +1180,21 @@ fn compute_summary(pairs: &[MetricPair]) -> MetricsSummary {
     }
 }
 
+/// Paired metric for a lower-is-better quantity (tool calls, cost, ...).
 fn paired_metric(control: &[f64], fmm: &[f64]) -> PairedMetric {
+    paired_metric_directed(control, fmm, true)
+}
+
+fn paired_metric_directed(control: &[f64], fmm: &[f64], lower_is_better: bool) -> PairedMetric {
     let c_mean = mean(control);
     let f_mean = mean(fmm);
     let delta = if c_mean > 0.0 {
-        ((c_mean - f_mean) / c_mean) * 100.0
+        let gain = if lower_is_better {
+            c_mean - f_mean
+        } else {
+            f_mean - c_mean
+        };
+        (gain / c_mean) * 100.0
     } else {
         0.0
     };
@@ -331,12 +1209,90 @@ fn paired_metric(control: &[f64], fmm: &[f64]) -> PairedMetric {
         control_mean: c_mean,
         fmm_mean: f_mean,
         delta_pct: delta,
+        lower_is_better,
         control_std: std_dev(control),
         fmm_std: std_dev(fmm),
+        control_sem: std_err(control),
+        fmm_sem: std_err(fmm),
         p_value,
     }
 }
 
+/// Deltas between two sets of per-issue rows covering the same issues.
+fn summary_delta(before: &[IssueResult], after: &[IssueResult]) -> SummaryDelta {
+    let summarize = |rows: &[IssueResult]| {
+        let pairs: Vec<MetricPair> = rows.iter().map(MetricPair::from).collect();
+        compute_summary(&pairs)
+    };
+    let win_rate = |rows: &[IssueResult]| {
+        let (fmm, control, ties) = tally_wins(rows);
+        let issues = fmm + control + ties;
+        if issues == 0 {
+            return 0.0;
+        }
+        fmm as f64 / issues as f64
+    };
+    let issues: BTreeSet<(&str, &str)> = before
+        .iter()
+        .map(|r| (r.language.as_str(), r.id.as_str()))
+        .collect();
+    let (b, a) = (summarize(before), summarize(after));
+    SummaryDelta {
+        issues: issues.len(),
+        tool_calls: MetricDelta::new(&b.tool_calls, &a.tool_calls),
+        tokens: MetricDelta::new(&b.tokens, &a.tokens),
+        cost: MetricDelta::new(&b.cost, &a.cost),
+        duration: MetricDelta::new(&b.duration, &a.duration),
+        read_calls: MetricDelta::new(&b.read_calls, &a.read_calls),
+        fmm_win_rate: ValueDelta::new(win_rate(before), win_rate(after)),
+    }
+}
+
+/// Fraction of rows whose FMM run used fmm at all, over rows that record it.
+fn adoption_rate(per_issue: &[IssueResult]) -> Option<f64> {
+    let recorded: Vec<bool> = per_issue.iter().filter_map(|r| r.fmm_adopted).collect();
+    if recorded.is_empty() {
+        return None;
+    }
+    let adopted = recorded.iter().filter(|&&a| a).count();
+    Some(adopted as f64 / recorded.len() as f64)
+}
+
+/// Count FMM wins / control wins / ties, one outcome per issue: the tool
+/// calls of an issue's rows (one per task when it ran a task set) are summed
+/// and the condition with fewer wins. Compared directly rather than by
+/// `delta_pct`, which is 0 whenever control made no tool calls.
+fn tally_wins(per_issue: &[IssueResult]) -> (u32, u32, u32) {
+    let mut by_issue: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for r in per_issue {
+        let calls = by_issue.entry(&r.id).or_default();
+        calls.0 += r.control_tool_calls;
+        calls.1 += r.fmm_tool_calls;
+    }
+    by_issue.values().fold(
+        (0, 0, 0),
+        |(fmm, control, ties), &(control_calls, fmm_calls)| {
+            if fmm_calls < control_calls {
+                (fmm + 1, control, ties)
+            } else if fmm_calls > control_calls {
+                (fmm, control + 1, ties)
+            } else {
+                (fmm, control, ties + 1)
+            }
+        },
+    )
+}
+
+pub(crate) fn metric_spread(xs: &[f64]) -> MetricSpread {
+    let m = mean(xs);
+    let sd = std_dev(xs);
+    MetricSpread {
+        mean: m,
+        std_dev: sd,
+        cov: if m > 0.0 { sd / m } else { 0.0 },
+    }
+}
+
 fn mean(xs: &[f64]) -> f64 {
     if xs.is_empty() {
         return 0.0;
@@ -357,6 +1313,14 @@ fn std_dev(xs: &[f64]) -> f64 {
     variance(xs).sqrt()
 }
 
+/// Standard error of the mean; 0 for fewer than two samples.
+fn std_err(xs: &[f64]) -> f64 {
+    if xs.len() < 2 {
+        return 0.0;
+    }
+    std_dev(xs) / (xs.len() as f64).sqrt()
+}
+
 /// Two-sample Welch's t-test. Returns approximate p-value.
 fn welch_t_test(a: &[f64], b: &[f64]) -> f64 {
     let n_a = a.len() as f64;
@@ -503,11 +1467,16 @@ fn ln_gamma(x: f64) -> f64 {
 }
 
 fn format_metric_row(md: &mut String, label: &str, m: &PairedMetric, divide_1k: bool) {
-    let (ctrl, fmm) = if divide_1k {
-        (m.control_mean / 1000.0, m.fmm_mean / 1000.0)
-    } else {
-        (m.control_mean, m.fmm_mean)
+    let scale = if divide_1k { 1000.0 } else { 1.0 };
+    let mean_sem = |mean: f64, sem: f64| {
+        if sem > 0.0 {
+            format!("{:.1} ± {:.1}", mean / scale, sem / scale)
+        } else {
+            format!("{:.1}", mean / scale)
+        }
     };
+    let ctrl = mean_sem(m.control_mean, m.control_sem);
+    let fmm = mean_sem(m.fmm_mean, m.fmm_sem);
 
     let p_str = match m.p_value {
         Some(p) if p < 0.001 => "<0.001".to_string(),
@@ -516,14 +1485,21 @@ fn format_metric_row(md: &mut String, label: &str, m: &PairedMetric, divide_1k:
     };
 
     md.push_str(&format!(
-        "| {} | {:.1} | {:.1} | {:.1}% | {} |\n",
-        label, ctrl, fmm, m.delta_pct, p_str
+        "| {} | {} | {} | {:.1}% {} | {} |\n",
+        label,
+        ctrl,
+        fmm,
+        m.delta_pct,
+        m.delta_label(),
+        p_str
     ));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::report::test_report;
+    use crate::tasks::test_task;
 
     #[test]
     fn test_mean() {
@@ -539,6 +1515,108 @@ mod tests {
         assert!((sd - 2.138).abs() < 0.01);
     }
 
+    #[test]
+    fn test_sem_is_std_over_sqrt_n() {
+        let vals = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert!((std_err(&vals) - std_dev(&vals) / 8f64.sqrt()).abs() < 1e-12);
+        // sqrt(32/7) / sqrt(8) ≈ 0.756
+        assert!((std_err(&vals) - 0.756).abs() < 0.001);
+        assert_eq!(std_err(&[3.0]), 0.0);
+        assert_eq!(std_err(&[]), 0.0);
+
+        let m = paired_metric(&vals, &[1.0, 3.0]);
+        assert!((m.control_sem - std_err(&vals)).abs() < 1e-12);
+        assert!((m.fmm_sem - 1.0).abs() < 1e-12);
+
+        let mut md = String::new();
+        format_metric_row(&mut md, "Tool calls", &m, false);
+        assert!(md.contains("| 5.0 ± 0.8 | 2.0 ± 1.0 |"), "{}", md);
+    }
+
+    #[test]
+    fn test_higher_is_better_metric_delta() {
+        // Pass rate up from 50% to 60%: a positive improvement for FMM
+        let m = paired_metric_directed(&[0.5, 0.5], &[0.6, 0.6], false);
+        assert!((m.delta_pct - 20.0).abs() < 1e-9);
+        assert_eq!(m.delta_label(), "improvement");
+        let mut md = String::new();
+        format_metric_row(&mut md, "Pass rate", &m, false);
+        assert!(md.contains("| 20.0% improvement |"), "{}", md);
+
+        // Going down is a regression, not a reduction
+        let m = paired_metric_directed(&[0.6, 0.6], &[0.5, 0.5], false);
+        assert!(m.delta_pct < 0.0);
+
+        // Lower-is-better metrics keep the reduction framing
+        let m = paired_metric(&[10.0, 10.0], &[5.0, 5.0]);
+        assert!((m.delta_pct - 50.0).abs() < 1e-9);
+        assert_eq!(m.delta_label(), "reduction");
+
+        // Metrics saved before the flag existed read as lower-is-better
+        let legacy: PairedMetric = serde_json::from_str(
+            r#"{"control_mean":1,"fmm_mean":1,"delta_pct":0,"control_std":0,"fmm_std":0,"p_value":null}"#,
+        )
+        .unwrap();
+        assert!(legacy.lower_is_better);
+    }
+
+    #[test]
+    fn test_baseline_cov() {
+        let runs: Vec<RunResult> = [(10, 0.10), (12, 0.12), (14, 0.14)]
+            .iter()
+            .map(|&(tools, cost)| RunResult {
+                tool_calls: tools,
+                total_cost_usd: cost,
+                ..RunResult::from_metrics(
+                    crate::metrics::RunMetrics::default(),
+                    String::new(),
+                    "t",
+                    "control",
+                )
+            })
+            .collect();
+
+        let baseline = BaselineReport::from_runs("t", &runs);
+        assert_eq!(baseline.runs, 3);
+        // mean 12, sample std 2 -> CoV 1/6
+        assert!((baseline.tool_calls.mean - 12.0).abs() < 1e-10);
+        assert!((baseline.tool_calls.std_dev - 2.0).abs() < 1e-10);
+        assert!((baseline.tool_calls.cov - 2.0 / 12.0).abs() < 1e-10);
+        // Cost scales identically, so the CoV matches
+        assert!((baseline.cost.cov - baseline.tool_calls.cov).abs() < 1e-9);
+        assert!((baseline.total_cost - 0.36).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_baseline_excludes_errored_runs() {
+        let run = |tools: u32, success: bool| RunResult {
+            tool_calls: tools,
+            total_cost_usd: 0.01 * tools as f64,
+            success,
+            ..RunResult::from_metrics(
+                crate::metrics::RunMetrics::default(),
+                String::new(),
+                "t",
+                "control",
+            )
+        };
+        let runs = [run(10, true), run(0, false), run(14, true)];
+        assert!(runs[1].is_errored());
+
+        let baseline = BaselineReport::from_runs("t", &runs);
+        assert_eq!(baseline.runs, 2);
+        assert_eq!(baseline.errored, 1);
+        assert!((baseline.tool_calls.mean - 12.0).abs() < 1e-10);
+        assert!(baseline.to_markdown().contains("1 errored run(s)"));
+    }
+
+    #[test]
+    fn test_baseline_cov_zero_mean() {
+        let baseline = BaselineReport::from_runs("t", &[]);
+        assert_eq!(baseline.runs, 0);
+        assert_eq!(baseline.tool_calls.cov, 0.0);
+    }
+
     #[test]
     fn test_variance_single_element() {
         assert_eq!(variance(&[5.0]), 0.0);
@@ -587,6 +1665,570 @@ mod tests {
         assert!(m.p_value.is_none());
     }
 
+    #[test]
+    fn test_by_label_breakdown() {
+        let run = |variant: &str, tools: u32| RunResult {
+            tool_calls: tools,
+            ..RunResult::from_metrics(
+                crate::metrics::RunMetrics::default(),
+                "done".to_string(),
+                "issue-1",
+                variant,
+            )
+        };
+        let task = test_task("issue-1");
+        let mut report = test_report(vec![(task, run("control", 10), run("fmm", 5), None, None)]);
+        report.labels = vec!["bug".to_string(), "p1".to_string()];
+        let entry: CorpusEntry =
+            serde_json::from_str(r#"{"id":"o/r#1","repo":"o/r","issue":1,"language":"rust"}"#)
+                .unwrap();
+
+        let agg = AggregateReport::from_reports(vec![(entry, report)], "sonnet", 1, 1);
+        assert_eq!(agg.by_label.len(), 2);
+        assert_eq!(agg.by_label["bug"].n, 1);
+        assert!((agg.by_label["p1"].tool_calls.delta_pct - 50.0).abs() < 1e-10);
+        assert!(agg.to_markdown().contains("## By Label"));
+    }
+
+    #[test]
+    fn test_cost_projection_for_half_completed_batch() {
+        // 3 of 6 issues done for $12.60 before the budget stopped the batch
+        let projection = CostProjection::from_spend(12.60, 3, 3).unwrap();
+        assert!((projection.mean_issue_cost - 4.20).abs() < 1e-9);
+        assert!((projection.estimated_cost - 12.60).abs() < 1e-9);
+        assert_eq!(
+            projection.describe(),
+            "Estimated $12.60 to complete the remaining 3 issues."
+        );
+
+        // Finished batch, or nothing to average over
+        assert_eq!(CostProjection::from_spend(12.60, 6, 0), None);
+        assert_eq!(CostProjection::from_spend(0.0, 0, 6), None);
+
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 6);
+        agg.cost_projection = Some(projection);
+        assert!(agg
+            .to_markdown()
+            .contains("Estimated $12.60 to complete the remaining 3 issues."));
+    }
+
+    #[test]
+    fn test_spend_rate_over_wall_clock() {
+        // $12 and 90 tool calls over 30 minutes
+        let rate = SpendRate::from_totals(12.0, 90, 30 * 60_000).unwrap();
+        assert!((rate.cost_per_hour - 24.0).abs() < 1e-9);
+        assert!((rate.tool_calls_per_minute - 3.0).abs() < 1e-9);
+        assert_eq!(rate.describe(), "$24.00/hour, 3.0 tool calls/minute");
+
+        // Reports saved before wall time was tracked
+        assert_eq!(SpendRate::from_totals(12.0, 90, 0), None);
+
+        // Merged re-runs add their wall time and spend
+        let spend = |cost_usd: f64, tool_calls: u64| {
+            Some(Spend {
+                cost_usd,
+                tool_calls,
+            })
+        };
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        agg.spent = spend(6.0, 30);
+        agg.wall_ms = 10 * 60_000;
+        let mut rerun = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        rerun.spent = spend(6.0, 60);
+        rerun.wall_ms = 20 * 60_000;
+        let merged = agg.merge(rerun);
+        assert_eq!(merged.wall_ms, 30 * 60_000);
+        let rate = merged.spend_rate().unwrap();
+        assert!((rate.cost_per_hour - 24.0).abs() < 1e-9);
+        assert!((rate.tool_calls_per_minute - 3.0).abs() < 1e-9);
+
+        // A merged report from before spend tracking leaves the rate unknown
+        let mut legacy = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        legacy.spent = None;
+        legacy.wall_ms = 60_000;
+        assert_eq!(merged.merge(legacy).spend_rate(), None);
+    }
+
+    #[test]
+    fn test_fmm_adoption_rate() {
+        let run = |variant: &str, sidecars: u32, mcp: u32| {
+            let mut r = RunResult::from_metrics(
+                crate::metrics::RunMetrics::default(),
+                "done".to_string(),
+                "issue-1",
+                variant,
+            );
+            r.fmm_usage.sidecars_read = sidecars;
+            r.fmm_usage.mcp_tool_calls = mcp;
+            r
+        };
+        let report = test_report(vec![
+            (
+                test_task("a"),
+                run("control", 0, 0),
+                run("fmm", 3, 0),
+                None,
+                None,
+            ),
+            (
+                test_task("b"),
+                run("control", 0, 0),
+                run("fmm", 0, 2),
+                None,
+                None,
+            ),
+            (
+                test_task("c"),
+                run("control", 0, 0),
+                run("fmm", 0, 0),
+                None,
+                None,
+            ),
+            (
+                test_task("d"),
+                run("control", 0, 0),
+                run("fmm", 0, 0),
+                None,
+                None,
+            ),
+        ]);
+        let entry: CorpusEntry =
+            serde_json::from_str(r#"{"id":"o/r#1","repo":"o/r","issue":1,"language":"rust"}"#)
+                .unwrap();
+
+        let agg = AggregateReport::from_reports(vec![(entry, report)], "sonnet", 1, 1);
+        assert_eq!(agg.fmm_adoption_rate, Some(0.5));
+        assert!(agg
+            .to_markdown()
+            .contains("FMM treatment adopted in 50% of runs"));
+
+        // Rows from older aggregates carry no adoption flag
+        let mut legacy = agg.clone();
+        legacy
+            .per_issue
+            .iter_mut()
+            .for_each(|r| r.fmm_adopted = None);
+        assert_eq!(adoption_rate(&legacy.per_issue), None);
+    }
+
+    #[test]
+    fn test_win_tally_mixed_deltas() {
+        let issue = |id: &str, control: f64, fmm: f64| IssueResult {
+            id: id.to_string(),
+            language: "rust".to_string(),
+            size: "medium".to_string(),
+            control_tool_calls: control,
+            fmm_tool_calls: fmm,
+            control_cost: 0.0,
+            fmm_cost: 0.0,
+            control_grade: "-".to_string(),
+            fmm_grade: "-".to_string(),
+            delta_pct: if control > 0.0 {
+                (control - fmm) / control * 100.0
+            } else {
+                0.0
+            },
+            ..Default::default()
+        };
+        let per_issue = vec![
+            issue("o/r#1", 10.0, 6.0),
+            issue("o/r#2", 8.0, 7.0),
+            issue("o/r#3", 10.0, 12.0),
+            issue("o/r#4", 10.0, 10.0),
+            issue("o/r#5", 4.0, 0.0),
+        ];
+        assert_eq!(tally_wins(&per_issue), (3, 1, 1));
+        assert_eq!(tally_wins(&[]), (0, 0, 0));
+
+        // A task set's rows make one issue: 20 -> 17 tool calls is one win
+        let task_set = vec![issue("o/r#6", 10.0, 5.0), issue("o/r#6", 10.0, 12.0)];
+        assert_eq!(tally_wins(&task_set), (1, 0, 0));
+
+        // Control made no tool calls: FMM making any is a loss, not a tie
+        assert_eq!(tally_wins(&[issue("o/r#7", 0.0, 3.0)]), (0, 1, 0));
+        assert_eq!(tally_wins(&[issue("o/r#8", 0.0, 0.0)]), (0, 0, 1));
+
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 0);
+        agg.per_issue = task_set;
+        agg.recompute();
+        assert_eq!(agg.win_line(), "FMM won 1/1 issues (0 lost, 0 tied)");
+    }
+
+    #[test]
+    fn test_markdown_language_filter() {
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 0);
+        for (id, lang) in [("o/r#1", "rust"), ("o/g#2", "go"), ("o/t#3", "typescript")] {
+            agg.per_issue.push(IssueResult {
+                id: id.to_string(),
+                language: lang.to_string(),
+                ..Default::default()
+            });
+            agg.by_language
+                .insert(lang.to_string(), MetricsSummary::default());
+        }
+        agg.summary.n = 3;
+
+        let md = agg.to_markdown_for_languages(&["Rust".to_string(), "go".to_string()]);
+        assert!(md.contains("| rust |"));
+        assert!(md.contains("| go |"));
+        assert!(md.contains("| o/r#1 |"));
+        assert!(!md.contains("typescript"));
+        assert!(!md.contains("o/t#3"));
+
+        // Unfiltered rendering lists everything
+        assert!(agg.to_markdown().contains("| o/t#3 | typescript |"));
+    }
+
+    #[test]
+    fn test_delta_histogram_bins() {
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 0);
+        agg.per_issue = [45.0, 40.0, 12.5, -20.0, -0.5, 0.0, 100.0]
+            .into_iter()
+            .map(|delta_pct| IssueResult {
+                delta_pct,
+                ..Default::default()
+            })
+            .collect();
+
+        let bins = agg.delta_histogram(20.0);
+        let counts: Vec<(f64, usize)> = bins.iter().map(|b| (b.lower, b.count)).collect();
+        assert_eq!(
+            counts,
+            vec![
+                (-20.0, 2), // -20, -0.5: regressions stay below zero
+                (0.0, 2),
+                (20.0, 0),
+                (40.0, 2),
+                (60.0, 0),
+                (80.0, 0),
+                (100.0, 1),
+            ]
+        );
+
+        let csv = agg.delta_histogram_csv(20.0);
+        assert!(csv.starts_with("lower_pct,upper_pct,count\n-20,0,2\n"));
+        assert!(agg
+            .to_markdown()
+            .contains("## Tool Call Reduction Distribution"));
+        assert!(AggregateReport::from_reports(vec![], "sonnet", 1, 0)
+            .delta_histogram(10.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_only_failed_selects_f_graded_and_missing() {
+        let corpus: Vec<CorpusEntry> = serde_json::from_value(serde_json::json!([
+            {"id": "o/r#1", "repo": "o/r", "issue": 1, "language": "rust"},
+            {"id": "o/r#2", "repo": "o/r", "issue": 2, "language": "rust"},
+            {"id": "o/r#3", "repo": "o/r", "issue": 3, "language": "go"},
+            {"id": "o/r#4", "repo": "o/r", "issue": 4, "language": "go"}
+        ]))
+        .unwrap();
+        let row = |id: &str, control_grade: &str, fmm_grade: &str, tools: f64| IssueResult {
+            id: id.to_string(),
+            language: "rust".to_string(),
+            size: "medium".to_string(),
+            control_tool_calls: 10.0,
+            fmm_tool_calls: tools,
+            control_cost: 0.1,
+            fmm_cost: 0.1,
+            control_grade: control_grade.to_string(),
+            fmm_grade: fmm_grade.to_string(),
+            delta_pct: (10.0 - tools) * 10.0,
+            ..Default::default()
+        };
+
+        let mut prior = AggregateReport::from_reports(vec![], "sonnet", 1, 4);
+        prior.per_issue = vec![
+            row("o/r#1", "A", "A", 5.0),
+            row("o/r#2", "A", "F", 20.0),
+            row("o/r#3", "-", "B", 20.0),
+            // o/r#4 errored: no row
+        ];
+        prior.total_cost = 0.6;
+        prior.recompute();
+        assert_eq!(prior.fmm_wins, 1);
+
+        let failed: Vec<String> = prior
+            .failed_entries(&corpus)
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(failed, vec!["o/r#2", "o/r#3", "o/r#4"]);
+
+        let mut rerun = AggregateReport::from_reports(vec![], "sonnet", 1, 3);
+        rerun.per_issue = vec![row("o/r#2", "A", "A", 4.0), row("o/r#4", "B", "B", 8.0)];
+        rerun.total_cost = 0.4;
+
+        let merged = prior.merge(rerun);
+        assert_eq!(merged.per_issue.len(), 4);
+        assert_eq!(merged.issues_completed, 4);
+        assert_eq!(merged.issues_total, 4);
+        assert_eq!(merged.fmm_wins, 3);
+        assert_eq!(merged.summary.n, 4);
+        assert!((merged.total_cost - 1.0).abs() < 1e-10);
+        let r2 = merged.per_issue.iter().find(|r| r.id == "o/r#2").unwrap();
+        assert_eq!(r2.fmm_grade, "A");
+    }
+
+    #[test]
+    fn test_only_failed_leaves_thin_body_skips_alone() {
+        let corpus: Vec<CorpusEntry> = serde_json::from_value(serde_json::json!([
+            {"id": "o/r#1", "repo": "o/r", "issue": 1, "language": "rust"},
+            {"id": "o/r#2", "repo": "o/r", "issue": 2, "language": "rust"}
+        ]))
+        .unwrap();
+        let skip = |id: &str| FailedIssue {
+            id: id.to_string(),
+            reason: "empty body".to_string(),
+        };
+
+        let mut prior = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        prior.failures = vec![FailedIssue {
+            id: "o/r#1".to_string(),
+            reason: "clone failed".to_string(),
+        }];
+        prior.skipped = vec![skip("o/r#2")];
+        let failed: Vec<String> = prior
+            .failed_entries(&corpus)
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(failed, vec!["o/r#1"]);
+        assert!(prior.to_markdown().contains("## Skipped"));
+
+        // An issue skipped on re-run is no longer listed as failed
+        let mut rerun = AggregateReport::from_reports(vec![], "sonnet", 1, 0);
+        rerun.skipped = vec![skip("o/r#1")];
+        let merged = prior.merge(rerun);
+        assert!(merged.failures.is_empty());
+        let ids: Vec<&str> = merged.skipped.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["o/r#2", "o/r#1"]);
+    }
+
+    #[test]
+    fn test_merge_skips_legacy_rows_missing_tokens_and_duration() {
+        // A row from an aggregate written before tokens/duration/reads existed
+        let legacy: IssueResult = serde_json::from_value(serde_json::json!({
+            "id": "o/r#1", "language": "rust", "size": "medium",
+            "control_tool_calls": 10.0, "fmm_tool_calls": 5.0,
+            "control_cost": 0.1, "fmm_cost": 0.1,
+            "control_grade": "A", "fmm_grade": "A", "delta_pct": 50.0
+        }))
+        .unwrap();
+        assert_eq!(legacy.control_tokens, None);
+        assert_eq!(legacy.fmm_duration_ms, None);
+
+        let mut prior = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        prior.per_issue = vec![legacy];
+        let mut rerun = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        rerun.per_issue = vec![IssueResult {
+            id: "o/r#2".to_string(),
+            control_tool_calls: 20.0,
+            fmm_tool_calls: 10.0,
+            control_tokens: Some(4000.0),
+            fmm_tokens: Some(2000.0),
+            control_duration_ms: Some(60_000.0),
+            fmm_duration_ms: Some(30_000.0),
+            ..Default::default()
+        }];
+
+        let merged = prior.merge(rerun);
+        assert_eq!(merged.summary.n, 2);
+        // Tool calls cover both rows; tokens and duration only the recorded one
+        assert!((merged.summary.tool_calls.control_mean - 15.0).abs() < 1e-10);
+        assert!((merged.summary.tokens.control_mean - 4000.0).abs() < 1e-10);
+        assert!((merged.summary.duration.fmm_mean - 30_000.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_aggregate_diff_matches_by_language_and_id() {
+        let row = |id: &str, language: &str, fmm_tools: f64| IssueResult {
+            id: id.to_string(),
+            language: language.to_string(),
+            control_tool_calls: 10.0,
+            fmm_tool_calls: fmm_tools,
+            delta_pct: (10.0 - fmm_tools) * 10.0,
+            ..Default::default()
+        };
+        let aggregate = |rows: Vec<IssueResult>| {
+            let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, rows.len());
+            agg.per_issue = rows;
+            agg.recompute();
+            agg
+        };
+
+        let before = aggregate(vec![
+            row("o/r#1", "rust", 8.0),
+            row("o/r#2", "rust", 12.0),
+            row("o/g#1", "go", 10.0),
+            row("o/old#1", "rust", 1.0),
+        ]);
+        let after = aggregate(vec![
+            row("o/r#1", "rust", 4.0),
+            row("o/r#2", "rust", 6.0),
+            row("o/g#1", "go", 5.0),
+            // Same id under another language is a different issue
+            row("o/old#1", "go", 1.0),
+        ]);
+        let diff = AggregateDiff::between(&before, &after);
+
+        assert_eq!(diff.overall.issues, 3);
+        let tools = &diff.overall.tool_calls;
+        assert!((tools.fmm_mean.before - 10.0).abs() < 1e-9);
+        assert!((tools.fmm_mean.after - 5.0).abs() < 1e-9);
+        assert!((tools.fmm_mean.change + 5.0).abs() < 1e-9);
+        assert_eq!(tools.control_mean.change, 0.0);
+        assert!((tools.delta_pct.before - 0.0).abs() < 1e-9);
+        assert!((tools.delta_pct.after - 50.0).abs() < 1e-9);
+        // 1 of 3 wins before, all 3 after
+        assert!((diff.overall.fmm_win_rate.before - 1.0 / 3.0).abs() < 1e-9);
+        assert!((diff.overall.fmm_win_rate.change - 2.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(diff.by_language.len(), 2);
+        assert_eq!(diff.by_language["rust"].issues, 2);
+        assert!((diff.by_language["go"].tool_calls.fmm_mean.change + 5.0).abs() < 1e-9);
+
+        let unmatched = |language: &str| UnmatchedIssue {
+            language: language.to_string(),
+            id: "o/old#1".to_string(),
+        };
+        assert_eq!(diff.only_before, vec![unmatched("rust")]);
+        assert_eq!(diff.only_after, vec![unmatched("go")]);
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["by_language"]["go"]["fmm_win_rate"]["after"], 1.0);
+    }
+
+    #[test]
+    fn test_append_two_single_issue_batches() {
+        let row = |id: &str, language: &str, fmm_tools: f64| IssueResult {
+            id: id.to_string(),
+            language: language.to_string(),
+            size: "medium".to_string(),
+            control_tool_calls: 10.0,
+            fmm_tool_calls: fmm_tools,
+            control_cost: 0.1,
+            fmm_cost: 0.1,
+            control_grade: "B".to_string(),
+            fmm_grade: "B".to_string(),
+            delta_pct: (10.0 - fmm_tools) * 10.0,
+            ..Default::default()
+        };
+        let batch = |r: IssueResult| {
+            let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+            agg.languages = vec![r.language.clone()];
+            agg.total_cost = 0.2;
+            agg.per_issue = vec![r];
+            agg.issues_completed = 1;
+            agg.recompute();
+            agg
+        };
+
+        let rust = batch(row("o/r#1", "rust", 5.0));
+        let go = batch(row("o/g#1", "go", 8.0));
+        let combined = rust.append(go).unwrap();
+
+        assert_eq!(combined.issues_total, 2);
+        assert_eq!(combined.issues_completed, 2);
+        assert_eq!(combined.summary.n, 2);
+        assert_eq!(combined.languages, vec!["go", "rust"]);
+        assert_eq!(combined.by_language.len(), 2);
+        assert_eq!(combined.fmm_wins, 2);
+        assert!((combined.total_cost - 0.4).abs() < 1e-10);
+
+        // A batch from another model can't be mixed in
+        let other = AggregateReport::from_reports(vec![], "opus", 1, 1);
+        assert!(combined.append(other).is_err());
+    }
+
+    #[test]
+    fn test_normalized_tool_calls() {
+        let mut entry: CorpusEntry =
+            serde_json::from_str(r#"{"id":"o/r#1","repo":"o/r","issue":1,"language":"rust"}"#)
+                .unwrap();
+        entry.estimated_files = 4;
+        assert_eq!(difficulty(&entry), 4.0);
+
+        // Unknown file estimate falls back to complexity
+        entry.estimated_files = 0;
+        entry.complexity = "simple".to_string();
+        assert_eq!(difficulty(&entry), 1.0);
+        entry.complexity = "complex".to_string();
+        assert_eq!(difficulty(&entry), 4.0);
+
+        let row = IssueResult {
+            control_tool_calls: 20.0,
+            fmm_tool_calls: 8.0,
+            difficulty: 4.0,
+            ..Default::default()
+        };
+        assert_eq!(row.control_tool_calls_normalized(), 5.0);
+        assert_eq!(row.fmm_tool_calls_normalized(), 2.0);
+
+        // Legacy rows without a difficulty stay raw instead of dividing by zero
+        let legacy = IssueResult {
+            control_tool_calls: 20.0,
+            ..Default::default()
+        };
+        assert_eq!(legacy.control_tool_calls_normalized(), 20.0);
+
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 2);
+        agg.per_issue = vec![
+            row,
+            IssueResult {
+                control_tool_calls: 10.0,
+                fmm_tool_calls: 10.0,
+                difficulty: 1.0,
+                ..Default::default()
+            },
+        ];
+        assert!(agg.normalized_tool_calls.is_none());
+        let agg = agg.normalized();
+        let n = agg.normalized_tool_calls.as_ref().unwrap();
+        assert!((n.control_mean - 7.5).abs() < 1e-10);
+        assert!((n.fmm_mean - 6.0).abs() < 1e-10);
+        assert!(agg.to_markdown().contains("## Normalized by Difficulty"));
+    }
+
+    #[test]
+    fn test_raw_pairs_round_trip_and_match_summary() {
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 2);
+        agg.per_issue = vec![
+            IssueResult {
+                control_tool_calls: 10.0,
+                fmm_tool_calls: 4.0,
+                control_cost: 0.5,
+                fmm_cost: 0.25,
+                ..Default::default()
+            },
+            IssueResult {
+                control_tool_calls: 6.0,
+                fmm_tool_calls: 8.0,
+                control_cost: 0.25,
+                fmm_cost: 0.5,
+                ..Default::default()
+            },
+        ];
+        agg.recompute();
+        assert!(agg.raw_pairs.is_empty());
+        let json = serde_json::to_string(&agg).unwrap();
+        assert!(!json.contains("\"raw_pairs\""));
+
+        let agg = agg.with_raw_pairs();
+        assert_eq!(agg.raw_pairs.len(), 2);
+        let json = serde_json::to_string(&agg).unwrap();
+        let back: AggregateReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.raw_pairs, agg.raw_pairs);
+
+        let control: Vec<f64> = back.raw_pairs.iter().map(|p| p.control_tools).collect();
+        let fmm: Vec<f64> = back.raw_pairs.iter().map(|p| p.fmm_tools).collect();
+        assert!((mean(&control) - back.summary.tool_calls.control_mean).abs() < 1e-10);
+        assert!((mean(&fmm) - back.summary.tool_calls.fmm_mean).abs() < 1e-10);
+        let cost: Vec<f64> = back.raw_pairs.iter().map(|p| p.fmm_cost).collect();
+        assert!((mean(&cost) - back.summary.cost.fmm_mean).abs() < 1e-10);
+    }
+
     #[test]
     fn test_empty_aggregate() {
         let report = AggregateReport::from_reports(vec![], "sonnet", 1, 0);
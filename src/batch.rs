@@ -5,11 +5,22 @@ use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use crate::aggregate::AggregateReport;
-use crate::issue::{self, GitHubIssue};
-use crate::orchestrator::{CompareOptions, Orchestrator};
+use crate::aggregate::{AggregateReport, CostProjection, FailedIssue};
+use crate::cache::{write_atomic, CacheManager};
+use crate::evaluator::EvalOptions;
+use crate::issue;
+use crate::orchestrator::{
+    deadline_passed, generate_job_id, CompareOptions, IssueSettings, Orchestrator,
+};
+use crate::outln;
+use crate::profile::Profile;
+use crate::ratelimit::{backoff_delay, RateLimiter};
 use crate::report::ComparisonReport;
+use crate::sandbox::{RepoCache, SettingsTemplate};
 
 /// A single entry in the corpus file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,7 +29,8 @@ pub struct CorpusEntry {
     pub id: String,
     /// Repository owner/name
     pub repo: String,
-    /// Issue number
+    /// Issue number in `repo`; also accepts `"123"`, `"#123"`, or `"GH-123"`
+    #[serde(deserialize_with = "deserialize_issue_number")]
     pub issue: u32,
     /// Primary language
     pub language: String,
@@ -49,6 +61,35 @@ pub struct CorpusEntry {
     /// Optional commit to pin to
     #[serde(default)]
     pub commit: Option<String>,
+    /// Commands (argv) run in both sandboxes before each run, e.g. `[["npm", "ci"]]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup_commands: Vec<Vec<String>>,
+    /// Spend cap (USD) for this issue, overriding `--budget-per-issue`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_budget: Option<f64>,
+    /// Task set (built-in name or path) to run on this repo instead of the
+    /// issue, overriding `--tasks`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_set: Option<String>,
+}
+
+/// Accept the issue as a JSON number or a repo-relative string like `GH-123`.
+fn deserialize_issue_number<'de, D>(deserializer: D) -> std::result::Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u32),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(s) => issue::parse_issue_number(&s)
+            .and_then(|n| u32::try_from(n).context("Issue number out of range"))
+            .map_err(|e| serde::de::Error::custom(format!("{e:#}"))),
+    }
 }
 
 fn default_size() -> String {
@@ -63,43 +104,191 @@ fn default_complexity() -> String {
     "medium".to_string()
 }
 
+/// A `CorpusEntry` that rejects keys it doesn't know (`--strict-corpus`), so
+/// a typo like `"comit"` fails the load instead of silently taking the
+/// default. Same effect as `deny_unknown_fields`, which can't be switched on
+/// per load.
+struct StrictCorpusEntry(CorpusEntry);
+
+impl<'de> Deserialize<'de> for StrictCorpusEntry {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let object = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+        let fields: Vec<&str> = CORPUS_CSV_COLUMNS.iter().map(|(name, _)| *name).collect();
+        if let Some(unknown) = object.keys().find(|k| !fields.contains(&k.as_str())) {
+            return Err(D::Error::custom(format!(
+                "unknown field `{}`, expected one of: {}",
+                unknown,
+                fields.join(", ")
+            )));
+        }
+        CorpusEntry::deserialize(serde_json::Value::Object(object))
+            .map(Self)
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Default spend cap for a single issue in a batch.
+pub const DEFAULT_PER_ISSUE_BUDGET: f64 = 10.0;
+
+/// Default number of recent failures after which resume skips an issue.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 2;
+
 /// Options for a batch run.
 #[derive(Debug, Clone)]
 pub struct BatchOptions {
     /// Maximum total spend across all issues
     pub budget: f64,
+    /// Maximum spend for a single issue (further capped by what's left of `budget`)
+    pub per_issue_budget: f64,
     /// Number of runs per issue
     pub runs: u32,
     /// Filter by language (case-insensitive)
     pub filter: Option<String>,
+    /// Corpus entry ids to skip (`--exclude`, `--exclude-file`)
+    pub exclude: Vec<String>,
     /// Skip issues with cached results
     pub resume: bool,
+    /// On resume, skip issues that already failed this many times recently (0 = never skip)
+    pub max_attempts: u32,
     /// Output directory
     pub output: Option<PathBuf>,
     /// Model to use
     pub model: String,
+    /// JSON file of team model aliases (see `CompareOptions::model_alias_file`)
+    pub model_alias_file: Option<PathBuf>,
+    /// Required commit message format passed to every issue run
+    pub commit_message_template: Option<String>,
+    /// Prior aggregate: re-run only its failed issues and merge into it
+    pub only_failed: Option<PathBuf>,
+    /// Existing aggregate to accumulate this batch into (rewritten in place)
+    pub append_to: Option<PathBuf>,
+    /// Also report tool calls normalized by issue difficulty
+    pub normalize: bool,
+    /// Include the paired samples behind the summary in the aggregate JSON
+    pub export_raw_pairs: bool,
+    /// Limit the markdown's language and per-issue breakdowns to these languages
+    pub languages: Vec<String>,
+    /// SQLite database to append per-issue rows to (requires the `sqlite` feature)
+    pub sqlite: Option<PathBuf>,
+    /// Issue bodies shorter than this after sanitization are flagged (0 = off)
+    pub min_body_chars: usize,
+    /// Skip (rather than warn about) issues with a too-short body
+    pub skip_empty_body: bool,
+    /// Truncate issue bodies to roughly this many estimated tokens
+    pub max_body_tokens: Option<usize>,
+    /// Only run issues carrying at least one of these labels
+    pub include_labels: Vec<String>,
+    /// Skip issues carrying any of these labels
+    pub exclude_labels: Vec<String>,
+    /// Record per-stage timings and sum them across issues
+    pub profile: bool,
+    /// Evaluator steps to skip (`--no-build-check`, `--no-test-check`)
+    pub eval: EvalOptions,
+    /// Wall-clock cap for the whole batch (`--max-wall-secs`)
+    pub max_wall: Option<Duration>,
+    /// `git clone --depth` for each issue's sandboxes (`None` = full history)
+    pub clone_depth: Option<u32>,
+    /// Kill any clone that runs longer than this (`--clone-timeout`)
+    pub clone_timeout: Duration,
+    /// Run Claude under permission checks (`--require-permissions`)
+    pub require_permissions: bool,
+    /// Fail an issue whose FMM condition got no sidecars or a partial
+    /// `fmm init` (`--require-sidecars`)
+    pub require_sidecars: bool,
+    /// Abort a condition's run past this many tool calls (`--tool-budget`)
+    pub max_tool_calls: Option<u32>,
+    /// Retries for a run that fails before doing any work (`--retries`)
+    pub task_retries: u32,
+    /// Cap on stream-json bytes parsed per run (`--max-output-mb`)
+    pub max_output_bytes: u64,
+    /// Keep each condition's diff in the per-issue reports (`--include-diff`)
+    pub include_diff: bool,
+    /// Leave tasks whose FMM run never used the treatment out of each
+    /// issue's summary (`--require-treatment-used`)
+    pub require_treatment_used: bool,
+    /// Settings template merged into each issue's workspaces
+    pub settings_template: Option<SettingsTemplate>,
+    /// Append each issue's linked raw files and gists to its prompt
+    pub fetch_linked: bool,
+    /// Task set for entries without their own (`None` = run the issue itself)
+    pub task_set: Option<String>,
+    /// Result cache directory, which also holds failure markers (`None` =
+    /// `FMM_BENCH_CACHE_DIR`, else the platform cache dir)
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl Default for BatchOptions {
     fn default() -> Self {
         Self {
             budget: 50.0,
+            per_issue_budget: DEFAULT_PER_ISSUE_BUDGET,
             runs: 1,
             filter: None,
+            exclude: vec![],
             resume: false,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
             output: None,
             model: "sonnet".to_string(),
+            model_alias_file: None,
+            commit_message_template: None,
+            only_failed: None,
+            append_to: None,
+            normalize: false,
+            export_raw_pairs: false,
+            languages: vec![],
+            sqlite: None,
+            min_body_chars: issue::DEFAULT_MIN_BODY_CHARS,
+            skip_empty_body: false,
+            max_body_tokens: None,
+            include_labels: vec![],
+            exclude_labels: vec![],
+            profile: false,
+            eval: EvalOptions::default(),
+            max_wall: None,
+            clone_depth: crate::sandbox::DEFAULT_CLONE_DEPTH,
+            clone_timeout: crate::sandbox::DEFAULT_CLONE_TIMEOUT,
+            require_permissions: false,
+            require_sidecars: false,
+            max_tool_calls: None,
+            task_retries: 1,
+            max_output_bytes: crate::metrics::DEFAULT_MAX_OUTPUT_BYTES,
+            include_diff: false,
+            require_treatment_used: false,
+            settings_template: None,
+            fetch_linked: false,
+            task_set: None,
+            cache_dir: None,
         }
     }
 }
 
-/// Load and validate a corpus file.
+/// Load and validate a corpus file: a JSON array, or a spreadsheet export
+/// when the extension is `.csv` (see `parse_corpus_csv`).
 pub fn load_corpus(path: &Path) -> Result<Vec<CorpusEntry>> {
+    load_corpus_with(path, false)
+}
+
+/// `load_corpus`, optionally rejecting unknown keys in JSON entries
+/// (`--strict-corpus`). CSV corpora always reject unknown columns.
+pub fn load_corpus_with(path: &Path, strict: bool) -> Result<Vec<CorpusEntry>> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read corpus: {}", path.display()))?;
 
-    let entries: Vec<CorpusEntry> = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse corpus: {}", path.display()))?;
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let entries: Vec<CorpusEntry> = if is_csv {
+        parse_corpus_csv(&content)
+    } else if strict {
+        parse_corpus_strict(&content)
+    } else {
+        serde_json::from_str(&content).map_err(anyhow::Error::from)
+    }
+    .with_context(|| format!("Failed to parse corpus: {}", path.display()))?;
 
     if entries.is_empty() {
         anyhow::bail!("Corpus is empty: {}", path.display());
@@ -108,8 +297,257 @@ pub fn load_corpus(path: &Path) -> Result<Vec<CorpusEntry>> {
     Ok(entries)
 }
 
+/// Parse a JSON corpus entry by entry as `StrictCorpusEntry`, naming the
+/// (1-based) entry an error is in.
+fn parse_corpus_strict(content: &str) -> Result<Vec<CorpusEntry>> {
+    let raw: Vec<serde_json::Value> = serde_json::from_str(content)?;
+    raw.into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let id = value
+                .get("id")
+                .and_then(|id| id.as_str())
+                .map(|id| format!(" ({})", id))
+                .unwrap_or_default();
+            StrictCorpusEntry::deserialize(value)
+                .map(|entry| entry.0)
+                .with_context(|| format!("Entry {}{}", i + 1, id))
+        })
+        .collect()
+}
+
+/// Parse a CSV corpus whose header row names `CorpusEntry` fields.
+///
+/// Empty cells (and missing columns) take the JSON defaults; `id` defaults
+/// to `repo#issue`. `has_tests` accepts true/false, yes/no, or 1/0;
+/// `expected_files` is `;`-separated, as is `setup_commands` (each command
+/// split on whitespace). Errors name the line a bad row starts on.
+fn parse_corpus_csv(content: &str) -> Result<Vec<CorpusEntry>> {
+    let mut rows = parse_csv(content)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(vec![]);
+    };
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let fields: Vec<&str> = CORPUS_CSV_COLUMNS.iter().map(|(name, _)| *name).collect();
+    if let Some(unknown) = header.iter().find(|h| !fields.contains(&h.as_str())) {
+        anyhow::bail!(
+            "Unknown corpus column '{}' (expected: {})",
+            unknown,
+            fields.join(", ")
+        );
+    }
+
+    let mut entries = vec![];
+    for (line, row) in rows {
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        if row.len() > header.len() {
+            anyhow::bail!(
+                "Line {}: {} cells but only {} columns",
+                line,
+                row.len(),
+                header.len()
+            );
+        }
+        let mut object = serde_json::Map::new();
+        for (column, cell) in header.iter().zip(&row) {
+            let cell = cell.trim();
+            if cell.is_empty() {
+                continue;
+            }
+            let kind = CORPUS_CSV_COLUMNS
+                .iter()
+                .find(|(name, _)| name == column)
+                .map(|(_, kind)| *kind)
+                .unwrap_or(CsvColumn::Text);
+            let value = csv_cell_value(kind, cell)
+                .with_context(|| format!("Line {}: bad '{}' value '{}'", line, column, cell))?;
+            object.insert(column.clone(), value);
+        }
+        let derive_id = !object.contains_key("id");
+        if derive_id {
+            object.insert("id".to_string(), serde_json::Value::String(String::new()));
+        }
+        let mut entry: CorpusEntry = serde_json::from_value(serde_json::Value::Object(object))
+            .with_context(|| format!("Line {}: invalid corpus row", line))?;
+        if derive_id {
+            entry.id = format!("{}#{}", entry.repo, entry.issue);
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// How a CSV cell is turned into the JSON a `CorpusEntry` field expects.
+#[derive(Clone, Copy)]
+enum CsvColumn {
+    Text,
+    Bool,
+    Number,
+    List,
+    Commands,
+}
+
+/// Every `CorpusEntry` field; also the key list `--strict-corpus` checks.
+const CORPUS_CSV_COLUMNS: &[(&str, CsvColumn)] = &[
+    ("id", CsvColumn::Text),
+    ("repo", CsvColumn::Text),
+    ("issue", CsvColumn::Text),
+    ("language", CsvColumn::Text),
+    ("size", CsvColumn::Text),
+    ("type", CsvColumn::Text),
+    ("has_tests", CsvColumn::Bool),
+    ("expected_files", CsvColumn::List),
+    ("complexity", CsvColumn::Text),
+    ("estimated_files", CsvColumn::Number),
+    ("notes", CsvColumn::Text),
+    ("branch", CsvColumn::Text),
+    ("commit", CsvColumn::Text),
+    ("setup_commands", CsvColumn::Commands),
+    ("max_budget", CsvColumn::Number),
+    ("task_set", CsvColumn::Text),
+];
+
+fn csv_cell_value(kind: CsvColumn, cell: &str) -> Result<serde_json::Value> {
+    let list = || cell.split(';').map(str::trim).filter(|s| !s.is_empty());
+    Ok(match kind {
+        CsvColumn::Text => serde_json::Value::String(cell.to_string()),
+        CsvColumn::Bool => match cell.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => serde_json::Value::Bool(true),
+            "false" | "no" | "n" | "0" => serde_json::Value::Bool(false),
+            _ => anyhow::bail!("expected true/false, yes/no, or 1/0"),
+        },
+        CsvColumn::Number => serde_json::from_str::<serde_json::Number>(cell)
+            .map(serde_json::Value::Number)
+            .context("expected a number")?,
+        CsvColumn::List => list().collect::<Vec<_>>().into(),
+        CsvColumn::Commands => list()
+            .map(|command| command.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+            .into(),
+    })
+}
+
+/// Split CSV text into rows of cells, each with the line number it starts on.
+///
+/// Follows RFC 4180: quoted cells may hold commas, newlines, and `""` for a
+/// literal quote. CRLF line endings are accepted.
+fn parse_csv(content: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if cell.is_empty() => in_quotes = true,
+            ',' if !in_quotes => row.push(std::mem::take(&mut cell)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                cell.push(c);
+            }
+        }
+    }
+    if in_quotes {
+        anyhow::bail!("Line {}: unterminated quoted cell", row_line);
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+/// Load a skip list for `--exclude-file`: one corpus id per line. Blank
+/// lines, `#` comment lines, and trailing ` # ...` comments are ignored (ids
+/// themselves contain `#`).
+pub fn load_exclude_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read exclude file: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(|l| l.split_whitespace().next().unwrap_or(""))
+        .filter(|id| !id.is_empty() && !id.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Drop entries whose id is in `exclude`, logging each skip.
+fn exclude_entries<'a>(entries: Vec<&'a CorpusEntry>, exclude: &[String]) -> Vec<&'a CorpusEntry> {
+    entries
+        .into_iter()
+        .filter(|e| {
+            let skip = exclude.contains(&e.id);
+            if skip {
+                outln!("  {} Excluding {}", "-".dimmed(), e.id);
+            }
+            !skip
+        })
+        .collect()
+}
+
+/// Load an aggregate report written by a previous batch.
+pub fn load_aggregate(path: &Path) -> Result<AggregateReport> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read aggregate: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse aggregate: {}", path.display()))
+}
+
 /// Run a batch of A/B comparisons across corpus issues.
+///
+/// With `only_failed`, only issues that are missing, errored, or graded F in
+/// the prior aggregate are run, and the results are merged into it.
 pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<AggregateReport> {
+    opts.validate()?;
+    let started = Instant::now();
+    let deadline = opts.max_wall.map(|wall| started + wall);
+    // Fail before spending anything if the export can't happen
+    if opts.sqlite.is_some() && !cfg!(feature = "sqlite") {
+        anyhow::bail!("--sqlite requires fmm-bench built with `--features sqlite`");
+    }
+
+    let prior = opts
+        .only_failed
+        .as_deref()
+        .map(load_aggregate)
+        .transpose()?;
+    // Load (and vet) the aggregate being appended to before spending anything
+    let appended = opts.append_to.as_deref().map(load_aggregate).transpose()?;
+    if let Some(ref appended) = appended {
+        appended.ensure_appendable(&opts.model, opts.runs)?;
+    }
+    let selected: Vec<CorpusEntry> = match prior {
+        Some(ref prior) => {
+            let failed = prior.failed_entries(corpus);
+            outln!(
+                "{} Re-running {} failed issues from prior aggregate",
+                ">>".yellow(),
+                failed.len()
+            );
+            failed
+        }
+        None => corpus.to_vec(),
+    };
+    let corpus = selected.as_slice();
+
     let filtered: Vec<&CorpusEntry> = if let Some(ref lang) = opts.filter {
         let lang_lower = lang.to_lowercase();
         corpus
@@ -119,8 +557,11 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
     } else {
         corpus.iter().collect()
     };
+    let filtered = exclude_entries(filtered, &opts.exclude);
+
+    validate_entry_budgets(&filtered, opts.budget)?;
 
-    println!(
+    outln!(
         "{} Batch: {} issues ({})",
         ">>".yellow(),
         filtered.len(),
@@ -131,13 +572,102 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
         }
     );
 
+    // Clone each distinct repo once up front; sandboxes then copy locally.
+    // A repo that fails here is retried (and recorded) by its issues' runs.
+    // The cache is shallow, so deeper clones skip it.
+    let mut repo_cache = RepoCache::new(&generate_job_id())?;
+    repo_cache.set_clone_timeout(opts.clone_timeout);
+    let unclonable = preclone_repos(&filtered, |url| {
+        if deadline_passed(deadline) || opts.clone_depth != Some(1) {
+            return Ok(());
+        }
+        repo_cache.ensure(url).map(|_| ())
+    });
+    for (url, e) in &unclonable {
+        eprintln!(
+            "{} could not pre-clone {}: {:#}",
+            "Warning:".yellow(),
+            url,
+            e
+        );
+    }
+
     let mut reports: Vec<(CorpusEntry, ComparisonReport)> = vec![];
+    // Attempted issues with no report (fetch, clone, setup, or run errors)
+    let mut failed: Vec<FailedIssue> = vec![];
+    // Issues skipped for a thin body (--skip-empty-body); not failures
+    let mut skipped: Vec<FailedIssue> = vec![];
     let mut total_cost = 0.0f64;
+    // Issues left out by --include-labels/--exclude-labels don't count as attempted
+    let mut label_skipped = 0usize;
+    // Nor do issues --resume skips for failing --max-attempts times already
+    let mut exhausted_skipped = 0usize;
+
+    // One orchestrator for the whole batch: runners and the in-memory result
+    // cache are shared, per-issue settings are applied in `run_next_issue`
+    let mut orchestrator = Orchestrator::new(CompareOptions {
+        branch: None,
+        commit: None,
+        src_path: None,
+        task_set: "standard".to_string(),
+        runs: opts.runs,
+        task_repeats: 1,
+        output: None, // Individual reports saved via cache
+        format: crate::report::ReportFormat::Json,
+        max_budget: opts.per_issue_budget,
+        use_cache: opts.resume,
+        quick: false,
+        model: opts.model.clone(),
+        model_alias_file: opts.model_alias_file.clone(),
+        commit_message_template: opts.commit_message_template.clone(),
+        require_sidecars: opts.require_sidecars,
+        seed: None,
+        exclude_degenerate: false,
+        require_treatment_used: opts.require_treatment_used,
+        max_output_bytes: opts.max_output_bytes,
+        export_raw: None,
+        keep_claude_logs: None,
+        strict_json: false,
+        ndjson: None,
+        include_diff: opts.include_diff,
+        task_retries: opts.task_retries,
+        control_context: None,
+        sidecar_args: vec![],
+        max_tool_calls: opts.max_tool_calls,
+        category_filter: None,
+        fmm_bin: None,
+        min_body_chars: opts.min_body_chars,
+        skip_empty_body: opts.skip_empty_body,
+        max_body_tokens: opts.max_body_tokens,
+        // Already filtered above
+        include_labels: vec![],
+        exclude_labels: vec![],
+        profile: opts.profile,
+        setup_commands: vec![],
+        repo_cache: Some(repo_cache.root().to_path_buf()),
+        cache_dir: opts.cache_dir.clone(),
+        eval: opts.eval,
+        deadline,
+        clone_depth: opts.clone_depth,
+        clone_timeout: opts.clone_timeout,
+        require_permissions: opts.require_permissions,
+        settings_template: opts.settings_template.clone(),
+        fetch_linked: opts.fetch_linked,
+    })?;
 
+    let mut unattempted = 0;
+    let mut deadline_truncated = false;
     for (i, entry) in filtered.iter().enumerate() {
+        if deadline_passed(deadline) {
+            outln!("\n{} Wall-clock deadline reached, stopping.", "!".yellow());
+            deadline_truncated = true;
+            unattempted = filtered.len() - i;
+            break;
+        }
         // Budget check
         if total_cost >= opts.budget {
-            println!(
+            unattempted = filtered.len() - i;
+            outln!(
                 "\n{} Budget limit reached (${:.2} / ${:.2}), stopping.",
                 "!".yellow(),
                 total_cost,
@@ -146,7 +676,7 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
             break;
         }
 
-        println!(
+        outln!(
             "\n{} [{}/{}] {} ({})",
             ">>".cyan().bold(),
             i + 1,
@@ -155,12 +685,27 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
             entry.language.dimmed()
         );
 
+        // Failure markers live in the orchestrator's cache dir
+        let failures = orchestrator.cache();
+        if opts.resume && attempts_exhausted(failures, &entry.id, opts.max_attempts) {
+            outln!(
+                "  {} Skipping {}: failed {} time(s) recently (--max-attempts {})",
+                "!".yellow(),
+                entry.id,
+                failures.failed_attempts(&entry.id),
+                opts.max_attempts
+            );
+            exhausted_skipped += 1;
+            continue;
+        }
+
         // Fetch issue
         let issue_id = format!("{}#{}", entry.repo, entry.issue);
         let issue_ref = match issue::parse_issue_identifier(&issue_id) {
             Ok(r) => r,
             Err(e) => {
                 eprintln!("  {} Skipping {}: {}", "!".red(), entry.id, e);
+                failed.push(failed_issue(&entry.id, &e));
                 continue;
             }
         };
@@ -169,25 +714,42 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
             Ok(i) => i,
             Err(e) => {
                 eprintln!("  {} Failed to fetch {}: {}", "!".red(), entry.id, e);
+                failed.push(failed_issue(&entry.id, &e));
                 continue;
             }
         };
 
+        if let Some(reason) = issue.label_filter_reason(&opts.include_labels, &opts.exclude_labels)
+        {
+            outln!("  {} Skipping {}: {}", "-".dimmed(), entry.id, reason);
+            label_skipped += 1;
+            continue;
+        }
+        if opts.skip_empty_body {
+            if let Some(reason) = issue.thin_body_reason(opts.min_body_chars) {
+                outln!("  {} Skipping {}: {}", "-".dimmed(), entry.id, reason);
+                skipped.push(FailedIssue {
+                    id: entry.id.clone(),
+                    reason,
+                });
+                continue;
+            }
+        }
+
         // Run comparison
-        let compare_opts = CompareOptions {
+        let settings = IssueSettings {
             branch: entry.branch.clone(),
-            src_path: None,
-            task_set: "standard".to_string(),
-            runs: opts.runs,
-            output: None, // Individual reports saved via cache
-            format: crate::report::ReportFormat::Json,
-            max_budget: (opts.budget - total_cost).min(10.0), // Per-issue cap
-            use_cache: opts.resume,
-            quick: false,
-            model: opts.model.clone(),
+            commit: entry.commit.clone(),
+            max_budget: per_issue_cap(opts, entry.max_budget, total_cost),
+            setup_commands: entry.setup_commands.clone(),
+            task_set: entry_task_set(opts, entry).map(str::to_string),
         };
 
-        match run_single_issue(&issue, compare_opts) {
+        let result = match settings.task_set {
+            Some(_) => orchestrator.run_next_repo(&issue.issue_ref.clone_url(), settings),
+            None => orchestrator.run_next_issue(&issue, settings),
+        };
+        match result {
             Ok(report) => {
                 let cost: f64 = report
                     .task_results
@@ -195,24 +757,67 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
                     .map(|t| t.control.total_cost_usd + t.fmm.total_cost_usd)
                     .sum();
                 total_cost += cost;
+                deadline_truncated |= report.deadline_truncated;
                 reports.push(((*entry).clone(), report));
+                orchestrator.cache().clear_failures(&entry.id);
             }
             Err(e) => {
                 eprintln!("  {} Error on {}: {}", "!".red(), entry.id, e);
+                failed.push(failed_issue(&entry.id, &e));
+                let _ = orchestrator
+                    .cache()
+                    .record_failure(&entry.id, &format!("{:#}", e));
             }
         }
     }
 
-    println!(
+    let attempted = filtered.len() - label_skipped - exhausted_skipped - skipped.len();
+    outln!(
         "\n{} Batch complete: {}/{} issues, ${:.2} total",
         ">>".green().bold(),
         reports.len(),
-        filtered.len(),
+        attempted,
         total_cost
     );
 
+    let profile = opts.profile.then(|| {
+        let mut total = Profile::default();
+        for (_, report) in &reports {
+            if let Some(p) = &report.profile {
+                total.merge(p);
+            }
+        }
+        total
+    });
+
     // Generate aggregate report
-    let aggregate = AggregateReport::from_reports(reports, &opts.model, opts.runs, filtered.len());
+    let mut aggregate = AggregateReport::from_reports(reports, &opts.model, opts.runs, attempted);
+    aggregate.profile = profile;
+    aggregate.failures = failed;
+    aggregate.skipped = skipped;
+    aggregate.cost_projection =
+        CostProjection::from_spend(total_cost, aggregate.issues_completed, unattempted);
+    aggregate.deadline_truncated = deadline_truncated;
+    aggregate.wall_ms = started.elapsed().as_millis() as u64;
+    let mut aggregate = match prior {
+        Some(prior) => prior.merge(aggregate),
+        None => aggregate,
+    };
+    if let Some(appended) = appended {
+        aggregate = appended.append(aggregate)?;
+    }
+    if opts.normalize {
+        aggregate = aggregate.normalized();
+    }
+    if opts.export_raw_pairs {
+        aggregate = aggregate.with_raw_pairs();
+    }
+
+    if let Some(ref path) = opts.append_to {
+        let json = serde_json::to_string_pretty(&aggregate)?;
+        write_atomic(path, json.as_bytes())?;
+        outln!("  {} {} (appended)", "+".green(), path.display());
+    }
 
     // Save aggregate if output dir specified
     if let Some(ref output_dir) = opts.output {
@@ -220,20 +825,363 @@ pub fn run_batch(corpus: &[CorpusEntry], opts: &BatchOptions) -> Result<Aggregat
 
         let json_path = output_dir.join("aggregate.json");
         let json = serde_json::to_string_pretty(&aggregate)?;
-        fs::write(&json_path, &json)?;
-        println!("  {} {}", "+".green(), json_path.display());
+        write_atomic(&json_path, json.as_bytes())?;
+        outln!("  {} {}", "+".green(), json_path.display());
 
         let md_path = output_dir.join("aggregate.md");
-        fs::write(&md_path, aggregate.to_markdown())?;
-        println!("  {} {}", "+".green(), md_path.display());
+        let md = aggregate.to_markdown_for_languages(&opts.languages);
+        write_atomic(&md_path, md.as_bytes())?;
+        outln!("  {} {}", "+".green(), md_path.display());
+
+        let csv_path = output_dir.join("delta_histogram.csv");
+        let csv = aggregate.delta_histogram_csv(crate::aggregate::DELTA_BIN_WIDTH);
+        write_atomic(&csv_path, csv.as_bytes())?;
+        outln!("  {} {}", "+".green(), csv_path.display());
+    }
+
+    #[cfg(feature = "sqlite")]
+    if let Some(ref db) = opts.sqlite {
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        let job_id = generate_job_id();
+        let rows = crate::sqlite::export_aggregate(db, &aggregate, &job_id, &timestamp)?;
+        outln!("  {} {} ({} rows)", "+".green(), db.display(), rows);
     }
 
     Ok(aggregate)
 }
 
-fn run_single_issue(issue: &GitHubIssue, opts: CompareOptions) -> Result<ComparisonReport> {
-    let mut orchestrator = Orchestrator::new(opts)?;
-    orchestrator.run_issue(issue)
+/// Whether resume should skip an issue that keeps failing.
+fn attempts_exhausted(failures: &CacheManager, issue_id: &str, max_attempts: u32) -> bool {
+    max_attempts > 0 && failures.failed_attempts(issue_id) >= max_attempts
+}
+
+impl BatchOptions {
+    /// Reject option values that would make the batch empty or meaningless.
+    pub fn validate(&self) -> Result<()> {
+        if self.runs == 0 {
+            anyhow::bail!("runs must be at least 1");
+        }
+        for (name, value) in [
+            ("budget", self.budget),
+            ("per-issue budget", self.per_issue_budget),
+        ] {
+            if !(value > 0.0 && value.is_finite()) {
+                anyhow::bail!("{} must be a positive amount of USD (got {})", name, value);
+            }
+        }
+        if self.model.trim().is_empty() {
+            anyhow::bail!("model must not be empty");
+        }
+        Ok(())
+    }
+}
+
+/// Budget for the next issue: its corpus `max_budget` if set, else the
+/// per-issue cap, clamped by what's left of the total batch budget.
+fn per_issue_cap(opts: &BatchOptions, entry_budget: Option<f64>, spent: f64) -> f64 {
+    (opts.budget - spent).min(entry_budget.unwrap_or(opts.per_issue_budget))
+}
+
+/// Task set for an entry: its own `task_set`, else the batch default. `None`
+/// means the issue-driven run.
+fn entry_task_set<'a>(opts: &'a BatchOptions, entry: &'a CorpusEntry) -> Option<&'a str> {
+    entry.task_set.as_deref().or(opts.task_set.as_deref())
+}
+
+/// Reject per-entry budgets that are not positive or exceed the batch budget.
+fn validate_entry_budgets(entries: &[&CorpusEntry], budget: f64) -> Result<()> {
+    for entry in entries {
+        let Some(cap) = entry.max_budget else {
+            continue;
+        };
+        if !(cap > 0.0 && cap.is_finite()) {
+            anyhow::bail!(
+                "{}: max_budget must be a positive amount of USD (got {})",
+                entry.id,
+                cap
+            );
+        }
+        if cap > budget {
+            anyhow::bail!(
+                "{}: max_budget ${:.2} exceeds the batch budget ${:.2}",
+                entry.id,
+                cap,
+                budget
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Clone URLs of the distinct repos whose issues clone the default branch
+/// (branch-pinned entries always clone from the network), in corpus order.
+fn distinct_repo_urls(entries: &[&CorpusEntry]) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    for entry in entries.iter().filter(|e| e.branch.is_none()) {
+        let Ok(issue_ref) =
+            issue::parse_issue_identifier(&format!("{}#{}", entry.repo, entry.issue))
+        else {
+            continue;
+        };
+        let url = issue_ref.clone_url();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Run `clone` once per distinct repo in `entries`, returning the failures.
+fn preclone_repos(
+    entries: &[&CorpusEntry],
+    mut clone: impl FnMut(&str) -> Result<()>,
+) -> Vec<(String, anyhow::Error)> {
+    let urls = distinct_repo_urls(entries);
+    if !urls.is_empty() {
+        outln!("{} Pre-cloning {} repo(s)...", ">>".yellow(), urls.len());
+    }
+    urls.into_iter()
+        .filter_map(|url| clone(&url).err().map(|e| (url, e)))
+        .collect()
+}
+
+/// Record why an attempted issue produced no report.
+fn failed_issue(id: &str, err: &anyhow::Error) -> FailedIssue {
+    FailedIssue {
+        id: id.to_string(),
+        reason: format!("{:#}", err),
+    }
+}
+
+/// Generate corpus entries from a GitHub issue search.
+///
+/// The query must name its repository with a `repo:owner/name` qualifier;
+/// the rest is passed to `gh issue list --search`. The language is taken from
+/// the repo's primary language and other fields are left at their defaults.
+pub fn generate_corpus(query: &str, limit: u32) -> Result<Vec<CorpusEntry>> {
+    generate_corpus_with(&issue::gh_binary(), query, limit)
+}
+
+fn generate_corpus_with(gh: &Path, query: &str, limit: u32) -> Result<Vec<CorpusEntry>> {
+    let (repo, search) = split_repo_qualifier(query)?;
+
+    let output = std::process::Command::new(gh)
+        .args([
+            "issue",
+            "list",
+            "--repo",
+            &repo,
+            "--search",
+            &search,
+            "--state",
+            "all",
+            "--limit",
+            &limit.to_string(),
+            "--json",
+            "number,title",
+        ])
+        .output()
+        .context("Failed to execute `gh` CLI. Is it installed and authenticated?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "gh issue list failed for {}: {}",
+            repo,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let issues: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)
+        .context("Failed to parse `gh issue list` JSON output")?;
+
+    let language = repo_language(gh, &repo);
+
+    Ok(issues
+        .iter()
+        .filter_map(|i| {
+            let number = i["number"].as_u64()? as u32;
+            Some(CorpusEntry {
+                id: format!("{}#{}", repo, number),
+                repo: repo.clone(),
+                issue: number,
+                language: language.clone(),
+                size: default_size(),
+                r#type: default_type(),
+                has_tests: false,
+                expected_files: vec![],
+                complexity: default_complexity(),
+                estimated_files: 0,
+                notes: i["title"].as_str().unwrap_or_default().to_string(),
+                branch: None,
+                commit: None,
+                setup_commands: vec![],
+                max_budget: None,
+                task_set: None,
+            })
+        })
+        .collect())
+}
+
+/// Split `repo:owner/name` out of a search query, returning (repo, rest).
+fn split_repo_qualifier(query: &str) -> Result<(String, String)> {
+    let mut repo = None;
+    let mut rest = vec![];
+    for token in query.split_whitespace() {
+        match token.strip_prefix("repo:") {
+            Some(r) if repo.is_none() => repo = Some(r.to_string()),
+            Some(_) => anyhow::bail!("Only one repo: qualifier is supported: {}", query),
+            None => rest.push(token),
+        }
+    }
+    let repo = repo.with_context(|| {
+        format!(
+            "Query must include a repo:owner/name qualifier (e.g. \"repo:owner/name label:bug\"): {}",
+            query
+        )
+    })?;
+    if repo.split('/').filter(|p| !p.is_empty()).count() != 2 {
+        anyhow::bail!("Invalid repo qualifier '{}': expected owner/name", repo);
+    }
+    Ok((repo, rest.join(" ")))
+}
+
+/// Primary language of a repo (lowercased), or "unknown".
+fn repo_language(gh: &Path, repo: &str) -> String {
+    std::process::Command::new(gh)
+        .args(["repo", "view", repo, "--json", "primaryLanguage"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| serde_json::from_slice::<serde_json::Value>(&o.stdout).ok())
+        .and_then(|v| {
+            v["primaryLanguage"]["name"]
+                .as_str()
+                .map(|s| s.to_lowercase())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Directory where runs cache their full reports.
+pub fn cached_reports_dir() -> Result<PathBuf> {
+    Ok(CacheManager::new(None)?.reports_dir())
+}
+
+/// Load every comparison report in `dir` (e.g. the cache's `reports/`),
+/// skipping JSON files that aren't reports with a warning.
+pub fn load_reports(dir: &Path) -> Result<Vec<ComparisonReport>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read reports from {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    paths.sort();
+
+    let mut reports = vec![];
+    for path in paths {
+        let parsed = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| ComparisonReport::from_json(&content));
+        match parsed {
+            Ok(report) => reports.push(report),
+            Err(e) => eprintln!(
+                "{} Skipping {}: {:#}",
+                "Warning:".yellow(),
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(reports)
+}
+
+/// Turn issue-run reports into corpus entries, one per issue, so issues
+/// explored with ad-hoc `run`s can be re-run as a batch.
+///
+/// Each entry is pinned to the commit its latest report ran at. The language
+/// is the most common one among the files either condition accessed
+/// ("unknown" if none is recognized). Task-set reports have no issue and are
+/// skipped. Entries come back sorted by id.
+pub fn corpus_from_reports(reports: &[ComparisonReport]) -> Vec<CorpusEntry> {
+    let mut latest: std::collections::BTreeMap<String, &ComparisonReport> =
+        std::collections::BTreeMap::new();
+    for report in reports {
+        let Some(issue_ref) = report_issue(report) else {
+            continue;
+        };
+        let id = issue_ref.short_id();
+        // RFC 3339 timestamps sort chronologically as strings
+        match latest.get(&id) {
+            Some(seen) if seen.timestamp > report.timestamp => {}
+            _ => {
+                latest.insert(id, report);
+            }
+        }
+    }
+
+    latest
+        .into_values()
+        .filter_map(|report| {
+            let issue_ref = report_issue(report)?;
+            Some(CorpusEntry {
+                id: issue_ref.short_id(),
+                repo: issue_ref.repo_slug(),
+                issue: u32::try_from(issue_ref.number).ok()?,
+                language: report_language(report),
+                size: default_size(),
+                r#type: default_type(),
+                has_tests: false,
+                expected_files: vec![],
+                complexity: default_complexity(),
+                estimated_files: 0,
+                notes: report
+                    .task_results
+                    .first()
+                    .map(|t| t.task_name.clone())
+                    .unwrap_or_default(),
+                // Reports record "main" when no branch was given
+                branch: Some(report.branch.clone()).filter(|b| b != "main"),
+                commit: Some(report.commit_sha.clone()).filter(|c| !c.is_empty()),
+                setup_commands: vec![],
+                max_budget: None,
+                task_set: None,
+            })
+        })
+        .collect()
+}
+
+/// The issue an issue-run report benchmarked: its repo plus the number in its
+/// `issue-N` task id.
+fn report_issue(report: &ComparisonReport) -> Option<issue::IssueRef> {
+    let number = report
+        .task_results
+        .iter()
+        .find_map(|t| t.task_id.strip_prefix("issue-"))?;
+    let slug = report
+        .repo_url
+        .strip_prefix("https://github.com/")?
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    issue::parse_issue_identifier(&format!("{}#{}", slug, number)).ok()
+}
+
+/// Most common language among the files a report's runs accessed.
+fn report_language(report: &ComparisonReport) -> String {
+    let mut counts: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for task in &report.task_results {
+        for file in task
+            .control
+            .files_accessed
+            .iter()
+            .chain(&task.fmm.files_accessed)
+        {
+            if let Some(lang) = crate::sandbox::path_language(Path::new(file)) {
+                *counts.entry(lang).or_insert(0) += 1;
+            }
+        }
+    }
+    // Highest count wins; ties go to the first name alphabetically
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map_or_else(|| "unknown".to_string(), |(lang, _)| lang.to_string())
 }
 
 /// Validation result for a single corpus entry.
@@ -243,47 +1191,157 @@ pub struct ValidationResult {
     pub issue_accessible: bool,
     pub issue_title: Option<String>,
     pub error: Option<String>,
+    /// Set when the issue body is too short to benchmark meaningfully
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_warning: Option<String>,
 }
 
 /// Validate all corpus entries: check that issues are fetchable via `gh`.
-pub fn validate_corpus(corpus: &[CorpusEntry]) -> Vec<ValidationResult> {
-    let mut results = vec![];
+///
+/// With `fail_fast`, stops at the first inaccessible issue; otherwise every
+/// entry is checked. Up to `jobs` lookups run at once; results come back in
+/// corpus order. Issues whose body is shorter than `min_body_chars` after
+/// sanitization get a `body_warning`.
+pub fn validate_corpus(
+    corpus: &[CorpusEntry],
+    fail_fast: bool,
+    jobs: usize,
+    min_body_chars: usize,
+) -> Vec<ValidationResult> {
+    validate_corpus_with(&issue::gh_binary(), corpus, fail_fast, jobs, min_body_chars)
+}
 
-    for (i, entry) in corpus.iter().enumerate() {
-        print!("  [{}/{}] {} ...", i + 1, corpus.len(), entry.id.white());
+/// Default number of concurrent `gh issue view` calls during validation.
+pub const DEFAULT_VALIDATE_JOBS: usize = 4;
 
-        let issue_id = format!("{}#{}", entry.repo, entry.issue);
-        let result =
-            match issue::parse_issue_identifier(&issue_id).and_then(|r| issue::fetch_issue(&r)) {
-                Ok(gh_issue) => {
-                    println!(" {} {}", "+".green(), gh_issue.title.dimmed());
-                    ValidationResult {
-                        id: entry.id.clone(),
-                        issue_accessible: true,
-                        issue_title: Some(gh_issue.title),
-                        error: None,
-                    }
+/// `gh` requests per second across all validation workers (and burst size).
+/// Well under GitHub's secondary limits, which punish bursts of concurrent calls.
+const GH_REQUESTS_PER_SECOND: u32 = 5;
+
+/// Retries after a rate-limit response, backing off exponentially from
+/// [`RATE_LIMIT_BACKOFF`].
+const RATE_LIMIT_RETRIES: u32 = 3;
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(2);
+
+fn validate_corpus_with(
+    gh: &Path,
+    corpus: &[CorpusEntry],
+    fail_fast: bool,
+    jobs: usize,
+    min_body_chars: usize,
+) -> Vec<ValidationResult> {
+    let limiter = RateLimiter::new(GH_REQUESTS_PER_SECOND, GH_REQUESTS_PER_SECOND);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let slots: Mutex<Vec<Option<ValidationResult>>> = Mutex::new(vec![None; corpus.len()]);
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, corpus.len().max(1)) {
+            scope.spawn(|| loop {
+                // Entries are claimed in order, so everything before a
+                // fail-fast failure has been claimed and will finish.
+                if stop.load(Ordering::SeqCst) {
+                    break;
                 }
-                Err(e) => {
-                    println!(" {} {}", "!".red(), e);
-                    ValidationResult {
-                        id: entry.id.clone(),
-                        issue_accessible: false,
-                        issue_title: None,
-                        error: Some(e.to_string()),
-                    }
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some(entry) = corpus.get(i) else {
+                    break;
+                };
+
+                let result = validate_entry(gh, entry, &limiter, min_body_chars);
+                let n = done.fetch_add(1, Ordering::SeqCst) + 1;
+                match &result.issue_title {
+                    Some(title) => outln!(
+                        "  [{}/{}] {} {} {}",
+                        n,
+                        corpus.len(),
+                        entry.id.white(),
+                        "+".green(),
+                        title.dimmed()
+                    ),
+                    None => outln!(
+                        "  [{}/{}] {} {} {}",
+                        n,
+                        corpus.len(),
+                        entry.id.white(),
+                        "!".red(),
+                        result.error.as_deref().unwrap_or("unknown error")
+                    ),
+                }
+                if let Some(warning) = &result.body_warning {
+                    outln!("      {} {}", "!".yellow(), warning);
                 }
-            };
 
+                if fail_fast && !result.issue_accessible {
+                    stop.store(true, Ordering::SeqCst);
+                }
+                slots.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+            });
+        }
+    });
+
+    // Report in corpus order; with --fail-fast, stop at the first failure.
+    let mut results = vec![];
+    for result in slots.into_inner().unwrap_or_else(|e| e.into_inner()) {
+        let Some(result) = result else {
+            break;
+        };
+        let failed = !result.issue_accessible;
         results.push(result);
+        if fail_fast && failed {
+            break;
+        }
     }
-
     results
 }
 
+/// Fetch one corpus issue, paced by `limiter` and retried on rate limits.
+fn validate_entry(
+    gh: &Path,
+    entry: &CorpusEntry,
+    limiter: &RateLimiter,
+    min_body_chars: usize,
+) -> ValidationResult {
+    let issue_id = format!("{}#{}", entry.repo, entry.issue);
+    let mut attempt = 0;
+    let fetched = loop {
+        limiter.acquire();
+        let fetched =
+            issue::parse_issue_identifier(&issue_id).and_then(|r| issue::fetch_issue_with(gh, &r));
+        match fetched {
+            Err(e)
+                if attempt < RATE_LIMIT_RETRIES && issue::is_rate_limit_error(&e.to_string()) =>
+            {
+                std::thread::sleep(backoff_delay(RATE_LIMIT_BACKOFF, attempt));
+                attempt += 1;
+            }
+            other => break other,
+        }
+    };
+
+    match fetched {
+        Ok(gh_issue) => ValidationResult {
+            id: entry.id.clone(),
+            issue_accessible: true,
+            body_warning: gh_issue.thin_body_reason(min_body_chars),
+            issue_title: Some(gh_issue.title),
+            error: None,
+        },
+        Err(e) => ValidationResult {
+            id: entry.id.clone(),
+            issue_accessible: false,
+            issue_title: None,
+            error: Some(e.to_string()),
+            body_warning: None,
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tasks::test_task;
 
     #[test]
     fn load_corpus_valid() {
@@ -317,6 +1375,158 @@ mod tests {
         assert!(entries[1].has_tests);
     }
 
+    #[test]
+    fn load_corpus_csv_matches_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_path = dir.path().join("corpus.json");
+        let csv_path = dir.path().join("corpus.csv");
+        let corpus = serde_json::json!([
+            {
+                "id": "owner/repo#1",
+                "repo": "owner/repo",
+                "issue": 1,
+                "language": "rust"
+            },
+            {
+                "id": "owner/repo#2",
+                "repo": "owner/repo",
+                "issue": 2,
+                "language": "typescript",
+                "size": "large",
+                "type": "feature",
+                "has_tests": true,
+                "expected_files": ["src/index.ts", "src/util.ts"],
+                "estimated_files": 2,
+                "notes": "Crash on save, \"sometimes\"",
+                "setup_commands": [["npm", "ci"], ["npm", "run", "build"]],
+                "max_budget": 2.5
+            }
+        ]);
+        fs::write(&json_path, corpus.to_string()).unwrap();
+        fs::write(
+            &csv_path,
+            "repo,Issue,language,size,type,has_tests,expected_files,estimated_files,notes,setup_commands,max_budget\r\n\
+             owner/repo,1,rust,,,,,,,,\r\n\
+             owner/repo,#2,typescript,large,feature,yes,src/index.ts; src/util.ts,2,\"Crash on save, \"\"sometimes\"\"\",npm ci; npm run build,2.5\r\n",
+        )
+        .unwrap();
+
+        let from_json = load_corpus(&json_path).unwrap();
+        let from_csv = load_corpus(&csv_path).unwrap();
+        assert_eq!(
+            serde_json::to_value(&from_csv).unwrap(),
+            serde_json::to_value(&from_json).unwrap()
+        );
+    }
+
+    #[test]
+    fn load_corpus_csv_reports_bad_row_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corpus.csv");
+        // The first row's quoted notes span two lines, so the bad row is line 4
+        fs::write(
+            &path,
+            "id,repo,issue,language,notes,has_tests\n\
+             o/r#1,o/r,1,rust,\"two\nlines\",no\n\
+             o/r#2,o/r,2,go,,maybe\n",
+        )
+        .unwrap();
+        let err = format!("{:#}", load_corpus(&path).unwrap_err());
+        assert!(
+            err.contains("Line 4: bad 'has_tests' value 'maybe'"),
+            "{}",
+            err
+        );
+
+        fs::write(&path, "id,repo,issue,lang\n").unwrap();
+        let err = format!("{:#}", load_corpus(&path).unwrap_err());
+        assert!(err.contains("Unknown corpus column 'lang'"), "{}", err);
+    }
+
+    #[test]
+    fn corpus_issue_accepts_repo_relative_forms() {
+        let parse = |issue: serde_json::Value| {
+            serde_json::from_value::<CorpusEntry>(serde_json::json!({
+                "id": "owner/repo#123",
+                "repo": "owner/repo",
+                "issue": issue,
+                "language": "rust"
+            }))
+        };
+        for issue in [
+            serde_json::json!(123),
+            serde_json::json!("123"),
+            serde_json::json!("GH-123"),
+            serde_json::json!("gh-123"),
+        ] {
+            assert_eq!(parse(issue).unwrap().issue, 123);
+        }
+        let err = parse(serde_json::json!("PROJ-123"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Invalid issue number"), "{err}");
+    }
+
+    #[test]
+    fn strict_corpus_rejects_unknown_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corpus.json");
+        std::fs::write(
+            &path,
+            r#"[
+                {"id": "a/b#1", "repo": "a/b", "issue": 1, "language": "rust"},
+                {"id": "a/b#2", "repo": "a/b", "issue": 2, "language": "rust", "comit": "abc123"}
+            ]"#,
+        )
+        .unwrap();
+
+        // Lenient by default: the typo is ignored
+        assert_eq!(load_corpus(&path).unwrap()[1].commit, None);
+
+        let err = format!("{:#}", load_corpus_with(&path, true).unwrap_err());
+        assert!(err.contains("Entry 2 (a/b#2)"), "{err}");
+        assert!(err.contains("unknown field `comit`"), "{err}");
+    }
+
+    #[test]
+    fn strict_corpus_columns_cover_every_field() {
+        // Every field set, so none is dropped by `skip_serializing_if`
+        let entry = CorpusEntry {
+            id: "a/b#1".to_string(),
+            repo: "a/b".to_string(),
+            issue: 1,
+            language: "rust".to_string(),
+            size: "large".to_string(),
+            r#type: "feature".to_string(),
+            has_tests: true,
+            expected_files: vec!["src/lib.rs".to_string()],
+            complexity: "complex".to_string(),
+            estimated_files: 3,
+            notes: "n".to_string(),
+            branch: Some("dev".to_string()),
+            commit: Some("abc123".to_string()),
+            setup_commands: vec![vec!["npm".to_string(), "ci".to_string()]],
+            max_budget: Some(2.5),
+            task_set: Some("standard".to_string()),
+        };
+        let value = serde_json::to_value(&entry).unwrap();
+
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut columns: Vec<&str> = CORPUS_CSV_COLUMNS.iter().map(|(name, _)| *name).collect();
+        keys.sort();
+        columns.sort();
+        assert_eq!(keys, columns);
+
+        // The strict loader accepts its own output unchanged
+        let StrictCorpusEntry(back) = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&back).unwrap(), value);
+    }
+
     #[test]
     fn load_corpus_empty_rejected() {
         let dir = tempfile::tempdir().unwrap();
@@ -352,8 +1562,523 @@ mod tests {
     fn batch_options_defaults() {
         let opts = BatchOptions::default();
         assert_eq!(opts.budget, 50.0);
+        assert_eq!(opts.per_issue_budget, 10.0);
+        assert_eq!(opts.max_attempts, 2);
         assert_eq!(opts.runs, 1);
         assert!(opts.filter.is_none());
         assert!(!opts.resume);
     }
+
+    #[test]
+    fn resume_skips_issue_after_repeated_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let failures = CacheManager::new(Some(dir.path().to_path_buf())).unwrap();
+        let max = BatchOptions::default().max_attempts;
+
+        // First and second resume: still attempted, each run fails
+        assert!(!attempts_exhausted(&failures, "o/r#1", max));
+        failures.record_failure("o/r#1", "boom").unwrap();
+        assert!(!attempts_exhausted(&failures, "o/r#1", max));
+        failures.record_failure("o/r#1", "boom").unwrap();
+
+        // Third resume: skipped; other issues and --max-attempts 0 are not
+        assert!(attempts_exhausted(&failures, "o/r#1", max));
+        assert!(!attempts_exhausted(&failures, "o/r#2", max));
+        assert!(!attempts_exhausted(&failures, "o/r#1", 0));
+
+        // A later success clears the record
+        failures.clear_failures("o/r#1");
+        assert!(!attempts_exhausted(&failures, "o/r#1", max));
+    }
+
+    #[test]
+    fn exhausted_issue_not_counted_as_attempted() {
+        let entry = |id: &str, repo: &str| -> CorpusEntry {
+            serde_json::from_str(&format!(
+                r#"{{"id": "{id}", "repo": "{repo}", "issue": 1, "language": "rust"}}"#
+            ))
+            .unwrap()
+        };
+        // o/r#1 has used up its attempts; "bad" fails without network access
+        let corpus = vec![entry("o/r#1", "o/r"), entry("bad", "not a repo")];
+
+        let cache = tempfile::tempdir().unwrap();
+        let failures = CacheManager::new(Some(cache.path().to_path_buf())).unwrap();
+        for _ in 0..BatchOptions::default().max_attempts {
+            failures.record_failure("o/r#1", "boom").unwrap();
+        }
+
+        let aggregate = run_batch(
+            &corpus,
+            &BatchOptions {
+                resume: true,
+                // Skip the shared pre-clone, which would need the network
+                clone_depth: None,
+                cache_dir: Some(cache.path().to_path_buf()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(aggregate.issues_total, 1);
+        assert_eq!(aggregate.issues_completed, 0);
+        assert_eq!(aggregate.failures.len(), 1);
+        assert_eq!(aggregate.failures[0].id, "bad");
+    }
+
+    #[test]
+    fn clone_failure_listed_in_aggregate() {
+        // Rejected before git runs, so no network is needed
+        let sandbox = crate::sandbox::Sandbox::new("test-batch-clone-failure").unwrap();
+        let err = sandbox
+            .clone_repo("https://localhost/o/r.git", None)
+            .unwrap_err();
+
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        agg.failures = vec![failed_issue("o/r#1", &err)];
+        assert_eq!(agg.issues_total, 1);
+        assert_eq!(agg.issues_completed, 0);
+        assert_eq!(agg.failures[0].id, "o/r#1");
+        assert!(agg.failures[0].reason.contains("Invalid repository host"));
+
+        let md = agg.to_markdown();
+        assert!(md.contains("**Completed:** 0 / 1 issues (1 failed"));
+        assert!(md.contains("## Failures"));
+        assert!(md.contains("| o/r#1 | Invalid repository host"));
+
+        // A successful re-run clears the failure
+        let mut rerun = AggregateReport::from_reports(vec![], "sonnet", 1, 1);
+        rerun.per_issue = vec![crate::aggregate::IssueResult {
+            id: "o/r#1".to_string(),
+            ..Default::default()
+        }];
+        let merged = agg.merge(rerun);
+        assert!(merged.failures.is_empty());
+        assert_eq!(merged.issues_completed, 1);
+    }
+
+    #[test]
+    fn expired_deadline_stops_batch_with_partial_aggregate() {
+        let corpus: Vec<CorpusEntry> = (1..=2)
+            .map(|n| {
+                serde_json::from_str(&format!(
+                    r#"{{"id": "o/r#{n}", "repo": "o/r", "issue": {n}, "language": "rust"}}"#
+                ))
+                .unwrap()
+            })
+            .collect();
+
+        // Nothing is cloned, fetched, or run once the deadline has passed
+        let cache = tempfile::tempdir().unwrap();
+        let aggregate = run_batch(
+            &corpus,
+            &BatchOptions {
+                max_wall: Some(Duration::ZERO),
+                cache_dir: Some(cache.path().to_path_buf()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(aggregate.deadline_truncated);
+        assert_eq!(aggregate.issues_completed, 0);
+        assert!(aggregate.failures.is_empty());
+        assert!(aggregate.to_markdown().contains("results are partial"));
+    }
+
+    #[test]
+    fn unparseable_issue_is_recorded_as_failure() {
+        let corpus: Vec<CorpusEntry> = vec![serde_json::from_str(
+            r#"{"id": "bad", "repo": "not a repo", "issue": 1, "language": "rust"}"#,
+        )
+        .unwrap()];
+
+        let cache = tempfile::tempdir().unwrap();
+        let aggregate = run_batch(
+            &corpus,
+            &BatchOptions {
+                cache_dir: Some(cache.path().to_path_buf()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(aggregate.issues_total, 1);
+        assert_eq!(aggregate.failures.len(), 1);
+        assert_eq!(aggregate.failures[0].id, "bad");
+    }
+
+    #[test]
+    fn excluded_entries_produce_no_reports() {
+        let entry = |id: &str| -> CorpusEntry {
+            serde_json::from_str(&format!(
+                r#"{{"id": "{id}", "repo": "o/r", "issue": 1, "language": "rust"}}"#
+            ))
+            .unwrap()
+        };
+        let corpus = vec![entry("o/r#1"), entry("o/r#2"), entry("o/r#3")];
+
+        let dir = tempfile::tempdir().unwrap();
+        let exclude_file = dir.path().join("skip.txt");
+        fs::write(&exclude_file, "# archived\no/r#2\n\no/r#3  # deleted\n").unwrap();
+        let mut exclude = vec!["o/r#1".to_string()];
+        exclude.extend(load_exclude_file(&exclude_file).unwrap());
+        assert_eq!(exclude, vec!["o/r#1", "o/r#2", "o/r#3"]);
+
+        let kept = exclude_entries(corpus.iter().collect(), &exclude[1..]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, "o/r#1");
+
+        // Everything excluded: nothing is fetched or run
+        let aggregate = run_batch(
+            &corpus,
+            &BatchOptions {
+                exclude,
+                cache_dir: Some(dir.path().join("cache")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(aggregate.issues_total, 0);
+        assert!(aggregate.per_issue.is_empty());
+        assert!(aggregate.failures.is_empty());
+    }
+
+    #[test]
+    fn batch_rejects_zero_runs_and_budget() {
+        let err = run_batch(
+            &[],
+            &BatchOptions {
+                runs: 0,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("runs must be at least 1"));
+
+        let err = run_batch(
+            &[],
+            &BatchOptions {
+                budget: 0.0,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("budget must be a positive"));
+
+        assert!(BatchOptions {
+            per_issue_budget: -5.0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(BatchOptions::default().validate().is_ok());
+    }
+
+    #[test]
+    fn per_issue_cap_is_configured_value_clamped_by_remaining() {
+        let opts = BatchOptions {
+            budget: 50.0,
+            per_issue_budget: 4.0,
+            ..Default::default()
+        };
+        assert_eq!(per_issue_cap(&opts, None, 0.0), 4.0);
+        assert_eq!(per_issue_cap(&opts, None, 47.5), 2.5);
+
+        let generous = BatchOptions {
+            per_issue_budget: 25.0,
+            ..opts
+        };
+        assert_eq!(per_issue_cap(&generous, None, 10.0), 25.0);
+    }
+
+    #[test]
+    fn preclone_clones_shared_repo_once() {
+        let entries: Vec<CorpusEntry> = [
+            r#"{"id": "o/r#1", "repo": "o/r", "issue": 1, "language": "rust"}"#,
+            r#"{"id": "o/r#2", "repo": "o/r", "issue": 2, "language": "rust"}"#,
+            r#"{"id": "o/r#3", "repo": "o/r", "issue": "GH-3", "language": "rust"}"#,
+        ]
+        .iter()
+        .map(|json| serde_json::from_str(json).unwrap())
+        .collect();
+        let refs: Vec<&CorpusEntry> = entries.iter().collect();
+
+        let mut cloned = vec![];
+        let failures = preclone_repos(&refs, |url| {
+            cloned.push(url.to_string());
+            Ok(())
+        });
+        assert!(failures.is_empty());
+        assert_eq!(cloned, vec!["https://github.com/o/r"]);
+
+        // Failures are reported per repo, not per entry
+        let failures = preclone_repos(&refs, |_| anyhow::bail!("not found"));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "https://github.com/o/r");
+    }
+
+    #[test]
+    fn entry_budget_overrides_per_issue_default() {
+        let opts = BatchOptions {
+            budget: 50.0,
+            per_issue_budget: 10.0,
+            ..Default::default()
+        };
+        assert_eq!(per_issue_cap(&opts, Some(2.0), 0.0), 2.0);
+        assert_eq!(per_issue_cap(&opts, Some(30.0), 0.0), 30.0);
+        // Still clamped by what's left of the batch
+        assert_eq!(per_issue_cap(&opts, Some(30.0), 45.0), 5.0);
+
+        let mut entry: CorpusEntry = serde_json::from_str(
+            r#"{"id": "o/r#1", "repo": "o/r", "issue": 1, "language": "rust", "max_budget": 2.0}"#,
+        )
+        .unwrap();
+        assert_eq!(entry.max_budget, Some(2.0));
+        assert!(validate_entry_budgets(&[&entry], opts.budget).is_ok());
+
+        entry.max_budget = Some(0.0);
+        let err = validate_entry_budgets(&[&entry], opts.budget).unwrap_err();
+        assert!(err.to_string().contains("must be a positive amount"));
+        entry.max_budget = Some(60.0);
+        let err = validate_entry_budgets(&[&entry], opts.budget).unwrap_err();
+        assert!(err.to_string().contains("exceeds the batch budget"));
+    }
+
+    #[test]
+    fn corpus_from_reports_pins_latest_commit_and_infers_language() {
+        use crate::tasks::Task;
+
+        let report = |job: &str, repo: &str, task_id: &str, commit: &str, files: &[&str]| {
+            let run = |variant: &str| {
+                let mut r = crate::RunResult::from_metrics(
+                    crate::metrics::RunMetrics::default(),
+                    "done".to_string(),
+                    task_id,
+                    variant,
+                );
+                r.files_accessed = files.iter().map(|f| f.to_string()).collect();
+                r
+            };
+            let task = Task {
+                name: "Crash on start".to_string(),
+                max_turns: 50,
+                ..test_task(task_id)
+            };
+            ComparisonReport::new(
+                job.to_string(),
+                repo.to_string(),
+                commit.to_string(),
+                "main".to_string(),
+                vec![(task, run("control"), run("fmm"), None, None)],
+            )
+        };
+
+        let mut old = report(
+            "job-1",
+            "https://github.com/o/r",
+            "issue-7",
+            "aaa111",
+            &["src/lib.rs", "src/main.rs", "README.md"],
+        );
+        old.timestamp = "2026-01-01T00:00:00+00:00".to_string();
+        let mut new = report(
+            "job-2",
+            "https://github.com/o/r",
+            "issue-7",
+            "bbb222",
+            &["src/lib.rs"],
+        );
+        new.timestamp = "2026-02-01T00:00:00+00:00".to_string();
+        new.branch = "dev".to_string();
+        let other = report(
+            "job-3",
+            "https://github.com/o/web.git",
+            "issue-3",
+            "ccc333",
+            &["app/index.ts", "app/util.js", "app/view.tsx"],
+        );
+        let task_set = report(
+            "job-4",
+            "https://github.com/o/r",
+            "explore-1",
+            "ddd444",
+            &[],
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        for r in [&new, &other, &task_set, &old] {
+            r.save(dir.path(), crate::ReportFormat::Json).unwrap();
+        }
+        fs::write(dir.path().join("aggregate.json"), "{\"issues_total\": 0}").unwrap();
+
+        let reports = load_reports(dir.path()).unwrap();
+        assert_eq!(reports.len(), 4);
+        let corpus = corpus_from_reports(&reports);
+        assert_eq!(corpus.len(), 2);
+
+        assert_eq!(corpus[0].id, "o/r#7");
+        assert_eq!(corpus[0].repo, "o/r");
+        assert_eq!(corpus[0].issue, 7);
+        assert_eq!(corpus[0].language, "rust");
+        assert_eq!(corpus[0].commit.as_deref(), Some("bbb222"));
+        assert_eq!(corpus[0].branch.as_deref(), Some("dev"));
+        assert_eq!(corpus[0].notes, "Crash on start");
+
+        assert_eq!(corpus[1].id, "o/web#3");
+        assert_eq!(corpus[1].language, "typescript");
+        assert_eq!(corpus[1].commit.as_deref(), Some("ccc333"));
+        assert_eq!(corpus[1].branch, None);
+    }
+
+    #[test]
+    fn entry_task_set_overrides_batch_default() {
+        let entry: CorpusEntry = serde_json::from_str(
+            r#"{"id": "o/r#1", "repo": "o/r", "issue": 1, "language": "rust", "task_set": "rust-navigation"}"#,
+        )
+        .unwrap();
+        let plain: CorpusEntry =
+            serde_json::from_str(r#"{"id": "o/r#2", "repo": "o/r", "issue": 2, "language": "go"}"#)
+                .unwrap();
+
+        let issue_driven = BatchOptions::default();
+        assert_eq!(
+            entry_task_set(&issue_driven, &entry),
+            Some("rust-navigation")
+        );
+        assert_eq!(entry_task_set(&issue_driven, &plain), None);
+
+        let with_default = BatchOptions {
+            task_set: Some("standard".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            entry_task_set(&with_default, &entry),
+            Some("rust-navigation")
+        );
+        assert_eq!(entry_task_set(&with_default, &plain), Some("standard"));
+    }
+
+    #[cfg(unix)]
+    fn validation_corpus_with_early_failure(dir: &Path) -> (PathBuf, Vec<CorpusEntry>) {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Issue #1 is missing; every other issue resolves.
+        let gh = dir.join("gh");
+        fs::write(
+            &gh,
+            "#!/bin/sh\nif [ \"$3\" = \"1\" ]; then echo 'Could not resolve to an Issue' >&2; exit 1; fi\necho '{\"title\":\"ok\",\"body\":\"\",\"state\":\"OPEN\",\"labels\":[]}'\n",
+        )
+        .unwrap();
+        fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let corpus: Vec<CorpusEntry> = serde_json::from_value(serde_json::json!([
+            {"id": "o/r#1", "repo": "o/r", "issue": 1, "language": "rust"},
+            {"id": "o/r#2", "repo": "o/r", "issue": 2, "language": "rust"},
+            {"id": "o/r#3", "repo": "o/r", "issue": 3, "language": "rust"}
+        ]))
+        .unwrap();
+        (gh, corpus)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validate_fail_fast_stops_at_first_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let (gh, corpus) = validation_corpus_with_early_failure(dir.path());
+
+        let results = validate_corpus_with(&gh, &corpus, true, 1, 0);
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].issue_accessible);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validate_warns_on_empty_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let (gh, corpus) = validation_corpus_with_early_failure(dir.path());
+
+        let results = validate_corpus_with(&gh, &corpus, false, 1, issue::DEFAULT_MIN_BODY_CHARS);
+        // The shim's issues all have an empty body; inaccessible ones can't be judged
+        assert!(results[0].body_warning.is_none());
+        let warning = results[1].body_warning.as_deref().unwrap();
+        assert!(warning.contains("0 characters"));
+        assert!(results[1].issue_accessible);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validate_keep_going_checks_every_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let (gh, corpus) = validation_corpus_with_early_failure(dir.path());
+
+        let results = validate_corpus_with(&gh, &corpus, false, 1, 0);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.iter().filter(|r| r.issue_accessible).count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validate_concurrently_keeps_corpus_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let (gh, corpus) = validation_corpus_with_early_failure(dir.path());
+
+        let results = validate_corpus_with(&gh, &corpus, false, 3, 0);
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["o/r#1", "o/r#2", "o/r#3"]);
+        assert!(!results[0].issue_accessible);
+
+        let results = validate_corpus_with(&gh, &corpus, true, 3, 0);
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].issue_accessible);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generate_corpus_from_fake_gh() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("args.log");
+        let gh = dir.path().join("gh");
+        fs::write(
+            &gh,
+            format!(
+                "#!/bin/sh\necho \"$@\" >> {}\ncase \"$1\" in\n\
+                 issue) echo '[{{\"number\":12,\"title\":\"Crash on start\"}},{{\"number\":7,\"title\":\"Bad flag\"}}]' ;;\n\
+                 repo) echo '{{\"primaryLanguage\":{{\"name\":\"TypeScript\"}}}}' ;;\n\
+                 esac\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let corpus =
+            generate_corpus_with(&gh, "repo:pmndrs/zustand is:issue label:bug", 5).unwrap();
+
+        assert_eq!(corpus.len(), 2);
+        assert_eq!(corpus[0].id, "pmndrs/zustand#12");
+        assert_eq!(corpus[0].repo, "pmndrs/zustand");
+        assert_eq!(corpus[0].issue, 12);
+        assert_eq!(corpus[0].language, "typescript");
+        assert_eq!(corpus[0].size, "medium");
+        assert_eq!(corpus[0].notes, "Crash on start");
+        assert_eq!(corpus[1].issue, 7);
+
+        let args = fs::read_to_string(&log).unwrap();
+        assert!(args.contains("--repo pmndrs/zustand --search is:issue label:bug"));
+        assert!(args.contains("--limit 5"));
+
+        // Round-trips through the corpus loader
+        let path = dir.path().join("corpus.json");
+        fs::write(&path, serde_json::to_string_pretty(&corpus).unwrap()).unwrap();
+        assert_eq!(load_corpus(&path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn generate_corpus_requires_repo_qualifier() {
+        assert!(split_repo_qualifier("is:issue label:bug").is_err());
+        assert!(split_repo_qualifier("repo:owner is:issue").is_err());
+        let (repo, rest) = split_repo_qualifier("label:bug repo:o/r").unwrap();
+        assert_eq!(repo, "o/r");
+        assert_eq!(rest, "label:bug");
+    }
 }
@@ -1,13 +1,16 @@
 //! Result caching layer for comparison runs
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
 use std::time::{Duration, SystemTime};
 
-use crate::report::ComparisonReport;
+use crate::report::{check_schema_version, ComparisonReport, SCHEMA_VERSION};
 use crate::runner::RunResult;
 
 /// Cache key for result lookups
@@ -42,12 +45,53 @@ impl CacheKey {
 /// Cached result entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedResult {
+    /// On-disk format version; 0 for entries written before versioning
+    #[serde(default)]
+    pub schema_version: u32,
     pub key: CacheKey,
     pub result: RunResult,
     pub cached_at: String,
     pub expires_at: String,
 }
 
+/// How long a failed batch issue is remembered before resume retries it freely
+const FAILURE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Record of a batch issue that was run and failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureMarker {
+    pub issue_id: String,
+    /// Consecutive failed attempts while the marker was live
+    pub attempts: u32,
+    pub last_error: String,
+    pub expires_at: String,
+}
+
+/// Cache location used when the platform has no cache directory
+const FALLBACK_CACHE_DIR: &str = ".fmm-cache";
+
+/// Pick the default cache directory: the `FMM_BENCH_CACHE_DIR` override, else
+/// `<platform cache dir>/fmm/compare`, else `./.fmm-cache` (warned about once).
+fn resolve_cache_dir(env_override: Option<OsString>, platform: Option<PathBuf>) -> PathBuf {
+    if let Some(dir) = env_override.filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    match platform {
+        Some(dir) => dir.join("fmm").join("compare"),
+        None => {
+            static WARNED: Once = Once::new();
+            WARNED.call_once(|| {
+                eprintln!(
+                    "{} no platform cache directory; caching results in ./{} (set FMM_BENCH_CACHE_DIR to choose)",
+                    "Warning:".yellow(),
+                    FALLBACK_CACHE_DIR
+                );
+            });
+            PathBuf::from(FALLBACK_CACHE_DIR)
+        }
+    }
+}
+
 /// Cache manager for comparison results
 pub struct CacheManager {
     cache_dir: PathBuf,
@@ -55,16 +99,15 @@ pub struct CacheManager {
     max_size_mb: u64,
     /// In-memory cache for current session
     memory_cache: HashMap<CacheKey, CachedResult>,
+    /// Lifetime of failure markers
+    failure_ttl: Duration,
 }
 
 impl CacheManager {
     /// Create a new cache manager
     pub fn new(cache_dir: Option<PathBuf>) -> Result<Self> {
         let cache_dir = cache_dir.unwrap_or_else(|| {
-            dirs::cache_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("fmm")
-                .join("compare")
+            resolve_cache_dir(std::env::var_os("FMM_BENCH_CACHE_DIR"), dirs::cache_dir())
         });
 
         fs::create_dir_all(&cache_dir).context("Failed to create cache directory")?;
@@ -74,6 +117,7 @@ impl CacheManager {
             ttl: Duration::from_secs(7 * 24 * 3600), // 7 days
             max_size_mb: 100,
             memory_cache: HashMap::new(),
+            failure_ttl: FAILURE_TTL,
         })
     }
 
@@ -84,6 +128,13 @@ impl CacheManager {
         self
     }
 
+    /// Set failure marker TTL
+    #[cfg(test)]
+    pub fn with_failure_ttl(mut self, ttl: Duration) -> Self {
+        self.failure_ttl = ttl;
+        self
+    }
+
     /// Set max cache size
     #[cfg(test)]
     pub fn with_max_size(mut self, max_size_mb: u64) -> Self {
@@ -106,7 +157,12 @@ impl CacheManager {
 
         if cache_path.exists() {
             if let Ok(content) = fs::read_to_string(&cache_path) {
-                if let Ok(cached) = serde_json::from_str::<CachedResult>(&content) {
+                if let Ok(mut cached) = serde_json::from_str::<CachedResult>(&content) {
+                    // Entries from a newer fmm-bench are a miss, not an error
+                    if check_schema_version(cached.schema_version, "Cache entry").is_err() {
+                        return None;
+                    }
+                    cached.schema_version = SCHEMA_VERSION;
                     if !Self::is_expired(&cached.expires_at) {
                         // Update memory cache
                         self.memory_cache.insert(key.clone(), cached.clone());
@@ -130,6 +186,7 @@ impl CacheManager {
                 .context("Cache TTL duration out of range for chrono")?;
 
         let cached = CachedResult {
+            schema_version: SCHEMA_VERSION,
             key: key.clone(),
             result,
             cached_at: now.to_rfc3339(),
@@ -143,7 +200,7 @@ impl CacheManager {
         let filename = key.to_filename();
         let cache_path = self.cache_dir.join(format!("{}.json", filename));
         let json = serde_json::to_string_pretty(&cached)?;
-        fs::write(&cache_path, json).context("Failed to write cache file")?;
+        write_atomic(&cache_path, json.as_bytes()).context("Failed to write cache file")?;
 
         // Evict if needed
         self.evict_if_needed()?;
@@ -190,17 +247,22 @@ impl CacheManager {
         Ok(cleared)
     }
 
+    /// Where `save_report` keeps full comparison reports
+    pub fn reports_dir(&self) -> PathBuf {
+        self.cache_dir.join("reports")
+    }
+
     /// Save a full comparison report
     pub fn save_report(&self, report: &ComparisonReport) -> Result<PathBuf> {
         validate_path_component(&report.job_id)?;
-        let reports_dir = self.cache_dir.join("reports");
+        let reports_dir = self.reports_dir();
         fs::create_dir_all(&reports_dir)?;
 
         let filename = format!("{}.json", report.job_id);
         let report_path = reports_dir.join(filename);
 
         let json = serde_json::to_string_pretty(report)?;
-        fs::write(&report_path, json)?;
+        write_atomic(&report_path, json.as_bytes())?;
 
         Ok(report_path)
     }
@@ -219,7 +281,8 @@ impl CacheManager {
         }
 
         let content = fs::read_to_string(&report_path)?;
-        let report: ComparisonReport = serde_json::from_str(&content)?;
+        let report = ComparisonReport::from_json(&content)
+            .with_context(|| format!("Failed to load report {}", report_path.display()))?;
 
         Ok(Some(report))
     }
@@ -243,6 +306,57 @@ impl CacheManager {
         Ok(reports)
     }
 
+    /// Failed attempts recorded for a batch issue that haven't expired yet
+    pub fn failed_attempts(&self, issue_id: &str) -> u32 {
+        self.load_failure(issue_id).map_or(0, |m| m.attempts)
+    }
+
+    /// Record a failed attempt for a batch issue, returning the attempt count.
+    ///
+    /// Each failure extends the marker's lifetime, so an issue that keeps
+    /// failing stays marked; one left alone expires and is retried.
+    pub fn record_failure(&self, issue_id: &str, error: &str) -> Result<u32> {
+        let attempts = self.failed_attempts(issue_id) + 1;
+        let expires = chrono::Utc::now()
+            + chrono::Duration::from_std(self.failure_ttl)
+                .context("Failure TTL duration out of range for chrono")?;
+        let marker = FailureMarker {
+            issue_id: issue_id.to_string(),
+            attempts,
+            last_error: error.to_string(),
+            expires_at: expires.to_rfc3339(),
+        };
+
+        let path = self.failure_path(issue_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&marker)?;
+        write_atomic(&path, json.as_bytes()).context("Failed to write failure marker")?;
+        Ok(attempts)
+    }
+
+    /// Forget earlier failures of a batch issue (after it succeeds)
+    pub fn clear_failures(&self, issue_id: &str) {
+        let _ = fs::remove_file(self.failure_path(issue_id));
+    }
+
+    fn load_failure(&self, issue_id: &str) -> Option<FailureMarker> {
+        let content = fs::read_to_string(self.failure_path(issue_id)).ok()?;
+        let marker: FailureMarker = serde_json::from_str(&content).ok()?;
+        // Guard against hash collisions between issue ids
+        if marker.issue_id != issue_id || Self::is_expired(&marker.expires_at) {
+            return None;
+        }
+        Some(marker)
+    }
+
+    fn failure_path(&self, issue_id: &str) -> PathBuf {
+        self.cache_dir
+            .join("failures")
+            .join(format!("{}.json", simple_hash(issue_id)))
+    }
+
     fn is_expired(expires_at: &str) -> bool {
         if let Ok(expires) = chrono::DateTime::parse_from_rfc3339(expires_at) {
             chrono::Utc::now() > expires
@@ -271,7 +385,7 @@ impl CacheManager {
         }
 
         // Sort by modification time (oldest first)
-        entries.sort_by(|a, b| a.1.cmp(&b.1));
+        entries.sort_by_key(|a| a.1);
 
         // Remove oldest entries until under limit
         let target_size = self.max_size_mb * 1_000_000 * 80 / 100; // Target 80%
@@ -324,6 +438,22 @@ fn validate_path_component(s: &str) -> Result<()> {
     Ok(())
 }
 
+/// Write `contents` to `path` via a `.tmp` sibling and an atomic rename, so an
+/// interrupted write never leaves a truncated file behind.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    fs::write(&tmp_path, contents)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e).with_context(|| format!("Failed to move {} into place", path.display()));
+    }
+    Ok(())
+}
+
 /// Simple string hash for cache filenames
 fn simple_hash(s: &str) -> String {
     let mut hash = 0u64;
@@ -351,14 +481,22 @@ mod tests {
             output_tokens: 500,
             cache_read_tokens: 0,
             total_cost_usd: 0.01,
+            cost_estimated: false,
+            prompt_hash: String::new(),
             duration_ms: 1000,
             num_turns: 2,
             response: "test".to_string(),
+            transcript: String::new(),
             success: true,
             error: None,
+            session_id: None,
+            actual_model: None,
+            hit_turn_limit: false,
+            repeated_tool_invocations: 0,
             tool_details: HashMap::new(),
             navigation: Default::default(),
             fmm_usage: Default::default(),
+            tool_categories: Default::default(),
         }
     }
 
@@ -377,6 +515,74 @@ mod tests {
         assert_eq!(retrieved.tool_calls, result.tool_calls);
     }
 
+    #[test]
+    fn test_cache_entry_from_newer_schema_is_miss() {
+        let temp = tempdir().unwrap();
+        let key = CacheKey::new("https://github.com/test/repo", "abc123", "task1", "control");
+        {
+            let mut cache = CacheManager::new(Some(temp.path().to_path_buf())).unwrap();
+            cache
+                .set(key.clone(), create_test_result("task1", "control"))
+                .unwrap();
+        }
+
+        let path = temp.path().join(format!("{}.json", key.to_filename()));
+        let mut value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        value["schema_version"] = serde_json::json!(SCHEMA_VERSION + 1);
+        fs::write(&path, value.to_string()).unwrap();
+
+        let mut cache = CacheManager::new(Some(temp.path().to_path_buf())).unwrap();
+        assert!(cache.get(&key).is_none());
+    }
+
+    #[test]
+    fn test_failure_markers_count_and_expire() {
+        let temp = tempdir().unwrap();
+        let mut cache = CacheManager::new(Some(temp.path().to_path_buf())).unwrap();
+
+        assert_eq!(cache.failed_attempts("o/r#1"), 0);
+        assert_eq!(cache.record_failure("o/r#1", "boom").unwrap(), 1);
+        assert_eq!(cache.record_failure("o/r#1", "boom").unwrap(), 2);
+        assert_eq!(cache.failed_attempts("o/r#2"), 0);
+
+        // Markers live apart from cached results
+        assert_eq!(cache.clear_all().unwrap(), 0);
+        assert_eq!(cache.failed_attempts("o/r#1"), 2);
+
+        let short = CacheManager::new(Some(temp.path().to_path_buf()))
+            .unwrap()
+            .with_failure_ttl(Duration::from_secs(0));
+        short.record_failure("o/r#3", "boom").unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(short.failed_attempts("o/r#3"), 0);
+    }
+
+    #[test]
+    fn test_cache_dir_resolution() {
+        let platform = Some(PathBuf::from("/home/u/.cache"));
+        // The override wins, even when the platform has no cache dir
+        assert_eq!(
+            resolve_cache_dir(Some(OsString::from("/tmp/fmm-cache")), None),
+            PathBuf::from("/tmp/fmm-cache")
+        );
+        assert_eq!(
+            resolve_cache_dir(Some(OsString::from("/tmp/fmm-cache")), platform.clone()),
+            PathBuf::from("/tmp/fmm-cache")
+        );
+        assert_eq!(
+            resolve_cache_dir(None, platform.clone()),
+            PathBuf::from("/home/u/.cache/fmm/compare")
+        );
+        // An empty override is ignored
+        assert_eq!(
+            resolve_cache_dir(Some(OsString::new()), platform),
+            PathBuf::from("/home/u/.cache/fmm/compare")
+        );
+        // No override and no platform dir: a dedicated subdirectory, not `.`
+        assert_eq!(resolve_cache_dir(None, None), PathBuf::from(".fmm-cache"));
+    }
+
     #[test]
     fn test_cache_key_filename() {
         let key = CacheKey::new("https://github.com/test/repo", "abc123", "task1", "fmm");
@@ -515,6 +721,37 @@ mod tests {
         assert_eq!(loaded.repo_url, "https://github.com/test/repo");
     }
 
+    #[test]
+    fn test_report_write_is_atomic() {
+        let temp = tempdir().unwrap();
+        let cache = CacheManager::new(Some(temp.path().to_path_buf())).unwrap();
+        let report = ComparisonReport::new(
+            "atomic-job".to_string(),
+            "https://github.com/test/repo".to_string(),
+            "abc123".to_string(),
+            "main".to_string(),
+            vec![],
+        );
+
+        // A stale partial file from an interrupted write is replaced wholesale
+        let reports_dir = temp.path().join("reports");
+        fs::create_dir_all(&reports_dir).unwrap();
+        fs::write(reports_dir.join("atomic-job.json"), "{\"job_id\": \"atom").unwrap();
+
+        let saved_path = cache.save_report(&report).unwrap();
+        assert!(!reports_dir.join("atomic-job.json.tmp").exists());
+        let parsed: ComparisonReport =
+            serde_json::from_str(&fs::read_to_string(&saved_path).unwrap()).unwrap();
+        assert_eq!(parsed.job_id, "atomic-job");
+
+        let leftovers: Vec<_> = fs::read_dir(&reports_dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
     #[test]
     fn test_cache_list_reports() {
         let temp = tempdir().unwrap();
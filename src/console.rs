@@ -0,0 +1,283 @@
+//! Console output routing for progress messages.
+//!
+//! Progress lines normally go to stdout. When a machine-readable report is
+//! being written to stdout (`--stdout`), they are routed to stderr instead so
+//! the JSON stream stays clean. With `--quiet`, progress is dropped entirely
+//! and only final summaries (`summaryln!`) and errors are printed.
+
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Total budgets above this (USD) require confirmation on a terminal.
+pub const BUDGET_CONFIRM_THRESHOLD: f64 = 50.0;
+
+static ROUTE_TO_STDERR: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Route all progress output to stderr (`true`) or stdout (`false`).
+pub fn route_to_stderr(enabled: bool) {
+    ROUTE_TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether progress output is currently routed to stderr.
+pub fn is_routed_to_stderr() -> bool {
+    ROUTE_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// Suppress progress output (`--quiet`); summaries and errors still print.
+pub fn set_quiet(enabled: bool) {
+    QUIET.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether progress output is suppressed.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Serializes tests that change the global routing settings, restoring the
+/// previous settings when dropped.
+#[cfg(test)]
+pub(crate) struct RoutingGuard {
+    quiet: bool,
+    to_stderr: bool,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl RoutingGuard {
+    /// Take the routing lock and apply `quiet` and `to_stderr` until dropped.
+    pub(crate) fn set(quiet: bool, to_stderr: bool) -> Self {
+        static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        // A test that panicked while holding the lock still restored the settings
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let guard = Self {
+            quiet: is_quiet(),
+            to_stderr: is_routed_to_stderr(),
+            _lock: lock,
+        };
+        set_quiet(quiet);
+        route_to_stderr(to_stderr);
+        guard
+    }
+}
+
+#[cfg(test)]
+impl Drop for RoutingGuard {
+    fn drop(&mut self) {
+        set_quiet(self.quiet);
+        route_to_stderr(self.to_stderr);
+    }
+}
+
+/// Where a console line is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+    Suppressed,
+}
+
+/// Destination for a progress line (`summary = false`) or a final-summary
+/// line (`summary = true`), given the quiet and routing settings.
+pub fn stream_for(summary: bool, quiet: bool, to_stderr: bool) -> Stream {
+    if quiet && !summary {
+        Stream::Suppressed
+    } else if to_stderr {
+        Stream::Stderr
+    } else {
+        Stream::Stdout
+    }
+}
+
+/// Destination for progress lines under the current settings.
+pub fn progress_stream() -> Stream {
+    stream_for(false, is_quiet(), is_routed_to_stderr())
+}
+
+/// Destination for final-summary lines under the current settings.
+pub fn summary_stream() -> Stream {
+    stream_for(true, is_quiet(), is_routed_to_stderr())
+}
+
+/// Print a progress line (stdout, or stderr when routed; nothing with `--quiet`).
+#[macro_export]
+macro_rules! outln {
+    () => {
+        match $crate::console::progress_stream() {
+            $crate::console::Stream::Stdout => println!(),
+            $crate::console::Stream::Stderr => eprintln!(),
+            $crate::console::Stream::Suppressed => {}
+        }
+    };
+    ($($arg:tt)*) => {
+        match $crate::console::progress_stream() {
+            $crate::console::Stream::Stdout => println!($($arg)*),
+            $crate::console::Stream::Stderr => eprintln!($($arg)*),
+            $crate::console::Stream::Suppressed => {}
+        }
+    };
+}
+
+/// Print progress without a trailing newline (stdout, or stderr when routed;
+/// nothing with `--quiet`).
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => {{
+        use std::io::Write as _;
+        match $crate::console::progress_stream() {
+            $crate::console::Stream::Stdout => {
+                print!($($arg)*);
+                let _ = std::io::stdout().flush();
+            }
+            $crate::console::Stream::Stderr => eprint!($($arg)*),
+            $crate::console::Stream::Suppressed => {}
+        }
+    }};
+}
+
+/// Write a final-summary line to `stdout`, or to `stderr` when routed there.
+pub fn write_summary_line(
+    line: std::fmt::Arguments,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) {
+    let _ = match summary_stream() {
+        Stream::Stderr => writeln!(stderr, "{}", line),
+        _ => writeln!(stdout, "{}", line),
+    };
+}
+
+/// Print a final-summary line: like `outln!`, but kept with `--quiet`.
+#[macro_export]
+macro_rules! summaryln {
+    () => {
+        $crate::summaryln!("")
+    };
+    ($($arg:tt)*) => {
+        $crate::console::write_summary_line(
+            format_args!($($arg)*),
+            &mut std::io::stdout(),
+            &mut std::io::stderr(),
+        )
+    };
+}
+
+/// What to do before spending `total_budget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetCheck {
+    /// Under the threshold, or confirmed up front with `--yes`.
+    Proceed,
+    /// Over the threshold on a terminal: ask before spending.
+    Confirm,
+    /// Over the threshold without a terminal (CI): proceed, but log it.
+    LogAndProceed,
+}
+
+/// Decide whether a budget needs confirmation.
+pub fn budget_check(total_budget: f64, yes: bool, interactive: bool) -> BudgetCheck {
+    if yes || total_budget <= BUDGET_CONFIRM_THRESHOLD {
+        BudgetCheck::Proceed
+    } else if interactive {
+        BudgetCheck::Confirm
+    } else {
+        BudgetCheck::LogAndProceed
+    }
+}
+
+/// Guard against accidentally large budgets (e.g. `--budget 500`).
+///
+/// Prompts for `y` on a terminal; bails if declined.
+pub fn confirm_budget(total_budget: f64, yes: bool) -> anyhow::Result<()> {
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    match budget_check(total_budget, yes, interactive) {
+        BudgetCheck::Proceed => Ok(()),
+        BudgetCheck::LogAndProceed => {
+            eprintln!(
+                "Budget: up to ${:.2} (above ${:.0}; non-interactive, proceeding)",
+                total_budget, BUDGET_CONFIRM_THRESHOLD
+            );
+            Ok(())
+        }
+        BudgetCheck::Confirm => {
+            eprint!(
+                "This run may spend up to ${:.2} (above ${:.0}). Proceed? [y/N] ",
+                total_budget, BUDGET_CONFIRM_THRESHOLD
+            );
+            let _ = std::io::stderr().flush();
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            if answer.trim().eq_ignore_ascii_case("y") {
+                Ok(())
+            } else {
+                anyhow::bail!(
+                    "Aborted: budget ${:.2} not confirmed (pass --yes to skip this prompt)",
+                    total_budget
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_keeps_summary_only() {
+        // Per-task progress lines are dropped in quiet mode...
+        assert_eq!(stream_for(false, true, false), Stream::Suppressed);
+        assert_eq!(stream_for(false, true, true), Stream::Suppressed);
+        // ...while the final summary still prints, honouring --stdout routing
+        assert_eq!(stream_for(true, true, false), Stream::Stdout);
+        assert_eq!(stream_for(true, true, true), Stream::Stderr);
+        // Without --quiet everything prints
+        assert_eq!(stream_for(false, false, false), Stream::Stdout);
+        assert_eq!(stream_for(false, false, true), Stream::Stderr);
+    }
+
+    #[test]
+    fn test_summary_line_follows_routing() {
+        let write = || {
+            let (mut stdout, mut stderr): (Vec<u8>, Vec<u8>) = (vec![], vec![]);
+            write_summary_line(format_args!("done {}", 1), &mut stdout, &mut stderr);
+            (
+                String::from_utf8(stdout).unwrap(),
+                String::from_utf8(stderr).unwrap(),
+            )
+        };
+
+        let routing = RoutingGuard::set(false, false);
+        assert_eq!(write(), ("done 1\n".to_string(), String::new()));
+        drop(routing);
+
+        // --stdout: summaries move to stderr
+        let routing = RoutingGuard::set(false, true);
+        assert_eq!(write(), (String::new(), "done 1\n".to_string()));
+        drop(routing);
+
+        // --quiet keeps summaries
+        let _routing = RoutingGuard::set(true, false);
+        assert_eq!(write(), ("done 1\n".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_budget_check() {
+        // At or under the threshold never prompts
+        assert_eq!(budget_check(10.0, false, true), BudgetCheck::Proceed);
+        assert_eq!(
+            budget_check(BUDGET_CONFIRM_THRESHOLD, false, true),
+            BudgetCheck::Proceed
+        );
+
+        // Over the threshold
+        assert_eq!(budget_check(500.0, false, true), BudgetCheck::Confirm);
+        assert_eq!(
+            budget_check(500.0, false, false),
+            BudgetCheck::LogAndProceed
+        );
+
+        // --yes bypasses the prompt
+        assert_eq!(budget_check(500.0, true, true), BudgetCheck::Proceed);
+        assert_eq!(budget_check(500.0, true, false), BudgetCheck::Proceed);
+    }
+}
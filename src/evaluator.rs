@@ -6,10 +6,13 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
 use std::process::Command;
 use std::time::Duration;
 
+use crate::sandbox::FMM_INFRA_PATHS;
+
 /// Timeout for test/build commands.
 const CMD_TIMEOUT_SECS: u64 = 300; // 5 minutes
 
@@ -23,45 +26,249 @@ pub struct EvalScores {
     pub files_touched: u32,
     pub diff_lines_added: u32,
     pub diff_lines_removed: u32,
+    /// New test files the agent added (see `is_test_path`).
+    #[serde(default)]
+    pub tests_added: u32,
+    /// The test suite was not run (`--no-test-check`); neutral for the grade
+    #[serde(default)]
+    pub tests_skipped: bool,
+    /// The build was not checked (`--no-build-check`); neutral for the grade
+    #[serde(default)]
+    pub build_skipped: bool,
+    /// Test reruns (`--test-reruns`) disagreed: some passed, some failed
+    #[serde(default)]
+    pub flaky: bool,
+    /// Exit code of each test run, in order (-1: timed out, killed, or failed
+    /// to start); empty when no tests ran. Kept so reports can be re-graded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test_exit_codes: Vec<i32>,
+    /// Exit code of the build check (-1 as above); None when it didn't run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_exit_code: Option<i32>,
     pub grade: String,
+    /// Unified diff of the agent's changes (with `--include-diff`), truncated
+    /// to [`MAX_DIFF_BYTES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Evaluation steps to bypass, for repos whose build or tests can't run in
+/// the sandbox (slow, or missing system dependencies).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EvalOptions {
+    pub skip_build: bool,
+    pub skip_tests: bool,
+    /// Run the test command up to this many times (0 or 1 = once)
+    pub test_reruns: u32,
+    /// How reruns combine into a pass
+    pub rerun_policy: RerunPolicy,
+}
+
+/// How repeated test runs combine into a pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RerunPolicy {
+    /// Pass if any run passes (stops at the first pass)
+    #[default]
+    Any,
+    /// Pass if more than half of all runs pass
+    Majority,
+}
+
+/// Run the tests up to `runs` times via `run_once`; returns whether they
+/// pass under `policy`, and whether the runs disagreed (flaky).
+fn run_tests_with_reruns(
+    runs: u32,
+    policy: RerunPolicy,
+    mut run_once: impl FnMut() -> bool,
+) -> (bool, bool) {
+    let mut outcomes = vec![];
+    for _ in 0..runs.max(1) {
+        let passed = run_once();
+        outcomes.push(passed);
+        if passed && policy == RerunPolicy::Any {
+            break;
+        }
+    }
+    let passes = outcomes.iter().filter(|&&p| p).count();
+    let flaky = passes > 0 && passes < outcomes.len();
+    let passed = match policy {
+        RerunPolicy::Any => passes > 0,
+        RerunPolicy::Majority => passes * 2 > outcomes.len(),
+    };
+    (passed, flaky)
 }
 
 /// Evaluate the sandbox state after a run.
 pub fn evaluate(sandbox_dir: &Path) -> Result<EvalScores> {
-    let diff = capture_diff_stats(sandbox_dir)?;
+    evaluate_with(sandbox_dir, EvalOptions::default())
+}
+
+/// Evaluate the sandbox state, skipping the steps `options` turns off.
+///
+/// Without a known base commit, a commit on top of the clone's single
+/// commit is taken to be the agent's (see `evaluate_since` for sandboxes
+/// with real history).
+pub fn evaluate_with(sandbox_dir: &Path, options: EvalOptions) -> Result<EvalScores> {
+    evaluate_inner(sandbox_dir, None, options)
+}
+
+/// Evaluate everything the agent changed since `base_sha` (the commit the
+/// sandbox was cloned at), committed or not, however many upstream or agent
+/// commits the checkout holds.
+pub fn evaluate_since(
+    sandbox_dir: &Path,
+    base_sha: &str,
+    options: EvalOptions,
+) -> Result<EvalScores> {
+    evaluate_inner(sandbox_dir, Some(base_sha), options)
+}
+
+fn evaluate_inner(
+    sandbox_dir: &Path,
+    base_sha: Option<&str>,
+    options: EvalOptions,
+) -> Result<EvalScores> {
+    let diff = match base_sha {
+        Some(base) => diff_stats_since(sandbox_dir, base)?,
+        None => capture_diff_stats(sandbox_dir)?,
+    };
     let has_commit = diff.files_changed > 0 || diff.lines_added > 0 || diff.lines_removed > 0;
 
-    let runner = detect_test_runner(sandbox_dir);
-    let (tests_existed, tests_pass) = if let Some(ref r) = runner {
-        (true, run_command_ok(sandbox_dir, r))
+    let runner = if options.skip_tests {
+        None
     } else {
-        (false, false)
+        detect_test_runner(sandbox_dir)
+    };
+    let mut flaky = false;
+    let mut test_exit_codes = vec![];
+    let tests = match runner {
+        Some(ref r) => {
+            let passed;
+            (passed, flaky) =
+                run_tests_with_reruns(options.test_reruns, options.rerun_policy, || {
+                    let code = run_command_status(sandbox_dir, r);
+                    test_exit_codes.push(code);
+                    code == 0
+                });
+            if passed {
+                Check::Passed
+            } else {
+                Check::Failed
+            }
+        }
+        None => Check::NotRun,
     };
 
-    let build_cmd = detect_build_command(sandbox_dir);
-    let build_passes = if let Some(ref cmd) = build_cmd {
-        run_command_ok(sandbox_dir, cmd)
+    let build_cmd = if options.skip_build {
+        None
     } else {
+        detect_build_command(sandbox_dir)
+    };
+    let build_exit_code = build_cmd.map(|cmd| run_command_status(sandbox_dir, &cmd));
+    let build = match build_exit_code {
+        Some(0) => Check::Passed,
+        Some(_) => Check::Failed,
         // No build system detected — don't penalize
-        true
+        None => Check::NotRun,
     };
 
-    let grade = compute_grade(has_commit, tests_existed, tests_pass, build_passes);
+    let tests_added = added_files(sandbox_dir, base_sha)
+        .iter()
+        .filter(|p| is_test_path(p))
+        .count() as u32;
 
-    Ok(EvalScores {
+    let mut scores = EvalScores {
         has_commit,
-        tests_pass,
-        tests_existed,
-        build_passes,
+        tests_pass: tests == Check::Passed,
+        tests_existed: tests != Check::NotRun,
+        build_passes: build != Check::Failed && !options.skip_build,
         files_touched: diff.files_changed,
         diff_lines_added: diff.lines_added,
         diff_lines_removed: diff.lines_removed,
-        grade,
-    })
+        tests_added,
+        tests_skipped: options.skip_tests,
+        build_skipped: options.skip_build,
+        flaky,
+        test_exit_codes,
+        build_exit_code,
+        grade: String::new(),
+        diff: None,
+    };
+    scores.regrade();
+    Ok(scores)
+}
+
+/// Evaluate a directory outside a comparison (the `eval` subcommand): a git
+/// checkout, or a sandbox root holding `control/` and `fmm/` checkouts.
+///
+/// Returns one labelled score set per checkout evaluated.
+pub fn evaluate_path(dir: &Path, options: EvalOptions) -> Result<Vec<(String, EvalScores)>> {
+    if dir.join(".git").exists() {
+        return Ok(vec![(
+            dir.display().to_string(),
+            evaluate_with(dir, options)?,
+        )]);
+    }
+    let variants: Vec<&str> = ["control", "fmm"]
+        .into_iter()
+        .filter(|v| dir.join(v).join(".git").exists())
+        .collect();
+    if variants.is_empty() {
+        anyhow::bail!(
+            "{} is not a git checkout or a sandbox with control/ and fmm/ checkouts",
+            dir.display()
+        );
+    }
+    variants
+        .into_iter()
+        .map(|v| Ok((v.to_string(), evaluate_with(&dir.join(v), options)?)))
+        .collect()
+}
+
+impl EvalScores {
+    /// Recompute `grade` from the recorded checks with this build's rubric
+    /// (see `standard_grade`), e.g. for a report graded by an older version.
+    pub fn regrade(&mut self) {
+        self.regrade_with(standard_grade);
+    }
+
+    /// Recompute `grade` from the recorded checks with another rubric.
+    pub fn regrade_with(&mut self, rubric: impl Fn(&EvalScores) -> String) {
+        self.grade = rubric(self);
+    }
+
+    /// Multi-line summary: grade, diff stats, tests and build.
+    pub fn describe(&self) -> String {
+        let tests = match (self.tests_skipped, self.tests_existed, self.tests_pass) {
+            (true, _, _) => "skipped",
+            (_, false, _) => "none detected",
+            (_, true, true) if self.flaky => "passed (flaky)",
+            (_, true, true) => "passed",
+            (_, true, false) => "failed",
+        };
+        let build = if self.build_skipped {
+            "skipped"
+        } else if self.build_passes {
+            "passed"
+        } else {
+            "failed"
+        };
+        format!(
+            "Grade: {}\nDiff: {} file(s), +{} -{}\nTests: {} ({} added)\nBuild: {}",
+            self.grade,
+            self.files_touched,
+            self.diff_lines_added,
+            self.diff_lines_removed,
+            tests,
+            self.tests_added,
+            build
+        )
+    }
 }
 
 // ── diff stats ──────────────────────────────────────────────────────────────
 
+#[derive(Debug)]
 struct DiffStats {
     files_changed: u32,
     lines_added: u32,
@@ -76,15 +283,16 @@ fn capture_diff_stats(dir: &Path) -> Result<DiffStats> {
         .output()
         .ok();
 
-    let commit_count: u32 = log_output
-        .as_ref()
-        .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse().ok())
-        .unwrap_or(1);
+    let commit_count = match log_output {
+        Some(o) if o.status.success() => parse_commit_count(&o.stdout)?,
+        _ => 1,
+    };
 
     // If Claude committed (>1 commit), diff against parent to see committed changes
     let committed_diff = if commit_count >= 2 {
         let output = Command::new("git")
-            .args(["diff", "HEAD~1", "--numstat"])
+            .args(["diff", "HEAD~1", "--numstat", "-M"])
+            .args(infra_exclude_pathspecs())
             .current_dir(dir)
             .output()
             .ok();
@@ -105,7 +313,8 @@ fn capture_diff_stats(dir: &Path) -> Result<DiffStats> {
         text
     } else {
         let output = Command::new("git")
-            .args(["diff", "HEAD", "--numstat"])
+            .args(["diff", "HEAD", "--numstat", "-M"])
+            .args(infra_exclude_pathspecs())
             .current_dir(dir)
             .output()
             .context("git diff failed")?;
@@ -115,32 +324,206 @@ fn capture_diff_stats(dir: &Path) -> Result<DiffStats> {
     parse_numstat(&diff_text)
 }
 
+/// Diff stats for everything changed since `base_sha`, committed or not.
+fn diff_stats_since(dir: &Path, base_sha: &str) -> Result<DiffStats> {
+    let output = Command::new("git")
+        .args(["diff", base_sha, "--numstat", "-M"])
+        .args(infra_exclude_pathspecs())
+        .current_dir(dir)
+        .output()
+        .context("git diff failed")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git diff {} failed: {}",
+            base_sha,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    parse_numstat(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Files the agent created: added since `base_sha` (or, without one, in the
+/// commit on top of the clone and the working tree), plus untracked files
+/// (fmm infrastructure excluded).
+fn added_files(dir: &Path, base_sha: Option<&str>) -> Vec<String> {
+    let git_lines = |args: &[&str]| -> Vec<String> {
+        Command::new("git")
+            .args(args)
+            .args(infra_exclude_pathspecs())
+            .current_dir(dir)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| {
+                String::from_utf8_lossy(&o.stdout)
+                    .lines()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut files = git_lines(&["ls-files", "--others", "--exclude-standard"]);
+    if let Some(base) = base_sha {
+        files.extend(git_lines(&["diff", base, "--name-only", "--diff-filter=A"]));
+        files.sort();
+        files.dedup();
+        return files;
+    }
+    files.extend(git_lines(&[
+        "diff",
+        "HEAD",
+        "--name-only",
+        "--diff-filter=A",
+    ]));
+    let has_parent = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD~1"])
+        .current_dir(dir)
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if has_parent {
+        files.extend(git_lines(&[
+            "diff",
+            "HEAD~1",
+            "--name-only",
+            "--diff-filter=A",
+        ]));
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Whether a path looks like a test file: a `test`/`spec` name
+/// (`test_x.py`, `x_test.go`, `x.test.ts`, `x.spec.js`) or a file under a
+/// `test(s)/`, `__tests__/` or `spec/` directory. Names that merely contain
+/// "test" (`latest.rs`, `contest.py`) don't count.
+fn is_test_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    let mut parts: Vec<&str> = lower.split('/').collect();
+    let file = parts.pop().unwrap_or("");
+    let stem = file.split('.').next().unwrap_or("");
+    file.starts_with("test_")
+        || stem.ends_with("_test")
+        || file.contains(".test.")
+        || file.contains(".spec.")
+        || parts
+            .iter()
+            .any(|d| matches!(*d, "test" | "tests" | "__tests__" | "spec"))
+}
+
+/// Cap on an embedded unified diff (per condition).
+pub const MAX_DIFF_BYTES: usize = 64 * 1024;
+
+/// Unified diff of everything changed since `base_sha`, committed or not
+/// (fmm infrastructure excluded), truncated to `max_bytes` with a note.
+pub fn capture_diff(dir: &Path, base_sha: &str, max_bytes: usize) -> Result<String> {
+    let output = Command::new("git")
+        .args(["diff", base_sha])
+        .args(infra_exclude_pathspecs())
+        .current_dir(dir)
+        .output()
+        .context("git diff failed")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git diff {} failed: {}",
+            base_sha,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(truncate_diff(
+        &String::from_utf8_lossy(&output.stdout),
+        max_bytes,
+    ))
+}
+
+/// Cut a diff at the last whole line within `max_bytes`, noting what was dropped.
+fn truncate_diff(diff: &str, max_bytes: usize) -> String {
+    if diff.len() <= max_bytes {
+        return diff.to_string();
+    }
+    let mut end = max_bytes;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    let end = diff[..end].rfind('\n').map_or(end, |i| i + 1);
+    format!(
+        "{}... (diff truncated: {} of {} bytes shown)\n",
+        &diff[..end],
+        end,
+        diff.len()
+    )
+}
+
+/// Pathspecs limiting a diff to Claude's changes (fmm infrastructure excluded).
+fn infra_exclude_pathspecs() -> Vec<String> {
+    let mut specs = vec!["--".to_string(), ".".to_string()];
+    specs.extend(FMM_INFRA_PATHS.iter().map(|p| format!(":(exclude){}", p)));
+    specs
+}
+
+/// Sum `git diff --numstat -M` output.
+///
+/// Renames (`old => new`, or `dir/{old => new}/file`) count as one changed
+/// file, keyed by destination, so a file is never counted twice.
 fn parse_numstat(text: &str) -> Result<DiffStats> {
-    let mut files_changed = 0u32;
+    let mut files: HashSet<String> = HashSet::new();
     let mut lines_added = 0u32;
     let mut lines_removed = 0u32;
 
     for line in text.lines() {
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 3 {
-            files_changed += 1;
-            // Binary files show "-" instead of numbers
-            if let Ok(added) = parts[0].parse::<u32>() {
-                lines_added += added;
-            }
-            if let Ok(removed) = parts[1].parse::<u32>() {
-                lines_removed += removed;
-            }
+        let parts: Vec<&str> = line.splitn(3, '\t').collect();
+        if parts.len() == 3 {
+            files.insert(numstat_destination(parts[2]));
+            lines_added += numstat_count(parts[0], line)?;
+            lines_removed += numstat_count(parts[1], line)?;
         }
     }
 
     Ok(DiffStats {
-        files_changed,
+        files_changed: files.len() as u32,
         lines_added,
         lines_removed,
     })
 }
 
+/// A numstat line count; binary files show "-" instead of a number (0).
+/// Anything else means the diff output is garbled, so it is an error.
+fn numstat_count(field: &str, line: &str) -> Result<u32> {
+    if field == "-" {
+        return Ok(0);
+    }
+    field
+        .parse()
+        .with_context(|| format!("Malformed git numstat line: {:?}", line))
+}
+
+/// Parse `git rev-list --count` output, rejecting anything but a number.
+fn parse_commit_count(stdout: &[u8]) -> Result<u32> {
+    let text = std::str::from_utf8(stdout).context("git rev-list output is not valid UTF-8")?;
+    text.trim().parse().with_context(|| {
+        format!(
+            "git rev-list returned an invalid commit count: {:?}",
+            text.trim()
+        )
+    })
+}
+
+/// Destination path of a numstat path field, resolving rename notation.
+fn numstat_destination(path: &str) -> String {
+    if let (Some(open), Some(close)) = (path.find('{'), path.rfind('}')) {
+        if let Some((_, new)) = path[open + 1..close].split_once(" => ") {
+            let joined = format!("{}{}{}", &path[..open], new, &path[close + 1..]);
+            // `dir/{ => sub}/f` and `dir/{sub => }/f` leave a doubled slash
+            return joined.replace("//", "/");
+        }
+    }
+    match path.split_once(" => ") {
+        Some((_, new)) => new.to_string(),
+        None => path.to_string(),
+    }
+}
+
 // ── test runner detection ───────────────────────────────────────────────────
 
 /// Detect the test command for a repository.
@@ -229,9 +612,16 @@ fn detect_build_command(dir: &Path) -> Option<Vec<String>> {
 
 // ── command execution ───────────────────────────────────────────────────────
 
-fn run_command_ok(dir: &Path, cmd: &[String]) -> bool {
+/// Run `cmd` in `dir` with output discarded, killing it after the command timeout.
+pub(crate) fn run_command_ok(dir: &Path, cmd: &[String]) -> bool {
+    run_command_status(dir, cmd) == 0
+}
+
+/// [`run_command_ok`], returning the exit code: -1 when the command timed
+/// out, was killed by a signal, or could not be started.
+pub(crate) fn run_command_status(dir: &Path, cmd: &[String]) -> i32 {
     if cmd.is_empty() {
-        return false;
+        return -1;
     }
 
     let Ok(mut child) = Command::new(&cmd[0])
@@ -241,7 +631,7 @@ fn run_command_ok(dir: &Path, cmd: &[String]) -> bool {
         .stderr(std::process::Stdio::null())
         .spawn()
     else {
-        return false;
+        return -1;
     };
 
     let timeout = Duration::from_secs(CMD_TIMEOUT_SECS);
@@ -249,45 +639,67 @@ fn run_command_ok(dir: &Path, cmd: &[String]) -> bool {
 
     loop {
         match child.try_wait() {
-            Ok(Some(status)) => return status.success(),
+            Ok(Some(status)) => return status.code().unwrap_or(-1),
             Ok(None) => {
                 if start.elapsed() > timeout {
                     let _ = child.kill();
-                    return false;
+                    return -1;
                 }
                 std::thread::sleep(Duration::from_millis(250));
             }
-            Err(_) => return false,
+            Err(_) => return -1,
         }
     }
 }
 
 // ── grading ─────────────────────────────────────────────────────────────────
 
-fn compute_grade(
-    has_commit: bool,
-    tests_existed: bool,
-    tests_pass: bool,
-    build_passes: bool,
-) -> String {
+/// Outcome of the test or build step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Check {
+    Passed,
+    Failed,
+    /// Nothing detected to run, or skipped by `EvalOptions`: neutral
+    NotRun,
+}
+
+/// The standard rubric over recorded scores (see `compute_grade`).
+///
+/// Added tests only earn credit once run: with a detected runner they're
+/// part of `tests_pass` (grade A); without one they can't be verified.
+pub fn standard_grade(scores: &EvalScores) -> String {
+    let tests = match (scores.tests_existed, scores.tests_pass) {
+        (false, _) => Check::NotRun,
+        (true, true) => Check::Passed,
+        (true, false) => Check::Failed,
+    };
+    let build = match scores.build_exit_code {
+        _ if scores.build_skipped => Check::NotRun,
+        Some(0) => Check::Passed,
+        Some(_) => Check::Failed,
+        // No build detected, or a report from before exit codes were kept
+        None if scores.build_passes => Check::NotRun,
+        None => Check::Failed,
+    };
+    compute_grade(scores.has_commit, tests, build)
+}
+
+fn compute_grade(has_commit: bool, tests: Check, build: Check) -> String {
     if !has_commit {
         return "F".to_string();
     }
 
-    if !build_passes {
+    if build == Check::Failed {
         return "D".to_string();
     }
 
-    if tests_existed && tests_pass {
-        return "A".to_string();
+    match tests {
+        Check::Passed => "A",
+        Check::Failed => "C",
+        // Build passes (or wasn't checked), no tests to validate
+        Check::NotRun => "B",
     }
-
-    if tests_existed && !tests_pass {
-        return "C".to_string();
-    }
-
-    // Build passes, no tests to validate
-    "B".to_string()
+    .to_string()
 }
 
 #[cfg(test)]
@@ -296,27 +708,108 @@ mod tests {
 
     #[test]
     fn grade_a_tests_pass() {
-        assert_eq!(compute_grade(true, true, true, true), "A");
+        assert_eq!(compute_grade(true, Check::Passed, Check::Passed), "A");
     }
 
     #[test]
     fn grade_b_no_tests() {
-        assert_eq!(compute_grade(true, false, false, true), "B");
+        assert_eq!(compute_grade(true, Check::NotRun, Check::Passed), "B");
     }
 
     #[test]
     fn grade_c_tests_fail() {
-        assert_eq!(compute_grade(true, true, false, true), "C");
+        assert_eq!(compute_grade(true, Check::Failed, Check::Passed), "C");
     }
 
     #[test]
     fn grade_d_build_fails() {
-        assert_eq!(compute_grade(true, true, true, false), "D");
+        assert_eq!(compute_grade(true, Check::Passed, Check::Failed), "D");
     }
 
     #[test]
     fn grade_f_no_commit() {
-        assert_eq!(compute_grade(false, true, true, true), "F");
+        assert_eq!(compute_grade(false, Check::Passed, Check::Passed), "F");
+    }
+
+    #[test]
+    fn grade_skipped_checks_are_neutral() {
+        // A skipped build can't drag the grade to D
+        assert_eq!(compute_grade(true, Check::Passed, Check::NotRun), "A");
+        assert_eq!(compute_grade(true, Check::Failed, Check::NotRun), "C");
+        // Skipped tests neither earn an A nor cost a C
+        assert_eq!(compute_grade(true, Check::NotRun, Check::Passed), "B");
+        assert_eq!(compute_grade(true, Check::NotRun, Check::NotRun), "B");
+        assert_eq!(compute_grade(true, Check::NotRun, Check::Failed), "D");
+        assert_eq!(compute_grade(false, Check::NotRun, Check::NotRun), "F");
+    }
+
+    #[test]
+    fn evaluate_with_skips_failing_build_and_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        git(dir.path(), &["config", "user.email", "t@example.com"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        // A Cargo.toml that can't build, so a real check would fail
+        std::fs::write(dir.path().join("Cargo.toml"), "not toml [\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "init"]);
+        std::fs::write(dir.path().join("notes.txt"), "fix\n").unwrap();
+        git(dir.path(), &["add", "."]);
+
+        let options = EvalOptions {
+            skip_build: true,
+            skip_tests: true,
+            ..Default::default()
+        };
+        let scores = evaluate_with(dir.path(), options).unwrap();
+        assert!(scores.build_skipped && scores.tests_skipped);
+        assert!(!scores.tests_existed);
+        assert_eq!(scores.grade, "B");
+        assert!(scores.describe().contains("Tests: skipped"));
+        assert!(scores.describe().contains("Build: skipped"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn flaky_tests_pass_on_rerun() {
+        let dir = tempfile::tempdir().unwrap();
+        // Fails the first time, passes once the marker exists
+        let cmd: Vec<String> = ["sh", "-c", "[ -f ran ] || { touch ran; exit 1; }"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let (passed, flaky) =
+            run_tests_with_reruns(3, RerunPolicy::Any, || run_command_ok(dir.path(), &cmd));
+        assert!(passed);
+        assert!(flaky);
+
+        // A single run only sees the failure
+        std::fs::remove_file(dir.path().join("ran")).unwrap();
+        let (passed, flaky) =
+            run_tests_with_reruns(1, RerunPolicy::Any, || run_command_ok(dir.path(), &cmd));
+        assert!(!passed);
+        assert!(!flaky);
+    }
+
+    #[test]
+    fn majority_policy_needs_most_runs_to_pass() {
+        let outcomes = |seq: Vec<bool>| {
+            let mut it = seq.into_iter();
+            move || it.next().unwrap()
+        };
+        assert_eq!(
+            run_tests_with_reruns(3, RerunPolicy::Majority, outcomes(vec![false, true, true])),
+            (true, true)
+        );
+        assert_eq!(
+            run_tests_with_reruns(3, RerunPolicy::Majority, outcomes(vec![false, true, false])),
+            (false, true)
+        );
+        assert_eq!(
+            run_tests_with_reruns(2, RerunPolicy::Majority, outcomes(vec![true, true])),
+            (true, false)
+        );
     }
 
     #[test]
@@ -328,6 +821,17 @@ mod tests {
         assert_eq!(stats.lines_removed, 3);
     }
 
+    #[test]
+    fn parse_numstat_rejects_garbled_counts() {
+        let err = parse_numstat("1\u{fffd}\t3\tsrc/main.rs\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Malformed git numstat line"), "{err}");
+        assert_eq!(parse_commit_count(b"3\n").unwrap(), 3);
+        assert!(parse_commit_count(b"3\xff\n").is_err());
+        assert!(parse_commit_count(b"three").is_err());
+    }
+
     #[test]
     fn parse_numstat_empty() {
         let stats = parse_numstat("").unwrap();
@@ -345,6 +849,24 @@ mod tests {
         assert_eq!(stats.lines_removed, 2);
     }
 
+    #[test]
+    fn parse_numstat_renames() {
+        let input = "0\t0\told.rs => new.rs\n\
+                     3\t1\tsrc/{parser => syntax}/lexer.rs\n\
+                     2\t0\tsrc/syntax/lexer.rs\n\
+                     0\t0\tdocs/{ => guide}/intro.md\n";
+        let stats = parse_numstat(input).unwrap();
+        // The pure rename counts once; the moved lexer is one file, not two
+        assert_eq!(stats.files_changed, 3);
+        assert_eq!(stats.lines_added, 5);
+        assert_eq!(stats.lines_removed, 1);
+        assert_eq!(
+            numstat_destination("docs/{ => guide}/intro.md"),
+            "docs/guide/intro.md"
+        );
+        assert_eq!(numstat_destination("a/{b => }/c.rs"), "a/c.rs");
+    }
+
     #[test]
     fn detect_cargo_test_runner() {
         let dir = tempfile::tempdir().unwrap();
@@ -460,4 +982,162 @@ mod tests {
         assert_eq!(scores.files_touched, 0);
         assert_eq!(scores.grade, "F");
     }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn capture_diff_shows_changed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        git(dir.path(), &["config", "user.email", "t@example.com"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        std::fs::write(dir.path().join("lib.rs"), "fn old() {}\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "init"]);
+        let base = git(dir.path(), &["rev-parse", "HEAD"]);
+
+        // One committed change, one left in the working tree
+        std::fs::write(dir.path().join("lib.rs"), "fn new() {}\n").unwrap();
+        git(dir.path(), &["commit", "-am", "fix"]);
+        std::fs::write(dir.path().join("lib.rs"), "fn newer() {}\n").unwrap();
+
+        let diff = capture_diff(dir.path(), &base, MAX_DIFF_BYTES).unwrap();
+        assert!(diff.contains("diff --git a/lib.rs b/lib.rs"));
+        assert!(diff.contains("-fn old() {}"));
+        assert!(diff.contains("+fn newer() {}"));
+    }
+
+    #[test]
+    fn test_path_detection() {
+        assert!(is_test_path("tests/integration.rs"));
+        assert!(is_test_path("pkg/store_test.go"));
+        assert!(is_test_path("src/test_utils.py"));
+        assert!(is_test_path("src/__tests__/store.ts"));
+        assert!(is_test_path("src/store.spec.ts"));
+        assert!(!is_test_path("src/store.ts"));
+        assert!(!is_test_path("README.md"));
+        assert!(is_test_path("src/app.test.ts"));
+        assert!(is_test_path("test/helpers.rb"));
+        // "test" inside an ordinary name
+        for path in [
+            "src/latest.rs",
+            "scripts/contest.py",
+            "pkg/attestation.go",
+            "src/testing.rs",
+            "protest/main.c",
+        ] {
+            assert!(!is_test_path(path), "{} counted as a test", path);
+        }
+    }
+
+    #[test]
+    fn evaluate_counts_added_test_files() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        git(dir.path(), &["config", "user.email", "t@example.com"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        std::fs::write(dir.path().join("lib.py"), "def f(): pass\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "init"]);
+
+        // Agent commits a fix plus a new test, and leaves another test untracked
+        std::fs::write(dir.path().join("lib.py"), "def f(): return 1\n").unwrap();
+        std::fs::create_dir(dir.path().join("tests")).unwrap();
+        std::fs::write(dir.path().join("tests/test_lib.py"), "def test_f(): pass\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "fix"]);
+        std::fs::write(dir.path().join("lib_test.py"), "def test_g(): pass\n").unwrap();
+
+        let scores = evaluate(dir.path()).unwrap();
+        assert_eq!(scores.tests_added, 2);
+        assert!(scores.has_commit);
+    }
+
+    #[test]
+    fn evaluate_since_ignores_upstream_history() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        git(dir.path(), &["config", "user.email", "t@example.com"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        std::fs::write(dir.path().join("lib.py"), "def f(): pass\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "first"]);
+        // Upstream's own last commit adds a test, as in a deep clone
+        std::fs::create_dir(dir.path().join("tests")).unwrap();
+        std::fs::write(dir.path().join("tests/test_lib.py"), "def test_f(): pass\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "upstream"]);
+        let base = git(dir.path(), &["rev-parse", "HEAD"]);
+
+        // The agent never committed or changed anything
+        let scores = evaluate_since(dir.path(), &base, EvalOptions::default()).unwrap();
+        assert!(!scores.has_commit);
+        assert_eq!(scores.files_touched, 0);
+        assert_eq!(scores.diff_lines_added, 0);
+        assert_eq!(scores.tests_added, 0);
+
+        // Two agent commits, the first adding a test: all of it counts
+        std::fs::write(
+            dir.path().join("tests/test_more.py"),
+            "def test_g(): pass\n",
+        )
+        .unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "agent test"]);
+        std::fs::write(dir.path().join("lib.py"), "def f(): return 1\n").unwrap();
+        git(dir.path(), &["commit", "-am", "agent fix"]);
+        let scores = evaluate_since(dir.path(), &base, EvalOptions::default()).unwrap();
+        assert!(scores.has_commit);
+        assert_eq!(scores.files_touched, 2);
+        assert_eq!(scores.tests_added, 1);
+    }
+
+    #[test]
+    fn evaluate_path_scores_staged_change() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        git(dir.path(), &["config", "user.email", "t@example.com"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        std::fs::write(dir.path().join("notes.txt"), "one\ntwo\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "init"]);
+
+        std::fs::write(dir.path().join("notes.txt"), "one\n2\nthree\n").unwrap();
+        git(dir.path(), &["add", "."]);
+
+        let results = evaluate_path(dir.path(), EvalOptions::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        let scores = &results[0].1;
+        assert!(scores.has_commit);
+        assert_eq!(scores.files_touched, 1);
+        assert_eq!(scores.diff_lines_added, 2);
+        assert_eq!(scores.diff_lines_removed, 1);
+        assert!(!scores.tests_existed);
+        assert_eq!(scores.grade, "B");
+        assert_eq!(
+            scores.describe(),
+            "Grade: B\nDiff: 1 file(s), +2 -1\nTests: none detected (0 added)\nBuild: passed"
+        );
+
+        let not_repo = tempfile::tempdir().unwrap();
+        assert!(evaluate_path(not_repo.path(), EvalOptions::default()).is_err());
+    }
+
+    #[test]
+    fn truncate_diff_notes_cut() {
+        let diff = "line one\nline two\nline three\n";
+        assert_eq!(truncate_diff(diff, 100), diff);
+
+        let cut = truncate_diff(diff, 12);
+        assert!(cut.starts_with("line one\n..."));
+        assert!(cut.contains("diff truncated: 9 of 29 bytes shown"));
+    }
 }
@@ -5,6 +5,7 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// A parsed GitHub issue reference.
@@ -38,6 +39,29 @@ impl std::fmt::Display for IssueRef {
     }
 }
 
+/// Default minimum issue body length (after sanitization) worth benchmarking.
+pub const DEFAULT_MIN_BODY_CHARS: usize = 30;
+
+/// Marker appended to an issue body cut down by `--max-body-tokens`.
+pub const BODY_TRUNCATED_MARKER: &str = "\n\n[... issue body truncated ...]";
+
+/// Bytes kept from each file fetched by `--fetch-linked`.
+pub const LINKED_CONTENT_MAX_BYTES: usize = 16 * 1024;
+
+/// Linked files fetched per issue, at most.
+const MAX_LINKED_URLS: usize = 5;
+
+/// Marker appended to linked content cut at `LINKED_CONTENT_MAX_BYTES`.
+const LINKED_TRUNCATED_MARKER: &str = "\n[... linked content truncated ...]";
+
+/// Rough token count for `text`: one token per 4 characters, rounded up.
+///
+/// Close enough to real tokenizers on English prose and code to compare
+/// prompt sizes; not meant for billing.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
 /// Fetched issue data from GitHub.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubIssue {
@@ -53,6 +77,20 @@ impl GitHubIssue {
     ///
     /// Both conditions (control and fmm) receive the exact same prompt.
     pub fn to_prompt(&self) -> String {
+        self.to_prompt_with_commit_template(None)
+    }
+
+    /// Build the benchmark prompt, optionally requiring a commit message format
+    /// (e.g. `fmm-bench: <summary>`) so the agent's commit is easy to find.
+    pub fn to_prompt_with_commit_template(&self, commit_template: Option<&str>) -> String {
+        let commit_instruction = match commit_template {
+            Some(template) => format!(
+                "When done, commit your changes with a message of the form `{}`.",
+                template
+            ),
+            None => "When done, commit your changes with a descriptive message.".to_string(),
+        };
+
         format!(
             r#"Here is a GitHub issue for this repository:
 
@@ -64,10 +102,187 @@ impl GitHubIssue {
 
 Fix this issue. Make the minimal changes needed to resolve it.
 Do not modify tests unless the issue specifically requires test changes.
-When done, commit your changes with a descriptive message."#,
-            self.title, self.body
+{}"#,
+            self.title, self.body, commit_instruction
         )
     }
+
+    /// Why the body is too thin to benchmark, if it is shorter than
+    /// `min_chars` after sanitization (`0` disables the check).
+    pub fn thin_body_reason(&self, min_chars: usize) -> Option<String> {
+        let len = sanitize_body(&self.body).chars().count();
+        (len < min_chars).then(|| {
+            format!(
+                "issue body has {} characters after sanitization (minimum {})",
+                len, min_chars
+            )
+        })
+    }
+
+    /// Cut the body to roughly `max_tokens` estimated tokens (see
+    /// `estimate_tokens`), breaking at whitespace where possible and
+    /// appending `BODY_TRUNCATED_MARKER`. Returns whether it truncated.
+    pub fn truncate_body(&mut self, max_tokens: usize) -> bool {
+        if estimate_tokens(&self.body) <= max_tokens {
+            return false;
+        }
+        let max_chars = max_tokens * 4;
+        let cut = self
+            .body
+            .char_indices()
+            .nth(max_chars)
+            .map_or(self.body.len(), |(i, _)| i);
+        let head = &self.body[..cut];
+        // Don't split a word unless the body has no whitespace to break on
+        let head = match head.rfind(char::is_whitespace) {
+            Some(i) if i > cut / 2 => &head[..i],
+            _ => head,
+        };
+        self.body = format!("{}{}", head.trim_end(), BODY_TRUNCATED_MARKER);
+        true
+    }
+
+    /// Append the raw files and gists the body links to (see `linked_urls`)
+    /// under a labeled section, so the prompt carries the full repro.
+    ///
+    /// Each file is cut to `LINKED_CONTENT_MAX_BYTES`; a link whose section
+    /// would take the appended text past `max_bytes` is skipped, as is one
+    /// `fetch` fails on. Both conditions get the resulting body.
+    pub fn append_linked_content(
+        &mut self,
+        max_bytes: usize,
+        mut fetch: impl FnMut(&str) -> Result<String>,
+    ) -> LinkedContent {
+        let mut linked = LinkedContent::default();
+        let mut sections = String::new();
+        for url in linked_urls(&self.body) {
+            let mut content = match fetch(&url) {
+                Ok(content) => content,
+                Err(e) => {
+                    linked.skipped.push((url, format!("{:#}", e)));
+                    continue;
+                }
+            };
+            if content.len() > LINKED_CONTENT_MAX_BYTES {
+                let mut cut = LINKED_CONTENT_MAX_BYTES;
+                while !content.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                content.truncate(cut);
+                content.push_str(LINKED_TRUNCATED_MARKER);
+            }
+            let header = if sections.is_empty() {
+                "\n\n---\n\n### Linked content (fetched from URLs in the issue)\n"
+            } else {
+                ""
+            };
+            let section = format!(
+                "{}\n#### {}\n\n```\n{}\n```\n",
+                header,
+                url,
+                content.trim_end()
+            );
+            if sections.len() + section.len() > max_bytes {
+                linked
+                    .skipped
+                    .push((url, "would exceed the prompt size limit".to_string()));
+                continue;
+            }
+            sections.push_str(&section);
+            linked.appended.push(url);
+        }
+        self.body.push_str(&sections);
+        linked
+    }
+
+    /// Why the issue fails a label filter, if it lacks all of `include` (when
+    /// non-empty) or carries any of `exclude`. Matching ignores case.
+    pub fn label_filter_reason(&self, include: &[String], exclude: &[String]) -> Option<String> {
+        let has = |label: &String| self.labels.iter().any(|l| l.eq_ignore_ascii_case(label));
+        if let Some(label) = exclude.iter().find(|l| has(l)) {
+            return Some(format!("issue has excluded label '{}'", label));
+        }
+        if !include.is_empty() && !include.iter().any(has) {
+            return Some(format!(
+                "issue has none of the included labels ({})",
+                include.join(", ")
+            ));
+        }
+        None
+    }
+}
+
+/// Outcome of `GitHubIssue::append_linked_content`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkedContent {
+    /// URLs whose contents were appended
+    pub appended: Vec<String>,
+    /// URLs left out, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Raw-file and gist URLs in an issue body, deduplicated, in order, as the
+/// URLs to fetch: `raw.githubusercontent.com` and `gist.githubusercontent.com`
+/// links as-is, `gist.github.com` pages via their `/raw` endpoint.
+pub fn linked_urls(body: &str) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    for (start, _) in body.match_indices("https://") {
+        let rest = &body[start..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || "()<>[]\"'`".contains(c))
+            .unwrap_or(rest.len());
+        let url = rest[..end].trim_end_matches(['.', ',', ';', ':']);
+        let host = url["https://".len()..].split('/').next().unwrap_or("");
+        let url = match host {
+            "raw.githubusercontent.com" | "gist.githubusercontent.com" => url.to_string(),
+            "gist.github.com" if !url.contains("/raw") => {
+                format!("{}/raw", url.trim_end_matches('/'))
+            }
+            "gist.github.com" => url.to_string(),
+            _ => continue,
+        };
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+        if urls.len() == MAX_LINKED_URLS {
+            break;
+        }
+    }
+    urls
+}
+
+/// Fetch a linked file's text with `curl` (`CURL_BIN` overrides the binary),
+/// reading at most a little past `LINKED_CONTENT_MAX_BYTES`.
+pub fn fetch_linked(url: &str) -> Result<String> {
+    let curl = std::env::var_os("CURL_BIN")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("curl"));
+    let output = Command::new(curl)
+        .args(["-fsSL", "--max-time", "30", "--range"])
+        .arg(format!("0-{}", LINKED_CONTENT_MAX_BYTES))
+        .arg(url)
+        .output()
+        .context("Failed to execute curl")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("curl failed: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Issue body without template HTML comments, with whitespace collapsed.
+fn sanitize_body(body: &str) -> String {
+    let mut text = String::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("<!--") {
+        text.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 /// Parse an issue identifier string into an IssueRef.
@@ -112,6 +327,37 @@ pub fn parse_issue_identifier(input: &str) -> Result<IssueRef> {
     )
 }
 
+/// Parse an issue identifier in the context of a known `owner/repo`.
+///
+/// Besides the standalone formats, accepts a bare number (`123`, `#123`) or a
+/// tracker-style `GH-123` / `gh-123`, resolved against `default_repo`.
+pub fn parse_issue_identifier_in(default_repo: &str, input: &str) -> Result<IssueRef> {
+    let input = input.trim();
+    if input.contains('/') {
+        return parse_issue_identifier(input);
+    }
+    let number = parse_issue_number(input)?;
+    let (owner, repo) = parse_owner_repo(default_repo.trim())?;
+    Ok(IssueRef {
+        owner,
+        repo,
+        number,
+    })
+}
+
+/// Parse a repo-relative issue number: `123`, `#123`, `GH-123`, or `gh-123`.
+pub fn parse_issue_number(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let digits = input
+        .strip_prefix('#')
+        .or_else(|| input.strip_prefix("GH-"))
+        .or_else(|| input.strip_prefix("gh-"))
+        .unwrap_or(input);
+    digits
+        .parse()
+        .with_context(|| format!("Invalid issue number: '{}' (expected 123 or GH-123)", input))
+}
+
 /// Parse `owner/repo/issues/N` path format.
 fn parse_path_with_issues(path: &str) -> Result<IssueRef> {
     let parts: Vec<&str> = path.split('/').collect();
@@ -163,9 +409,21 @@ fn validate_component(s: &str, label: &str) -> Result<String> {
 
 /// Fetch a GitHub issue using the `gh` CLI.
 pub fn fetch_issue(issue_ref: &IssueRef) -> Result<GitHubIssue> {
+    fetch_issue_with(&gh_binary(), issue_ref)
+}
+
+/// Resolve the `gh` binary, honoring the `GH_BIN` override (for testing / custom installs).
+pub(crate) fn gh_binary() -> PathBuf {
+    std::env::var_os("GH_BIN")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("gh"))
+}
+
+/// Fetch a GitHub issue using the given `gh` binary.
+pub(crate) fn fetch_issue_with(gh: &Path, issue_ref: &IssueRef) -> Result<GitHubIssue> {
     let repo_arg = issue_ref.repo_slug();
 
-    let output = Command::new("gh")
+    let output = Command::new(gh)
         .args([
             "issue",
             "view",
@@ -173,13 +431,28 @@ pub fn fetch_issue(issue_ref: &IssueRef) -> Result<GitHubIssue> {
             "--repo",
             &repo_arg,
             "--json",
-            "title,body,labels,state",
+            "number,title,body,labels,state",
         ])
         .output()
         .context("Failed to execute `gh` CLI. Is it installed and authenticated?")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        if is_auth_error(&stderr) {
+            anyhow::bail!(
+                "GitHub CLI is not authenticated, so {} could not be fetched.\n\
+                 Run `gh auth login` (or set GH_TOKEN) and try again.\n{}",
+                issue_ref,
+                stderr.trim()
+            );
+        }
+        if is_rate_limit_error(&stderr) {
+            anyhow::bail!(
+                "GitHub rate limit hit while fetching {}.\n{}",
+                issue_ref,
+                stderr.trim()
+            );
+        }
         if stderr.contains("not found") || stderr.contains("Could not resolve") {
             anyhow::bail!(
                 "Issue {} not found. It may be private, deleted, or the repo doesn't exist.\n{}",
@@ -190,9 +463,18 @@ pub fn fetch_issue(issue_ref: &IssueRef) -> Result<GitHubIssue> {
         anyhow::bail!("Failed to fetch {}: {}", issue_ref, stderr.trim());
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    // `from_slice` rejects non-UTF-8 output instead of mangling it
     let data: serde_json::Value =
-        serde_json::from_str(&stdout).context("Failed to parse `gh` JSON output")?;
+        serde_json::from_slice(&output.stdout).context("Failed to parse `gh` JSON output")?;
+    if let Some(number) = data.get("number") {
+        if number.as_u64() != Some(issue_ref.number) {
+            anyhow::bail!(
+                "`gh` returned issue number {} for {}; refusing to use it",
+                number,
+                issue_ref
+            );
+        }
+    }
 
     let title = data["title"].as_str().unwrap_or("(no title)").to_string();
     let body = data["body"].as_str().unwrap_or("").to_string();
@@ -215,6 +497,21 @@ pub fn fetch_issue(issue_ref: &IssueRef) -> Result<GitHubIssue> {
     })
 }
 
+/// Whether `gh` stderr indicates missing or invalid authentication.
+fn is_auth_error(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    lower.contains("authentication")
+        || lower.contains("gh auth login")
+        || lower.contains("http 401")
+}
+
+/// Whether `gh` output (or an error built from it) indicates a GitHub
+/// primary or secondary rate limit.
+pub(crate) fn is_rate_limit_error(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    lower.contains("rate limit") || lower.contains("http 429")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,6 +554,29 @@ mod tests {
         assert_eq!(r.number, 1);
     }
 
+    #[test]
+    fn parse_bare_number_in_repo_context() {
+        for input in ["123", "#123", " 123 "] {
+            let r = parse_issue_identifier_in("owner/repo", input).unwrap();
+            assert_eq!(r.to_string(), "owner/repo#123");
+        }
+        // Full identifiers ignore the default repo
+        let r = parse_issue_identifier_in("owner/repo", "other/proj#9").unwrap();
+        assert_eq!(r.to_string(), "other/proj#9");
+    }
+
+    #[test]
+    fn parse_gh_prefix_in_repo_context() {
+        for input in ["GH-42", "gh-42"] {
+            let r = parse_issue_identifier_in("owner/repo", input).unwrap();
+            assert_eq!(r.repo_slug(), "owner/repo");
+            assert_eq!(r.number, 42);
+        }
+        assert!(parse_issue_identifier_in("owner/repo", "GH-").is_err());
+        assert!(parse_issue_identifier_in("owner/repo", "JIRA-42").is_err());
+        assert!(parse_issue_identifier_in("not-a-slug", "42").is_err());
+    }
+
     #[test]
     fn parse_invalid_no_number() {
         assert!(parse_issue_identifier("srobinson/fmm").is_err());
@@ -315,6 +635,85 @@ mod tests {
         assert!(prompt.contains("commit your changes"));
     }
 
+    #[test]
+    fn long_body_truncated_near_token_target() {
+        let mut issue = GitHubIssue {
+            issue_ref: parse_issue_identifier("o/r#1").unwrap(),
+            title: "Crash".to_string(),
+            body: "lorem ipsum dolor sit amet ".repeat(500),
+            state: "OPEN".to_string(),
+            labels: vec![],
+        };
+        assert_eq!(estimate_tokens(&issue.body), 3375);
+
+        assert!(issue.truncate_body(200));
+        assert!(issue.body.ends_with(BODY_TRUNCATED_MARKER));
+        let kept = issue.body.trim_end_matches(BODY_TRUNCATED_MARKER);
+        let tokens = estimate_tokens(kept);
+        assert!((190..=200).contains(&tokens), "{tokens} tokens");
+        assert!(kept.ends_with(|c: char| c.is_alphabetic()));
+
+        // Short bodies are left alone
+        let before = issue.body.clone();
+        assert!(!issue.truncate_body(10_000));
+        assert_eq!(issue.body, before);
+    }
+
+    #[test]
+    fn linked_urls_detects_raw_files_and_gists() {
+        let body = "Repro: https://gist.github.com/alice/abc123. Log at \
+            (https://raw.githubusercontent.com/o/r/main/log.txt), docs at \
+            https://example.com/guide and https://gist.github.com/alice/abc123 again";
+        assert_eq!(
+            linked_urls(body),
+            vec![
+                "https://gist.github.com/alice/abc123/raw",
+                "https://raw.githubusercontent.com/o/r/main/log.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn linked_content_appended_identically_for_both_conditions() {
+        let mut issue = GitHubIssue {
+            issue_ref: IssueRef {
+                owner: "a".to_string(),
+                repo: "b".to_string(),
+                number: 1,
+            },
+            title: "Crash on save".to_string(),
+            body: "See https://gist.github.com/bob/f00 and \
+                https://raw.githubusercontent.com/a/b/main/missing.txt"
+                .to_string(),
+            state: "OPEN".to_string(),
+            labels: vec![],
+        };
+
+        let linked = issue.append_linked_content(4096, |url| {
+            if url.ends_with("/raw") {
+                Ok("fn main() { panic!() }\n".to_string())
+            } else {
+                anyhow::bail!("404")
+            }
+        });
+        assert_eq!(linked.appended, vec!["https://gist.github.com/bob/f00/raw"]);
+        assert_eq!(linked.skipped.len(), 1);
+
+        let control = issue.to_prompt();
+        let fmm = issue.to_prompt();
+        assert_eq!(control, fmm);
+        assert!(control.contains("### Linked content (fetched from URLs in the issue)"));
+        assert!(control.contains(
+            "#### https://gist.github.com/bob/f00/raw\n\n```\nfn main() { panic!() }\n```"
+        ));
+
+        // No room left: nothing more is appended
+        let before = issue.body.clone();
+        let linked = issue.append_linked_content(10, |_| Ok("x".repeat(100)));
+        assert!(linked.appended.is_empty());
+        assert_eq!(issue.body, before);
+    }
+
     #[test]
     fn prompt_identical_for_both_conditions() {
         let issue = GitHubIssue {
@@ -349,4 +748,148 @@ mod tests {
         assert!(validate_component("my-repo.js", "repo").is_ok());
         assert!(validate_component("user_name", "owner").is_ok());
     }
+
+    #[cfg(unix)]
+    fn write_gh_shim(dir: &Path, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("gh");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn auth_error_detection() {
+        assert!(is_auth_error(
+            "To get started with GitHub CLI, please run:  gh auth login"
+        ));
+        assert!(is_auth_error("HTTP 401: Bad credentials"));
+        assert!(is_auth_error("error: authentication required"));
+        assert!(!is_auth_error("GraphQL: Could not resolve to an Issue"));
+    }
+
+    #[test]
+    fn detects_rate_limit_errors() {
+        assert!(is_rate_limit_error(
+            "HTTP 403: API rate limit exceeded for user ID 1234."
+        ));
+        assert!(is_rate_limit_error(
+            "HTTP 403: You have exceeded a secondary rate limit."
+        ));
+        assert!(is_rate_limit_error("HTTP 429: Too Many Requests"));
+        assert!(!is_rate_limit_error("HTTP 404: Not Found"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fetch_issue_unauthenticated_gh() {
+        let dir = tempfile::tempdir().unwrap();
+        let gh = write_gh_shim(
+            dir.path(),
+            "echo 'To get started with GitHub CLI, please run:  gh auth login' >&2\nexit 4",
+        );
+        let r = parse_issue_identifier("srobinson/fmm#42").unwrap();
+
+        let err = fetch_issue_with(&gh, &r).unwrap_err().to_string();
+        assert!(err.contains("not authenticated"), "got: {}", err);
+        assert!(err.contains("gh auth login"));
+        assert!(!err.contains("not found"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fetch_issue_not_found_gh() {
+        let dir = tempfile::tempdir().unwrap();
+        let gh = write_gh_shim(
+            dir.path(),
+            "echo 'GraphQL: Could not resolve to an issue or pull request' >&2\nexit 1",
+        );
+        let r = parse_issue_identifier("srobinson/fmm#42").unwrap();
+
+        let err = fetch_issue_with(&gh, &r).unwrap_err().to_string();
+        assert!(err.contains("not found"), "got: {}", err);
+        assert!(!err.contains("not authenticated"));
+    }
+
+    #[test]
+    fn prompt_commit_template_identical_for_both_conditions() {
+        let issue = GitHubIssue {
+            issue_ref: IssueRef {
+                owner: "a".to_string(),
+                repo: "b".to_string(),
+                number: 1,
+            },
+            title: "Title".to_string(),
+            body: "Body".to_string(),
+            state: "OPEN".to_string(),
+            labels: vec![],
+        };
+
+        let control = issue.to_prompt_with_commit_template(Some("fmm-bench: <summary>"));
+        let fmm = issue.to_prompt_with_commit_template(Some("fmm-bench: <summary>"));
+        assert_eq!(control, fmm);
+        assert!(control.contains("`fmm-bench: <summary>`"));
+        assert!(!control.contains("descriptive message"));
+        assert_eq!(
+            issue.to_prompt_with_commit_template(None),
+            issue.to_prompt()
+        );
+    }
+
+    #[test]
+    fn thin_body_detected_after_sanitization() {
+        let issue = |body: &str| GitHubIssue {
+            issue_ref: parse_issue_identifier("o/r#1").unwrap(),
+            title: "Broken".to_string(),
+            body: body.to_string(),
+            state: "OPEN".to_string(),
+            labels: vec![],
+        };
+
+        assert!(issue("").thin_body_reason(DEFAULT_MIN_BODY_CHARS).is_some());
+        // Template comments and padding don't count
+        let templated = issue("<!-- Describe the bug -->\n\n  it's broken  \n<!-- Steps -->");
+        let reason = templated.thin_body_reason(DEFAULT_MIN_BODY_CHARS).unwrap();
+        assert!(reason.contains("11 characters"));
+
+        let detailed = issue("Parsing `a = [1, 2,]` panics with index out of bounds in lexer.rs");
+        assert!(detailed.thin_body_reason(DEFAULT_MIN_BODY_CHARS).is_none());
+        assert!(issue("").thin_body_reason(0).is_none());
+    }
+
+    #[test]
+    fn label_filters_select_fetched_issues() {
+        let issue = |n: u64, labels: &[&str]| GitHubIssue {
+            issue_ref: parse_issue_identifier(&format!("o/r#{}", n)).unwrap(),
+            title: "Broken".to_string(),
+            body: String::new(),
+            state: "OPEN".to_string(),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+        };
+        let fetched = [
+            issue(1, &["good-first-issue", "bug"]),
+            issue(2, &["bug"]),
+            issue(3, &["Good-First-Issue", "wontfix"]),
+            issue(4, &[]),
+        ];
+        let select = |include: &[&str], exclude: &[&str]| -> Vec<u64> {
+            let include: Vec<String> = include.iter().map(|l| l.to_string()).collect();
+            let exclude: Vec<String> = exclude.iter().map(|l| l.to_string()).collect();
+            fetched
+                .iter()
+                .filter(|i| i.label_filter_reason(&include, &exclude).is_none())
+                .map(|i| i.issue_ref.number)
+                .collect()
+        };
+
+        assert_eq!(select(&[], &[]), vec![1, 2, 3, 4]);
+        assert_eq!(select(&["good-first-issue"], &[]), vec![1, 3]);
+        assert_eq!(select(&["good-first-issue"], &["wontfix"]), vec![1]);
+        assert_eq!(select(&[], &["bug"]), vec![3, 4]);
+
+        let reason = fetched[1]
+            .label_filter_reason(&["good-first-issue".to_string()], &[])
+            .unwrap();
+        assert!(reason.contains("none of the included labels"));
+    }
 }
@@ -1,15 +1,27 @@
 pub mod aggregate;
 pub mod batch;
 mod cache;
+pub mod console;
 pub mod evaluator;
 pub mod issue;
 pub mod metrics;
 pub mod orchestrator;
+mod pricing;
+pub mod profile;
+mod ratelimit;
 pub mod report;
 mod runner;
 pub mod sandbox;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 mod tasks;
 
-pub use orchestrator::{CompareOptions, Orchestrator};
+pub use cache::write_atomic;
+pub use orchestrator::{CompareOptions, IssueSettings, Orchestrator};
+pub use pricing::{
+    default_model_alias_file, load_model_aliases, resolve_model, ANY_MODEL, ANY_MODEL_PREFIX,
+    KNOWN_MODEL_IDS, MODEL_ALIASES,
+};
 pub use report::{ComparisonReport, ReportFormat};
 pub use runner::RunResult;
+pub use tasks::TaskCategory;
@@ -1,24 +1,76 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand, ValueEnum};
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
+use fmm_bench::{outln, summaryln};
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    fmm_bench::console::set_quiet(cli.quiet);
 
-    match cli.command {
+    if cli.list_models {
+        list_models();
+        return Ok(());
+    }
+    let Some(command) = cli.command else {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "a subcommand is required (or pass --list-models)",
+            )
+            .exit();
+    };
+
+    match command {
         Commands::Run(args) => cmd_run(args),
         Commands::Compare(args) => cmd_compare(args),
         Commands::Batch(args) => cmd_batch(args),
         Commands::Validate(args) => cmd_validate(args),
+        Commands::Eval(args) => cmd_eval(args),
+        Commands::AggregateDiff(args) => cmd_aggregate_diff(args),
+        Commands::Regrade(args) => cmd_regrade(args),
+        Commands::Tasks(TasksCommand::Validate(args)) => cmd_tasks_validate(args),
+        Commands::Corpus(CorpusCommand::Generate(args)) => cmd_corpus_generate(args),
+        Commands::Corpus(CorpusCommand::FromReports(args)) => cmd_corpus_from_reports(args),
+    }
+}
+
+/// Print the model aliases and full ids `--model` accepts.
+fn list_models() {
+    println!("Aliases:");
+    for alias in fmm_bench::MODEL_ALIASES {
+        println!("  {}", alias);
+    }
+    println!("Full ids (any claude-* id of these families is accepted):");
+    for id in fmm_bench::KNOWN_MODEL_IDS {
+        println!("  {}", id);
+    }
+    println!(
+        "Use --model {}<name> to pass an unlisted model through unchecked, or --model {} for the CLI's default.",
+        fmm_bench::ANY_MODEL_PREFIX,
+        fmm_bench::ANY_MODEL
+    );
+    if let Some(path) = fmm_bench::default_model_alias_file() {
+        match fmm_bench::load_model_aliases(&path) {
+            Ok(aliases) => {
+                println!("Team aliases ({}):", path.display());
+                for (name, id) in aliases {
+                    println!("  {} -> {}", name, id);
+                }
+            }
+            Err(e) => eprintln!("{} {:#}", "Warning:".yellow(), e),
+        }
     }
 }
 
 /// Run an issue-driven A/B comparison.
 fn cmd_run(args: RunArgs) -> Result<()> {
+    fmm_bench::console::route_to_stderr(args.stdout);
+
     let issue_ref = fmm_bench::issue::parse_issue_identifier(&args.issue)?;
 
-    println!(
+    outln!(
         "{} Fetching {}...",
         ">>".yellow(),
         issue_ref.to_string().cyan().bold()
@@ -26,7 +78,7 @@ fn cmd_run(args: RunArgs) -> Result<()> {
 
     let issue = fmm_bench::issue::fetch_issue(&issue_ref)?;
 
-    println!(
+    outln!(
         "{} {} [{}]",
         ">>".yellow(),
         issue.title.white().bold(),
@@ -35,45 +87,153 @@ fn cmd_run(args: RunArgs) -> Result<()> {
 
     let options = fmm_bench::CompareOptions {
         branch: args.branch,
+        commit: None,
         src_path: None,
         task_set: "standard".to_string(),
         runs: args.runs,
+        task_repeats: 1,
+        format: fmm_bench::ReportFormat::resolve(
+            args.output.as_deref(),
+            args.format.map(to_report_format),
+        )?,
         output: args.output,
-        format: to_report_format(args.format),
         max_budget: args.budget,
         use_cache: !args.no_cache,
         quick: false,
         model: args.model,
+        model_alias_file: model_alias_file(args.model_alias_file),
+        commit_message_template: args.commit_message_template,
+        require_sidecars: args.require_sidecars,
+        seed: None,
+        exclude_degenerate: args.exclude_degenerate,
+        require_treatment_used: args.require_treatment_used,
+        max_output_bytes: args.max_output_mb * 1024 * 1024,
+        export_raw: args.export_raw,
+        keep_claude_logs: args.keep_claude_logs,
+        strict_json: args.strict_json,
+        ndjson: args.ndjson,
+        include_diff: args.include_diff,
+        task_retries: args.retries,
+        control_context: read_control_context(args.control_context.as_deref())?,
+        sidecar_args: split_sidecar_args(args.sidecar_args.as_deref()),
+        max_tool_calls: args.tool_budget,
+        category_filter: None,
+        fmm_bin: None,
+        min_body_chars: args.min_body_chars,
+        skip_empty_body: args.skip_empty_body,
+        max_body_tokens: args.max_body_tokens,
+        fetch_linked: args.fetch_linked,
+        include_labels: args.include_labels,
+        exclude_labels: args.exclude_labels,
+        profile: args.profile,
+        deadline: wall_deadline(args.max_wall_secs),
+        clone_depth: clone_depth(args.clone_depth),
+        clone_timeout: Duration::from_secs(args.clone_timeout),
+        require_permissions: args.require_permissions,
+        settings_template: settings_template(&args.settings),
+        setup_commands: vec![],
+        repo_cache: None,
+        cache_dir: None,
+        eval: eval_options(&args.checks),
+    };
+
+    let total_budget = match (args.compare_baselines, &args.fmm_bin_a) {
+        (Some(k), _) => args.budget * k as f64,
+        // One full comparison per fmm binary
+        (None, Some(_)) => args.budget * 4.0 * args.runs as f64,
+        (None, None) => args.budget * 2.0 * args.runs as f64,
     };
+    fmm_bench::console::confirm_budget(total_budget, args.yes)?;
 
     let mut orchestrator = fmm_bench::Orchestrator::new(options)?;
-    let report = orchestrator.run_issue(&issue)?;
 
-    println!("\n{}", "=".repeat(60).dimmed());
-    println!("{}", "COMPARISON RESULTS".green().bold());
-    println!("{}", "=".repeat(60).dimmed());
+    if let Some(runs) = args.compare_baselines {
+        let baseline = orchestrator.run_baselines(&issue, runs)?;
+        summaryln!("\n{}", baseline.to_markdown());
+        if args.stdout {
+            println!("{}", serde_json::to_string_pretty(&baseline)?);
+        }
+        return Ok(());
+    }
 
-    report.print_summary();
+    if let (Some(bin_a), Some(bin_b)) = (&args.fmm_bin_a, &args.fmm_bin_b) {
+        let regression = orchestrator.run_fmm_regression(&issue, bin_a, bin_b)?;
+        summaryln!("\n{}", regression.to_markdown());
+        if args.stdout {
+            println!("{}", serde_json::to_string_pretty(&regression)?);
+        }
+        return Ok(());
+    }
 
-    Ok(())
+    let report = orchestrator.run_issue(&issue)?;
+
+    print_results(&report, args.stdout, args.compact)?;
+    check_reduction_baseline(
+        &report,
+        args.compare_with_baseline_json.as_deref(),
+        args.baseline_tolerance,
+    )
 }
 
 /// Run task-based comparison on a repository (original mode).
 fn cmd_compare(args: CompareArgs) -> Result<()> {
+    fmm_bench::console::route_to_stderr(args.stdout);
+
     let options = fmm_bench::CompareOptions {
         branch: args.branch,
+        commit: None,
         src_path: args.src_path,
         task_set: args.tasks,
         runs: args.runs,
+        task_repeats: args.tasks_repeat,
+        format: fmm_bench::ReportFormat::resolve(
+            args.output.as_deref(),
+            args.format.map(to_report_format),
+        )?,
         output: args.output,
-        format: to_report_format(args.format),
         max_budget: args.max_budget,
         use_cache: !args.no_cache,
         quick: args.quick,
         model: args.model,
+        model_alias_file: model_alias_file(args.model_alias_file),
+        commit_message_template: None,
+        require_sidecars: args.require_sidecars,
+        seed: args.seed,
+        exclude_degenerate: args.exclude_degenerate,
+        require_treatment_used: args.require_treatment_used,
+        max_output_bytes: args.max_output_mb * 1024 * 1024,
+        export_raw: args.export_raw,
+        keep_claude_logs: args.keep_claude_logs,
+        strict_json: args.strict_json,
+        ndjson: args.ndjson,
+        include_diff: false,
+        task_retries: args.retries,
+        control_context: read_control_context(args.control_context.as_deref())?,
+        sidecar_args: split_sidecar_args(args.sidecar_args.as_deref()),
+        max_tool_calls: args.tool_budget,
+        category_filter: (!args.category.is_empty()).then_some(args.category),
+        fmm_bin: None,
+        min_body_chars: fmm_bench::issue::DEFAULT_MIN_BODY_CHARS,
+        skip_empty_body: false,
+        max_body_tokens: None,
+        fetch_linked: false,
+        include_labels: vec![],
+        exclude_labels: vec![],
+        profile: args.profile,
+        deadline: wall_deadline(args.max_wall_secs),
+        clone_depth: clone_depth(args.clone_depth),
+        clone_timeout: Duration::from_secs(args.clone_timeout),
+        require_permissions: args.require_permissions,
+        settings_template: settings_template(&args.settings),
+        setup_commands: vec![],
+        repo_cache: None,
+        cache_dir: None,
+        eval: fmm_bench::evaluator::EvalOptions::default(),
     };
 
-    println!(
+    fmm_bench::console::confirm_budget(args.max_budget, args.yes)?;
+
+    outln!(
         "{} Starting comparison for {}",
         ">>".yellow(),
         args.url.cyan().bold()
@@ -82,89 +242,318 @@ fn cmd_compare(args: CompareArgs) -> Result<()> {
     let mut orchestrator = fmm_bench::Orchestrator::new(options)?;
     let report = orchestrator.run(&args.url)?;
 
-    println!("\n{}", "=".repeat(60).dimmed());
-    println!("{}", "COMPARISON RESULTS".green().bold());
-    println!("{}", "=".repeat(60).dimmed());
+    print_results(&report, args.stdout, args.compact)?;
+    check_reduction_baseline(
+        &report,
+        args.compare_with_baseline_json.as_deref(),
+        args.baseline_tolerance,
+    )
+}
 
-    report.print_summary();
+/// Read the control condition's ablation prompt, if one was given.
+fn read_control_context(path: Option<&std::path::Path>) -> Result<Option<String>> {
+    path.map(|p| {
+        std::fs::read_to_string(p)
+            .with_context(|| format!("Failed to read control context: {}", p.display()))
+    })
+    .transpose()
+}
 
-    Ok(())
+/// Split `--sidecar-args` into individual arguments (validated by the orchestrator).
+fn split_sidecar_args(args: Option<&str>) -> Vec<String> {
+    args.map(|a| a.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Print the comparison summary. With `json_stdout`, the summary goes to
+/// stderr and the JSON report is the only thing written to stdout.
+fn print_results(
+    report: &fmm_bench::ComparisonReport,
+    json_stdout: bool,
+    compact: bool,
+) -> Result<()> {
+    report.write_results(
+        &mut std::io::stdout(),
+        &mut std::io::stderr(),
+        json_stdout,
+        compact,
+    )
+}
+
+/// Compare per-task reductions against a committed baseline, failing (non-zero
+/// exit) when any task regressed beyond `tolerance` percentage points.
+fn check_reduction_baseline(
+    report: &fmm_bench::ComparisonReport,
+    baseline: Option<&std::path::Path>,
+    tolerance: f64,
+) -> Result<()> {
+    let Some(path) = baseline else {
+        return Ok(());
+    };
+    let baseline = fmm_bench::report::ReductionBaseline::load(path)?;
+    let regressions = baseline.regressions(report, tolerance);
+    if regressions.is_empty() {
+        summaryln!(
+            "{} No regressions against {} ({} tasks, tolerance {:.1} pp)",
+            "✓".green(),
+            path.display(),
+            baseline.0.len(),
+            tolerance
+        );
+        return Ok(());
+    }
+    summaryln!("\n{} Regressions against {}:", "✗".red(), path.display());
+    for regression in &regressions {
+        summaryln!("  {}", regression.describe());
+    }
+    anyhow::bail!(
+        "{} task(s) regressed beyond {:.1} pp of the baseline",
+        regressions.len(),
+        tolerance
+    )
+}
+
+/// Print a `--profile` stage breakdown.
+fn print_profile(profile: &fmm_bench::profile::Profile) {
+    summaryln!("\n{}", "Stage timings".bold());
+    summaryln!("{}", profile.to_table().trim_end());
 }
 
 /// Run batch A/B comparisons across a corpus.
 fn cmd_batch(args: BatchArgs) -> Result<()> {
-    let corpus = fmm_bench::batch::load_corpus(&args.corpus)?;
+    let corpus = fmm_bench::batch::load_corpus_with(&args.corpus, args.strict_corpus)?;
 
-    println!(
+    outln!(
         "{} Loaded {} issues from {}",
         ">>".yellow(),
         corpus.len(),
         args.corpus.display()
     );
 
+    let mut exclude = args.exclude;
+    if let Some(ref path) = args.exclude_file {
+        exclude.extend(fmm_bench::batch::load_exclude_file(path)?);
+    }
+
     let opts = fmm_bench::batch::BatchOptions {
         budget: args.budget,
+        per_issue_budget: args.budget_per_issue,
         runs: args.runs,
         filter: args.filter,
+        exclude,
         resume: args.resume,
+        max_attempts: args.max_attempts,
         output: args.output,
         model: args.model,
+        model_alias_file: model_alias_file(args.model_alias_file),
+        commit_message_template: args.commit_message_template,
+        only_failed: args.only_failed,
+        append_to: args.append_to,
+        normalize: args.normalize,
+        export_raw_pairs: args.export_raw_pairs,
+        languages: args.languages,
+        sqlite: args.sqlite,
+        min_body_chars: args.min_body_chars,
+        skip_empty_body: args.skip_empty_body,
+        max_body_tokens: args.max_body_tokens,
+        fetch_linked: args.fetch_linked,
+        include_labels: args.include_labels,
+        exclude_labels: args.exclude_labels,
+        profile: args.profile,
+        eval: eval_options(&args.checks),
+        max_wall: args.max_wall_secs.map(Duration::from_secs),
+        clone_depth: clone_depth(args.clone_depth),
+        clone_timeout: Duration::from_secs(args.clone_timeout),
+        require_permissions: args.require_permissions,
+        require_sidecars: args.require_sidecars,
+        max_tool_calls: args.tool_budget,
+        task_retries: args.retries,
+        max_output_bytes: args.max_output_mb * 1024 * 1024,
+        include_diff: args.include_diff,
+        require_treatment_used: args.require_treatment_used,
+        settings_template: settings_template(&args.settings),
+        task_set: args.tasks,
+        cache_dir: None,
     };
 
+    fmm_bench::console::confirm_budget(opts.budget, args.yes)?;
+
     let aggregate = fmm_bench::batch::run_batch(&corpus, &opts)?;
 
-    println!("\n{}", "=".repeat(60).dimmed());
-    println!("{}", "AGGREGATE RESULTS".green().bold());
-    println!("{}", "=".repeat(60).dimmed());
+    summaryln!("\n{}", "=".repeat(60).dimmed());
+    summaryln!("{}", "AGGREGATE RESULTS".green().bold());
+    summaryln!("{}", "=".repeat(60).dimmed());
 
-    println!(
+    summaryln!(
         "  Issues: {}/{} completed",
-        aggregate.issues_completed, aggregate.issues_total
+        aggregate.issues_completed,
+        aggregate.issues_total
     );
-    println!("  Total cost: ${:.2}", aggregate.total_cost);
+    for f in &aggregate.failures {
+        summaryln!("  {} {}: {}", "!".red(), f.id, f.reason);
+    }
+    summaryln!("  Total cost: ${:.2}", aggregate.total_cost);
+    if let Some(rate) = aggregate.spend_rate() {
+        summaryln!("  Spend rate: {}", rate.describe());
+    }
+    if aggregate.deadline_truncated {
+        summaryln!(
+            "  {} Stopped at the wall-clock deadline; results are partial",
+            "!".yellow()
+        );
+    }
+    if let Some(ref projection) = aggregate.cost_projection {
+        summaryln!("  {}", projection.describe().yellow());
+    }
+    if !aggregate.per_issue.is_empty() {
+        summaryln!("  {}", aggregate.win_line().bold());
+    }
 
     let s = &aggregate.summary;
     if s.n > 0 {
-        println!(
+        summaryln!(
             "  Tool calls: {:.1} (ctrl) vs {:.1} (fmm) = {:.1}% reduction",
-            s.tool_calls.control_mean, s.tool_calls.fmm_mean, s.tool_calls.delta_pct
+            s.tool_calls.control_mean,
+            s.tool_calls.fmm_mean,
+            s.tool_calls.delta_pct
         );
-        println!(
+        summaryln!(
             "  Cost: ${:.3} (ctrl) vs ${:.3} (fmm) = {:.1}% savings",
-            s.cost.control_mean, s.cost.fmm_mean, s.cost.delta_pct
+            s.cost.control_mean,
+            s.cost.fmm_mean,
+            s.cost.delta_pct
         );
+        if let Some(ref n) = aggregate.normalized_tool_calls {
+            summaryln!(
+                "  Tool calls / difficulty: {:.1} (ctrl) vs {:.1} (fmm) = {:.1}% reduction",
+                n.control_mean,
+                n.fmm_mean,
+                n.delta_pct
+            );
+        }
+    }
+    if let Some(ref profile) = aggregate.profile {
+        print_profile(profile);
     }
 
     Ok(())
 }
 
+/// Grade a checkout (or a sandbox's control/fmm pair) without running Claude.
+fn cmd_eval(args: EvalArgs) -> Result<()> {
+    let options = eval_options(&args.checks);
+    for (label, scores) in fmm_bench::evaluator::evaluate_path(&args.dir, options)? {
+        summaryln!("{} {}", ">>".green().bold(), label.cyan().bold());
+        for line in scores.describe().lines() {
+            summaryln!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Diff two aggregate reports as structured JSON.
+fn cmd_aggregate_diff(args: AggregateDiffArgs) -> Result<()> {
+    let before = fmm_bench::batch::load_aggregate(&args.before)?;
+    let after = fmm_bench::batch::load_aggregate(&args.after)?;
+    let diff = fmm_bench::aggregate::AggregateDiff::between(&before, &after);
+    let json = serde_json::to_string_pretty(&diff)?;
+
+    match args.compare_json {
+        Some(path) => {
+            fmm_bench::write_atomic(&path, json.as_bytes())?;
+            summaryln!(
+                "{} {} shared issues compared ({} only in before, {} only in after)",
+                ">>".green().bold(),
+                diff.overall.issues,
+                diff.only_before.len(),
+                diff.only_after.len()
+            );
+            summaryln!("  JSON: {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// Recompute a report's grades offline from its recorded checks.
+fn cmd_regrade(args: RegradeArgs) -> Result<()> {
+    let content = std::fs::read_to_string(&args.report)
+        .with_context(|| format!("Failed to read report: {}", args.report.display()))?;
+    let mut report = fmm_bench::ComparisonReport::from_json(&content)
+        .with_context(|| format!("Failed to parse report: {}", args.report.display()))?;
+
+    let changes = report.regrade_with(fmm_bench::evaluator::standard_grade);
+    if changes.is_empty() {
+        summaryln!("{} No grades changed", ">>".green().bold());
+    }
+    for change in &changes {
+        summaryln!(
+            "  {} {}: {} -> {}",
+            change.task_id,
+            change.variant,
+            change.before,
+            change.after.bold()
+        );
+    }
+
+    if let Some(ref output) = args.output {
+        for path in report.save(output, fmm_bench::ReportFormat::Both)? {
+            summaryln!("  Saved: {}", path);
+        }
+    }
+    Ok(())
+}
+
+/// Check a custom task file for problems before spending budget on it.
+fn cmd_tasks_validate(args: TasksValidateArgs) -> Result<()> {
+    let path = args.path.to_string_lossy();
+    let (tasks, problems) = fmm_bench::orchestrator::validate_custom_tasks(&path)?;
+    if problems.is_empty() {
+        summaryln!("{} {}: {} task(s), no problems", "+".green(), path, tasks);
+        return Ok(());
+    }
+    for problem in &problems {
+        summaryln!("  - {}", problem);
+    }
+    anyhow::bail!("{} problem(s) in {}", problems.len(), path)
+}
+
 /// Validate a corpus file.
 fn cmd_validate(args: ValidateArgs) -> Result<()> {
-    let corpus = fmm_bench::batch::load_corpus(&args.corpus)?;
+    let corpus = fmm_bench::batch::load_corpus_with(&args.corpus, args.strict_corpus)?;
 
-    println!(
+    outln!(
         "{} Validating {} corpus entries...\n",
         ">>".yellow(),
         corpus.len()
     );
 
-    let results = fmm_bench::batch::validate_corpus(&corpus);
+    let results =
+        fmm_bench::batch::validate_corpus(&corpus, args.fail_fast, args.jobs, args.min_body_chars);
 
     let accessible = results.iter().filter(|r| r.issue_accessible).count();
     let failed = results.iter().filter(|r| !r.issue_accessible).count();
+    let thin = results.iter().filter(|r| r.body_warning.is_some()).count();
 
-    println!(
+    summaryln!(
         "\n{} {} accessible, {} failed out of {}",
         ">>".green().bold(),
         accessible,
         failed,
         results.len()
     );
+    if thin > 0 {
+        summaryln!(
+            "{} {} issue(s) have too little body text to benchmark meaningfully",
+            "!".yellow(),
+            thin
+        );
+    }
 
     if failed > 0 {
-        println!("\n{} Failed entries:", "!".red());
+        summaryln!("\n{} Failed entries:", "!".red());
         for r in results.iter().filter(|r| !r.issue_accessible) {
-            println!(
+            summaryln!(
                 "  - {}: {}",
                 r.id,
                 r.error.as_deref().unwrap_or("unknown error")
@@ -176,6 +565,52 @@ fn cmd_validate(args: ValidateArgs) -> Result<()> {
     Ok(())
 }
 
+/// Generate a corpus file from a GitHub issue search.
+fn cmd_corpus_generate(args: CorpusGenerateArgs) -> Result<()> {
+    outln!("{} Searching {}...", ">>".yellow(), args.query.cyan());
+
+    let corpus = fmm_bench::batch::generate_corpus(&args.query, args.limit)?;
+    if corpus.is_empty() {
+        anyhow::bail!("No issues matched {}", args.query);
+    }
+
+    let json = serde_json::to_string_pretty(&corpus)? + "\n";
+    fmm_bench::write_atomic(&args.output, json.as_bytes())?;
+    summaryln!(
+        "{} Wrote {} entries to {}",
+        "+".green(),
+        corpus.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Build a corpus from saved issue-run reports.
+fn cmd_corpus_from_reports(args: CorpusFromReportsArgs) -> Result<()> {
+    let dir = match args.reports {
+        Some(dir) => dir,
+        None => fmm_bench::batch::cached_reports_dir()?,
+    };
+    outln!("{} Scanning {}...", ">>".yellow(), dir.display());
+
+    let reports = fmm_bench::batch::load_reports(&dir)?;
+    let corpus = fmm_bench::batch::corpus_from_reports(&reports);
+    if corpus.is_empty() {
+        anyhow::bail!("No issue-run reports found in {}", dir.display());
+    }
+
+    let json = serde_json::to_string_pretty(&corpus)? + "\n";
+    fmm_bench::write_atomic(&args.output, json.as_bytes())?;
+    summaryln!(
+        "{} Wrote {} entries from {} reports to {}",
+        "+".green(),
+        corpus.len(),
+        reports.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
 fn to_report_format(fmt: OutputFormat) -> fmm_bench::ReportFormat {
     match fmt {
         OutputFormat::Json => fmm_bench::ReportFormat::Json,
@@ -192,7 +627,15 @@ fn to_report_format(fmt: OutputFormat) -> fmm_bench::ReportFormat {
 )]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+
+    /// List the model names `--model` accepts and exit
+    #[arg(long)]
+    list_models: bool,
+
+    /// Suppress progress output; print only the final summary and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand)]
@@ -205,6 +648,32 @@ enum Commands {
     Batch(BatchArgs),
     /// Validate a corpus file (check all issues are accessible)
     Validate(ValidateArgs),
+    /// Grade a kept checkout with the evaluator, without running Claude
+    Eval(EvalArgs),
+    /// Compare two batch aggregate.json files, per language and overall
+    AggregateDiff(AggregateDiffArgs),
+    /// Re-grade a saved report's evaluations with this build's rubric
+    Regrade(RegradeArgs),
+    /// Corpus utilities
+    #[command(subcommand)]
+    Corpus(CorpusCommand),
+    /// Custom task file utilities
+    #[command(subcommand)]
+    Tasks(TasksCommand),
+}
+
+#[derive(Subcommand)]
+enum TasksCommand {
+    /// Check a custom task file (ids, prompt sizes, budgets, turns, categories)
+    Validate(TasksValidateArgs),
+}
+
+#[derive(Subcommand)]
+enum CorpusCommand {
+    /// Generate a corpus from a GitHub issue search
+    Generate(CorpusGenerateArgs),
+    /// Build a corpus from the issues of saved `run` reports
+    FromReports(CorpusFromReportsArgs),
 }
 
 #[derive(Parser)]
@@ -216,10 +685,15 @@ struct RunArgs {
     #[arg(short, long)]
     branch: Option<String>,
 
-    /// Model to use for Claude CLI
+    /// Model to use for Claude CLI (see --list-models; `any` or any:<name> skips the check)
     #[arg(long, default_value = "sonnet")]
     model: String,
 
+    /// JSON file mapping model aliases to ids [default: fmm-bench/models.json
+    /// in the config dir, if present]
+    #[arg(long, value_name = "PATH")]
+    model_alias_file: Option<PathBuf>,
+
     /// Max spend per condition in USD
     #[arg(long, default_value = "5.0")]
     budget: f64,
@@ -228,17 +702,172 @@ struct RunArgs {
     #[arg(long, default_value = "1")]
     runs: u32,
 
-    /// Output directory for results
+    /// Output directory for results, or a `.md`/`.json` file path
     #[arg(short, long)]
     output: Option<PathBuf>,
 
-    /// Output format
-    #[arg(long, value_enum, default_value = "both")]
-    format: OutputFormat,
+    /// Output format [default: both, or implied by an --output file extension]
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
 
     /// Disable result caching
     #[arg(long)]
     no_cache: bool,
+
+    /// Write the JSON report to stdout (progress and summary go to stderr)
+    #[arg(long)]
+    stdout: bool,
+
+    /// Print a single-line summary instead of the full breakdown
+    #[arg(long)]
+    compact: bool,
+
+    /// Fail if any task's tool-call reduction falls short of this baseline
+    /// (JSON object of task id -> expected reduction %) by more than the tolerance
+    #[arg(long, value_name = "PATH")]
+    compare_with_baseline_json: Option<PathBuf>,
+
+    /// Allowed shortfall versus --compare-with-baseline-json, in percentage points
+    #[arg(
+        long,
+        value_name = "PP",
+        default_value = "5.0",
+        requires = "compare_with_baseline_json"
+    )]
+    baseline_tolerance: f64,
+
+    /// Commit message format both conditions must use (e.g. "fmm-bench: <summary>")
+    #[arg(long)]
+    commit_message_template: Option<String>,
+
+    /// Abort if fmm generates no sidecars or `fmm init` installs only some of
+    /// its files (instead of running a weak treatment)
+    #[arg(long)]
+    require_sidecars: bool,
+
+    /// Exclude degenerate results (zero tool calls, no answer) from the summary
+    #[arg(long)]
+    exclude_degenerate: bool,
+
+    /// Exclude tasks whose FMM run read no sidecars and made no fmm MCP calls
+    /// from the summary
+    #[arg(long)]
+    require_treatment_used: bool,
+
+    /// Max stream-json output parsed per run, in MiB (run fails as output_too_large beyond it)
+    #[arg(long, default_value = "512")]
+    max_output_mb: u64,
+
+    /// Retries for a run whose CLI fails before doing any work
+    #[arg(long, default_value = "1")]
+    retries: u32,
+
+    /// Append this file's text to the control condition's system prompt (ablation)
+    #[arg(long, value_name = "FILE")]
+    control_context: Option<PathBuf>,
+
+    /// Extra arguments for `fmm generate`, space-separated (e.g. "--lang ts")
+    #[arg(long, value_name = "ARGS", allow_hyphen_values = true)]
+    sidecar_args: Option<String>,
+
+    /// Abort a condition's run once it makes more than N tool calls
+    #[arg(long, value_name = "N")]
+    tool_budget: Option<u32>,
+
+    /// Warn about issues whose body is shorter than this after sanitization (0 = off)
+    #[arg(long, default_value_t = fmm_bench::issue::DEFAULT_MIN_BODY_CHARS)]
+    min_body_chars: usize,
+
+    /// Skip issues whose body is too short instead of just warning
+    #[arg(long)]
+    skip_empty_body: bool,
+
+    /// Truncate the issue body to roughly N tokens (chars/4 estimate) before building the prompt
+    #[arg(long, value_name = "N")]
+    max_body_tokens: Option<usize>,
+
+    /// Fetch raw files and gists linked from the issue body (size-capped) and
+    /// append them to the prompt
+    #[arg(long)]
+    fetch_linked: bool,
+
+    /// Warn when the issue has none of these labels (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    include_labels: Vec<String>,
+
+    /// Warn when the issue has any of these labels (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    exclude_labels: Vec<String>,
+
+    /// Save each run's raw stream-json to <DIR>/<job>-<task>-<variant>.jsonl
+    #[arg(long, value_name = "DIR")]
+    export_raw: Option<PathBuf>,
+
+    /// Save each run's readable transcript (text and tool calls in order) to
+    /// <DIR>/<job>-<task>-<variant>.log
+    #[arg(long, value_name = "DIR")]
+    keep_claude_logs: Option<PathBuf>,
+
+    /// Fail on malformed stream-json lines and warn on unknown event types (for CI)
+    #[arg(long)]
+    strict_json: bool,
+
+    /// Append each finished task comparison to FILE as one JSON line, as the run progresses
+    #[arg(long, value_name = "FILE")]
+    ndjson: Option<PathBuf>,
+
+    /// Embed each condition's unified diff (truncated) in the markdown report
+    #[arg(long)]
+    include_diff: bool,
+
+    #[command(flatten)]
+    checks: EvalCheckArgs,
+
+    /// Only run the control condition K times and report its run-to-run variance
+    #[arg(long, value_name = "K", value_parser = clap::value_parser!(u32).range(2..))]
+    compare_baselines: Option<u32>,
+
+    /// Regression mode: run the issue with this fmm binary, then with --fmm-bin-b
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "fmm_bin_b",
+        conflicts_with = "compare_baselines"
+    )]
+    fmm_bin_a: Option<PathBuf>,
+
+    /// fmm binary to compare against --fmm-bin-a (e.g. a newer build)
+    #[arg(long, value_name = "PATH", requires = "fmm_bin_a")]
+    fmm_bin_b: Option<PathBuf>,
+
+    /// Skip the confirmation prompt for budgets above $50
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Print a per-stage timing breakdown (clone, sidecars, runs, eval) and store it in the report
+    #[arg(long)]
+    profile: bool,
+
+    /// Stop starting new tasks/issues after this many seconds of wall-clock
+    /// time and write a partial report
+    #[arg(long, value_name = "SECS")]
+    max_wall_secs: Option<u64>,
+
+    /// Clone repos with this many commits of history (0 = full history)
+    #[arg(long, value_name = "N", default_value = "1")]
+    clone_depth: u32,
+
+    /// Kill a git clone that takes longer than this many seconds
+    #[arg(long, value_name = "SECS", default_value = "600")]
+    clone_timeout: u64,
+
+    /// Keep Claude's permission checks instead of passing
+    /// --dangerously-skip-permissions (runs needing unapproved tools may stall)
+    #[arg(long)]
+    require_permissions: bool,
+
+    #[command(flatten)]
+    settings: SettingsTemplateArgs,
 }
 
 #[derive(Parser)]
@@ -249,20 +878,35 @@ struct CompareArgs {
     #[arg(short, long)]
     branch: Option<String>,
 
+    /// Scope sidecar generation and task prompts to a subdirectory (monorepos)
     #[arg(long)]
     src_path: Option<String>,
 
+    /// Task set: auto (detect from repo language), standard, quick,
+    /// rust-navigation, web-frontend, python-navigation, go-navigation,
+    /// or a path to a custom JSON file
     #[arg(long, default_value = "standard")]
     tasks: String,
 
+    /// Only run tasks in these categories (exploration, understanding,
+    /// dependencies, exports; comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    category: Vec<fmm_bench::TaskCategory>,
+
     #[arg(long, default_value = "1")]
     runs: u32,
 
+    /// Run each task N times per condition and average them (std dev recorded per task)
+    #[arg(long, value_name = "N", default_value = "1")]
+    tasks_repeat: u32,
+
+    /// Output directory, or a `.md`/`.json` file path
     #[arg(short, long)]
     output: Option<PathBuf>,
 
-    #[arg(long, value_enum, default_value = "both")]
-    format: OutputFormat,
+    /// Output format [default: both, or implied by an --output file extension]
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
 
     #[arg(long, default_value = "10.0")]
     max_budget: f64,
@@ -275,6 +919,116 @@ struct CompareArgs {
 
     #[arg(long, default_value = "sonnet")]
     model: String,
+
+    /// JSON file mapping model aliases to ids (see `run`)
+    #[arg(long, value_name = "PATH")]
+    model_alias_file: Option<PathBuf>,
+
+    /// Write the JSON report to stdout (progress and summary go to stderr)
+    #[arg(long)]
+    stdout: bool,
+
+    /// Print a single-line summary instead of the full breakdown
+    #[arg(long)]
+    compact: bool,
+
+    /// Fail if any task's tool-call reduction falls short of this baseline
+    /// (JSON object of task id -> expected reduction %) by more than the tolerance
+    #[arg(long, value_name = "PATH")]
+    compare_with_baseline_json: Option<PathBuf>,
+
+    /// Allowed shortfall versus --compare-with-baseline-json, in percentage points
+    #[arg(
+        long,
+        value_name = "PP",
+        default_value = "5.0",
+        requires = "compare_with_baseline_json"
+    )]
+    baseline_tolerance: f64,
+
+    /// Abort if fmm generates no sidecars or `fmm init` installs only some of
+    /// its files (instead of running a weak treatment)
+    #[arg(long)]
+    require_sidecars: bool,
+
+    /// Shuffle task order with this seed (reproducible; recorded in the report)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Exclude degenerate results (zero tool calls, no answer) from the summary
+    #[arg(long)]
+    exclude_degenerate: bool,
+
+    /// Exclude tasks whose FMM run read no sidecars and made no fmm MCP calls
+    /// from the summary
+    #[arg(long)]
+    require_treatment_used: bool,
+
+    /// Max stream-json output parsed per run, in MiB (run fails as output_too_large beyond it)
+    #[arg(long, default_value = "512")]
+    max_output_mb: u64,
+
+    /// Retries for a run whose CLI fails before doing any work
+    #[arg(long, default_value = "1")]
+    retries: u32,
+
+    /// Append this file's text to the control condition's system prompt (ablation)
+    #[arg(long, value_name = "FILE")]
+    control_context: Option<PathBuf>,
+
+    /// Extra arguments for `fmm generate`, space-separated (e.g. "--lang ts")
+    #[arg(long, value_name = "ARGS", allow_hyphen_values = true)]
+    sidecar_args: Option<String>,
+
+    /// Abort a condition's run once it makes more than N tool calls
+    #[arg(long, value_name = "N")]
+    tool_budget: Option<u32>,
+
+    /// Save each run's raw stream-json to <DIR>/<job>-<task>-<variant>.jsonl
+    #[arg(long, value_name = "DIR")]
+    export_raw: Option<PathBuf>,
+
+    /// Save each run's readable transcript (text and tool calls in order) to
+    /// <DIR>/<job>-<task>-<variant>.log
+    #[arg(long, value_name = "DIR")]
+    keep_claude_logs: Option<PathBuf>,
+
+    /// Fail on malformed stream-json lines and warn on unknown event types (for CI)
+    #[arg(long)]
+    strict_json: bool,
+
+    /// Append each finished task comparison to FILE as one JSON line, as the run progresses
+    #[arg(long, value_name = "FILE")]
+    ndjson: Option<PathBuf>,
+
+    /// Skip the confirmation prompt for budgets above $50
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Print a per-stage timing breakdown (clone, sidecars, runs, eval) and store it in the report
+    #[arg(long)]
+    profile: bool,
+
+    /// Stop starting new tasks/issues after this many seconds of wall-clock
+    /// time and write a partial report
+    #[arg(long, value_name = "SECS")]
+    max_wall_secs: Option<u64>,
+
+    /// Clone repos with this many commits of history (0 = full history)
+    #[arg(long, value_name = "N", default_value = "1")]
+    clone_depth: u32,
+
+    /// Kill a git clone that takes longer than this many seconds
+    #[arg(long, value_name = "SECS", default_value = "600")]
+    clone_timeout: u64,
+
+    /// Keep Claude's permission checks instead of passing
+    /// --dangerously-skip-permissions (runs needing unapproved tools may stall)
+    #[arg(long)]
+    require_permissions: bool,
+
+    #[command(flatten)]
+    settings: SettingsTemplateArgs,
 }
 
 #[derive(Parser)]
@@ -286,6 +1040,10 @@ struct BatchArgs {
     #[arg(long, default_value = "50.0")]
     budget: f64,
 
+    /// Maximum spend per issue in USD (also capped by the remaining total budget)
+    #[arg(long, default_value = "10.0")]
+    budget_per_issue: f64,
+
     /// Number of runs per issue (for statistical significance)
     #[arg(long, default_value = "1")]
     runs: u32,
@@ -294,10 +1052,26 @@ struct BatchArgs {
     #[arg(long)]
     filter: Option<String>,
 
+    /// Skip this corpus id (e.g. owner/repo#123); repeatable
+    #[arg(long, value_name = "ID")]
+    exclude: Vec<String>,
+
+    /// Reject corpus entries with unknown keys (catches typos like "comit")
+    #[arg(long)]
+    strict_corpus: bool,
+
+    /// Skip the corpus ids listed in this file (one per line, # comment lines)
+    #[arg(long, value_name = "PATH")]
+    exclude_file: Option<PathBuf>,
+
     /// Skip issues with cached results
     #[arg(long)]
     resume: bool,
 
+    /// With --resume, skip issues that failed this many times in the last day (0 = never skip)
+    #[arg(long, default_value = "2")]
+    max_attempts: u32,
+
     /// Output directory for aggregate report
     #[arg(short, long)]
     output: Option<PathBuf>,
@@ -305,12 +1079,296 @@ struct BatchArgs {
     /// Model to use
     #[arg(long, default_value = "sonnet")]
     model: String,
+
+    /// JSON file mapping model aliases to ids (see `run`)
+    #[arg(long, value_name = "PATH")]
+    model_alias_file: Option<PathBuf>,
+
+    /// Commit message format both conditions must use (e.g. "fmm-bench: <summary>")
+    #[arg(long)]
+    commit_message_template: Option<String>,
+
+    /// Re-run only issues missing, errored, or graded F in this prior aggregate.json, merging into it
+    #[arg(long, value_name = "AGGREGATE_JSON")]
+    only_failed: Option<PathBuf>,
+
+    /// Merge this batch into an existing aggregate.json and rewrite it (e.g. per-language batches)
+    #[arg(long, value_name = "AGGREGATE_JSON", conflicts_with = "only_failed")]
+    append_to: Option<PathBuf>,
+
+    /// Also report tool calls normalized by issue difficulty (estimated files / complexity)
+    #[arg(long)]
+    normalize: bool,
+
+    /// Include the paired per-issue samples behind the summary in aggregate.json
+    #[arg(long)]
+    export_raw_pairs: bool,
+
+    /// Limit the markdown's language/per-issue breakdowns to these (e.g. rust,go)
+    #[arg(long, value_delimiter = ',')]
+    languages: Vec<String>,
+
+    /// Append one row per issue to this SQLite database (needs the `sqlite` feature)
+    #[arg(long, value_name = "PATH")]
+    sqlite: Option<PathBuf>,
+
+    /// Warn about issues whose body is shorter than this after sanitization (0 = off)
+    #[arg(long, default_value_t = fmm_bench::issue::DEFAULT_MIN_BODY_CHARS)]
+    min_body_chars: usize,
+
+    /// Skip issues whose body is too short instead of just warning
+    #[arg(long)]
+    skip_empty_body: bool,
+
+    /// Truncate the issue body to roughly N tokens (chars/4 estimate) before building the prompt
+    #[arg(long, value_name = "N")]
+    max_body_tokens: Option<usize>,
+
+    /// Fetch raw files and gists linked from the issue body (size-capped) and
+    /// append them to the prompt
+    #[arg(long)]
+    fetch_linked: bool,
+
+    /// Only run issues with at least one of these labels (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    include_labels: Vec<String>,
+
+    /// Skip issues with any of these labels (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    exclude_labels: Vec<String>,
+
+    /// Run this task set (built-in name or path) on each entry's repo instead
+    /// of the issue; an entry's own `task_set` takes precedence
+    #[arg(long, value_name = "SET")]
+    tasks: Option<String>,
+
+    /// Skip the confirmation prompt for budgets above $50
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Print a per-stage timing breakdown (clone, sidecars, runs, eval) and store it in the report
+    #[arg(long)]
+    profile: bool,
+
+    /// Stop starting new tasks/issues after this many seconds of wall-clock
+    /// time and write a partial report
+    #[arg(long, value_name = "SECS")]
+    max_wall_secs: Option<u64>,
+
+    /// Clone repos with this many commits of history (0 = full history)
+    #[arg(long, value_name = "N", default_value = "1")]
+    clone_depth: u32,
+
+    /// Kill a git clone that takes longer than this many seconds
+    #[arg(long, value_name = "SECS", default_value = "600")]
+    clone_timeout: u64,
+
+    /// Keep Claude's permission checks instead of passing
+    /// --dangerously-skip-permissions (runs needing unapproved tools may stall)
+    #[arg(long)]
+    require_permissions: bool,
+
+    /// Abort a condition's run once it makes more than N tool calls
+    #[arg(long, value_name = "N")]
+    tool_budget: Option<u32>,
+
+    /// Retries for a run whose CLI fails before doing any work
+    #[arg(long, default_value = "1")]
+    retries: u32,
+
+    /// Max stream-json output parsed per run, in MiB (run fails as output_too_large beyond it)
+    #[arg(long, default_value = "512")]
+    max_output_mb: u64,
+
+    /// Embed each condition's unified diff (truncated) in the per-issue reports
+    #[arg(long)]
+    include_diff: bool,
+
+    /// Exclude tasks whose FMM run read no sidecars and made no fmm MCP calls
+    /// from each issue's summary
+    #[arg(long)]
+    require_treatment_used: bool,
+
+    /// Fail an issue (recorded under `failures`) if fmm generates no sidecars
+    /// or `fmm init` installs only some of its files
+    #[arg(long)]
+    require_sidecars: bool,
+
+    #[command(flatten)]
+    settings: SettingsTemplateArgs,
+
+    #[command(flatten)]
+    checks: EvalCheckArgs,
+}
+
+#[derive(Parser)]
+struct EvalArgs {
+    /// Git checkout to grade, or a sandbox root with control/ and fmm/
+    dir: PathBuf,
+
+    #[command(flatten)]
+    checks: EvalCheckArgs,
+}
+
+#[derive(Parser)]
+struct AggregateDiffArgs {
+    /// Earlier aggregate.json
+    before: PathBuf,
+
+    /// Later aggregate.json
+    after: PathBuf,
+
+    /// Write the JSON comparison to this file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    compare_json: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+struct RegradeArgs {
+    /// Report JSON written by `run` or `compare`
+    report: PathBuf,
+
+    /// Write the re-graded report here (`.json`, `.md`, or a directory)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+struct TasksValidateArgs {
+    /// Custom task JSON file, as passed to `--tasks`
+    path: PathBuf,
 }
 
 #[derive(Parser)]
 struct ValidateArgs {
     /// Path to corpus JSON file
     corpus: PathBuf,
+
+    /// Stop at the first inaccessible issue instead of checking every entry
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Concurrent `gh` lookups (paced by a shared rate limiter)
+    #[arg(short, long, default_value_t = fmm_bench::batch::DEFAULT_VALIDATE_JOBS)]
+    jobs: usize,
+
+    /// Warn about issues whose body is shorter than this after sanitization (0 = off)
+    #[arg(long, default_value_t = fmm_bench::issue::DEFAULT_MIN_BODY_CHARS)]
+    min_body_chars: usize,
+
+    /// Reject corpus entries with unknown keys (catches typos like "comit")
+    #[arg(long)]
+    strict_corpus: bool,
+}
+
+#[derive(Parser)]
+struct CorpusGenerateArgs {
+    /// GitHub search query with a repo qualifier, e.g. "repo:owner/name is:issue label:bug"
+    query: String,
+
+    /// Maximum number of issues to include
+    #[arg(long, default_value = "20")]
+    limit: u32,
+
+    /// Corpus file to write
+    #[arg(short, long, default_value = "corpus.json")]
+    output: PathBuf,
+}
+
+#[derive(Parser)]
+struct CorpusFromReportsArgs {
+    /// Directory of report JSON files [default: the cache's reports directory]
+    reports: Option<PathBuf>,
+
+    /// Corpus file to write
+    #[arg(short, long, default_value = "corpus.json")]
+    output: PathBuf,
+}
+
+/// Evaluator flags shared by `run`, `batch`, and `eval`.
+#[derive(Args)]
+struct EvalCheckArgs {
+    /// Don't run the build when grading (neutral for the grade)
+    #[arg(long)]
+    no_build_check: bool,
+
+    /// Don't run the test suite when grading (neutral for the grade)
+    #[arg(long)]
+    no_test_check: bool,
+
+    /// Run the test command up to N times to ride out flaky suites
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    test_reruns: u32,
+
+    /// How test reruns combine: pass if any run passes, or most of them
+    #[arg(long, value_enum, default_value = "any")]
+    test_rerun_policy: RerunPolicyArg,
+}
+
+#[derive(Args)]
+struct SettingsTemplateArgs {
+    /// Merge this .claude/settings.json into the workspace's local settings
+    /// (after fmm init) before each run, e.g. to try other MCP servers or hooks
+    #[arg(long, value_name = "PATH")]
+    settings_template: Option<PathBuf>,
+
+    /// Workspaces that get the settings template
+    #[arg(long, value_enum, default_value = "fmm")]
+    settings_template_scope: SettingsScopeArg,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SettingsScopeArg {
+    /// The FMM condition only
+    Fmm,
+    /// Both conditions (control then loads local settings too)
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum RerunPolicyArg {
+    Any,
+    Majority,
+}
+
+/// Absolute deadline for `--max-wall-secs`, counted from now.
+fn wall_deadline(secs: Option<u64>) -> Option<Instant> {
+    secs.map(|secs| Instant::now() + Duration::from_secs(secs))
+}
+
+fn settings_template(args: &SettingsTemplateArgs) -> Option<fmm_bench::sandbox::SettingsTemplate> {
+    use fmm_bench::sandbox::{SettingsScope, SettingsTemplate};
+    args.settings_template
+        .as_ref()
+        .map(|path| SettingsTemplate {
+            path: path.clone(),
+            scope: match args.settings_template_scope {
+                SettingsScopeArg::Fmm => SettingsScope::Fmm,
+                SettingsScopeArg::Both => SettingsScope::Both,
+            },
+        })
+}
+
+/// `--model-alias-file`, else the default alias file if one exists.
+fn model_alias_file(path: Option<PathBuf>) -> Option<PathBuf> {
+    path.or_else(fmm_bench::default_model_alias_file)
+}
+
+/// `--clone-depth` as a depth limit, with 0 meaning full history.
+fn clone_depth(depth: u32) -> Option<u32> {
+    (depth > 0).then_some(depth)
+}
+
+fn eval_options(checks: &EvalCheckArgs) -> fmm_bench::evaluator::EvalOptions {
+    fmm_bench::evaluator::EvalOptions {
+        skip_build: checks.no_build_check,
+        skip_tests: checks.no_test_check,
+        test_reruns: checks.test_reruns,
+        rerun_policy: match checks.test_rerun_policy {
+            RerunPolicyArg::Any => fmm_bench::evaluator::RerunPolicy::Any,
+            RerunPolicyArg::Majority => fmm_bench::evaluator::RerunPolicy::Majority,
+        },
+    }
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
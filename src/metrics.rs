@@ -3,11 +3,21 @@
 //! Extracts rich per-tool breakdowns, navigation efficiency, fmm usage
 //! tracking, and outcome metrics from Claude's stream-json JSONL output.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Read};
 use std::time::Duration;
 
+/// Default cap on stream-json bytes parsed from a single run (512 MiB).
+pub const DEFAULT_MAX_OUTPUT_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Error recorded when a run's stream-json exceeds the byte cap.
+pub const OUTPUT_TOO_LARGE: &str = "output_too_large";
+
+/// Error recorded when a run is aborted for exceeding its tool-call budget.
+pub const TOOL_BUDGET_EXCEEDED: &str = "tool_budget_exceeded";
+
 /// Per-tool detail: count + associated args (files, patterns, commands).
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolDetail {
@@ -42,6 +52,19 @@ pub struct FmmUsage {
     pub fmm_tool_names: Vec<String>,
 }
 
+/// Tool calls bucketed by intent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCategoryCounts {
+    /// Read/Glob/Grep/LS and fmm MCP lookups.
+    pub navigation: u32,
+    /// Edit/Write/MultiEdit.
+    pub mutation: u32,
+    /// Bash invocations that run tests or builds.
+    pub verification: u32,
+    /// Everything else.
+    pub other: u32,
+}
+
 /// Accumulated metrics from a Claude CLI run.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RunMetrics {
@@ -58,6 +81,15 @@ pub struct RunMetrics {
     pub read_calls: u32,
     pub success: bool,
     pub error: Option<String>,
+    /// Session id from the `system`/`init` event.
+    pub session_id: Option<String>,
+    /// Model the CLI actually ran, from the `system`/`init` event.
+    pub actual_model: Option<String>,
+    /// The run was cut off by `--max-turns` rather than finishing.
+    pub hit_turn_limit: bool,
+    /// Tool calls identical (same tool, same input) to the one just before
+    /// them; a run of these is a strong sign the agent is stuck in a loop.
+    pub repeated_tool_invocations: u32,
 
     /// Per-tool detail with args.
     pub tool_details: HashMap<String, ToolDetail>,
@@ -65,21 +97,130 @@ pub struct RunMetrics {
     pub navigation: NavigationMetrics,
     /// FMM-specific usage tracking.
     pub fmm_usage: FmmUsage,
+    /// Tool calls bucketed by category.
+    pub tool_categories: ToolCategoryCounts,
 }
 
 /// Parsed output from a Claude CLI stream-json invocation.
 #[derive(Debug, Clone)]
 pub struct ParsedOutput {
     pub metrics: RunMetrics,
+    /// The final assistant message (falls back to the result event text).
     pub response_text: String,
+    /// Every assistant text block, in order (plans, progress notes, final answer).
+    pub text_blocks: Vec<String>,
+    /// Assistant text and tool calls interleaved in stream order.
+    pub log: Vec<TranscriptEntry>,
+    /// Bytes of stream-json consumed.
+    pub bytes_read: u64,
+    /// Unrecognized event types, each with the line it first appeared on
+    /// (collected in strict mode only, for the caller to warn about).
+    pub unknown_event_types: Vec<(String, u64)>,
+}
+
+/// One step of a run, in the order the assistant produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptEntry {
+    Text {
+        turn: u32,
+        text: String,
+    },
+    ToolCall {
+        turn: u32,
+        name: String,
+        input: String,
+    },
+}
+
+impl ParsedOutput {
+    /// All assistant text blocks joined into a single transcript.
+    pub fn transcript(&self) -> String {
+        self.text_blocks.join("\n\n")
+    }
+
+    /// Human-readable log of the run: each turn's text and tool calls (with
+    /// their JSON input), in order.
+    pub fn full_transcript(&self) -> String {
+        let mut out = String::new();
+        let mut last_turn = 0;
+        for entry in &self.log {
+            let turn = match entry {
+                TranscriptEntry::Text { turn, .. } | TranscriptEntry::ToolCall { turn, .. } => {
+                    *turn
+                }
+            };
+            if turn != last_turn {
+                out.push_str(&format!("=== Turn {} ===\n", turn));
+                last_turn = turn;
+            }
+            match entry {
+                TranscriptEntry::Text { text, .. } => {
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+                TranscriptEntry::ToolCall { name, input, .. } => {
+                    out.push_str(&format!("-> {} {}\n", name, input));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Whether a `result` subtype means the turn cap ended the run
+/// (`error_max_turns`, or `max_turns` in older CLIs).
+fn is_turn_limit_subtype(subtype: &str) -> bool {
+    subtype.ends_with("max_turns")
 }
 
 /// Parse Claude CLI stream-json output into metrics and response text.
 ///
 /// The `fallback_duration` is used when the result event doesn't include `duration_ms`.
 pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<ParsedOutput> {
+    parse_stream_json_reader(
+        output.as_bytes(),
+        fallback_duration,
+        DEFAULT_MAX_OUTPUT_BYTES,
+    )
+}
+
+/// Parse stream-json line by line from a reader, without buffering the whole
+/// output.
+///
+/// At most `max_bytes` are consumed (a single oversized line cannot exceed
+/// it either). Past the cap, parsing stops and the run is marked failed with
+/// `error = "output_too_large"`, keeping whatever metrics were seen so far.
+pub fn parse_stream_json_reader<R: BufRead>(
+    reader: R,
+    fallback_duration: Duration,
+    max_bytes: u64,
+) -> Result<ParsedOutput> {
+    parse_stream_json_limited(reader, fallback_duration, max_bytes, None, false, || {})
+}
+
+/// [`parse_stream_json_reader`] with an optional tool-call budget.
+///
+/// Once more than `max_tool_calls` tool calls have streamed, parsing stops
+/// and the run is marked failed with `error = "tool_budget_exceeded"`.
+/// `on_abort` runs as soon as parsing stops early for either cap, so the
+/// caller can kill the producer instead of leaving it blocked on a full pipe.
+///
+/// Parsing is lenient by default: malformed lines and unknown event types are
+/// skipped. With `strict`, a malformed line is an error and each unknown
+/// event type is recorded once in `unknown_event_types`, so CLI format
+/// changes surface.
+pub fn parse_stream_json_limited<R: BufRead>(
+    mut reader: R,
+    fallback_duration: Duration,
+    max_bytes: u64,
+    max_tool_calls: Option<u32>,
+    strict: bool,
+    on_abort: impl FnOnce(),
+) -> Result<ParsedOutput> {
     let mut metrics = RunMetrics::default();
     let mut response_text = String::new();
+    let mut text_blocks: Vec<String> = vec![];
+    let mut log: Vec<TranscriptEntry> = vec![];
     let mut final_result: Option<serde_json::Value> = None;
 
     // Track per-turn state for navigation efficiency
@@ -87,18 +228,55 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
     let mut first_edit_turn: u32 = 0;
     let mut files_read_set: HashSet<String> = HashSet::new();
     let mut files_edited_set: HashSet<String> = HashSet::new();
+    let mut last_invocation: Option<(String, String)> = None;
+
+    let mut bytes_read: u64 = 0;
+    let mut too_large = false;
+    let mut over_tool_budget = false;
+    let mut buf: Vec<u8> = Vec::new();
+    let mut line_no: u64 = 0;
+    let mut unknown_event_types: Vec<(String, u64)> = vec![];
+
+    loop {
+        buf.clear();
+        // Read one byte past the remaining budget so an overrun is detectable.
+        let remaining = max_bytes.saturating_sub(bytes_read);
+        let n = reader
+            .by_ref()
+            .take(remaining.saturating_add(1))
+            .read_until(b'\n', &mut buf)
+            .context("Failed to read stream-json output")?;
+        if n == 0 {
+            break;
+        }
+        line_no += 1;
+        bytes_read += n as u64;
+        if bytes_read > max_bytes {
+            too_large = true;
+            break;
+        }
 
-    for line in output.lines() {
+        let line = String::from_utf8_lossy(&buf);
         if line.trim().is_empty() {
             continue;
         }
 
-        let data: serde_json::Value = match serde_json::from_str(line) {
+        let data: serde_json::Value = match serde_json::from_str(&line) {
             Ok(v) => v,
+            Err(e) if strict => {
+                anyhow::bail!("Malformed stream-json on line {}: {}", line_no, e)
+            }
             Err(_) => continue,
         };
 
         match data.get("type").and_then(|v| v.as_str()) {
+            Some("system") if data.get("subtype").and_then(|v| v.as_str()) == Some("init") => {
+                metrics.session_id = data
+                    .get("session_id")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+                metrics.actual_model = data.get("model").and_then(|v| v.as_str()).map(String::from);
+            }
             Some("assistant") => {
                 current_turn += 1;
 
@@ -107,6 +285,24 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
                         for item in content {
                             match item.get("type").and_then(|t| t.as_str()) {
                                 Some("tool_use") => {
+                                    let invocation = (
+                                        item.get("name")
+                                            .and_then(|n| n.as_str())
+                                            .unwrap_or("<unnamed>")
+                                            .to_string(),
+                                        item.get("input")
+                                            .map(|i| i.to_string())
+                                            .unwrap_or_default(),
+                                    );
+                                    if last_invocation.as_ref() == Some(&invocation) {
+                                        metrics.repeated_tool_invocations += 1;
+                                    }
+                                    log.push(TranscriptEntry::ToolCall {
+                                        turn: current_turn,
+                                        name: invocation.0.clone(),
+                                        input: invocation.1.clone(),
+                                    });
+                                    last_invocation = Some(invocation);
                                     process_tool_use(
                                         item,
                                         &mut metrics,
@@ -119,6 +315,11 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
                                 Some("text") => {
                                     if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
                                         response_text = text.to_string();
+                                        text_blocks.push(text.to_string());
+                                        log.push(TranscriptEntry::Text {
+                                            turn: current_turn,
+                                            text: text.to_string(),
+                                        });
                                     }
                                 }
                                 _ => {}
@@ -126,6 +327,10 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
                         }
                     }
                 }
+                if max_tool_calls.is_some_and(|cap| metrics.tool_calls > cap) {
+                    over_tool_budget = true;
+                    break;
+                }
             }
             Some("result") => {
                 final_result = Some(data.clone());
@@ -165,10 +370,20 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
                     }
                 }
             }
-            _ => {}
+            Some("system") | Some("user") => {}
+            other => {
+                let kind = other.unwrap_or("<missing>");
+                if strict && !unknown_event_types.iter().any(|(k, _)| k == kind) {
+                    unknown_event_types.push((kind.to_string(), line_no));
+                }
+            }
         }
     }
 
+    if too_large || over_tool_budget {
+        on_abort();
+    }
+
     // Finalize success/error
     metrics.success = final_result
         .as_ref()
@@ -177,7 +392,13 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
         .map(|e| !e)
         .unwrap_or(false);
 
-    metrics.error = if !metrics.success {
+    metrics.error = if too_large {
+        metrics.success = false;
+        Some(OUTPUT_TOO_LARGE.to_string())
+    } else if over_tool_budget {
+        metrics.success = false;
+        Some(TOOL_BUDGET_EXCEEDED.to_string())
+    } else if !metrics.success {
         final_result
             .as_ref()
             .and_then(|r| r.get("subtype"))
@@ -187,6 +408,12 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
         None
     };
 
+    metrics.hit_turn_limit = final_result
+        .as_ref()
+        .and_then(|r| r.get("subtype"))
+        .and_then(|s| s.as_str())
+        .is_some_and(is_turn_limit_subtype);
+
     // Compute navigation efficiency
     metrics.navigation.unique_files_read = files_read_set.len() as u32;
     metrics.navigation.unique_files_edited = files_edited_set.len() as u32;
@@ -202,6 +429,10 @@ pub fn parse_stream_json(output: &str, fallback_duration: Duration) -> Result<Pa
     Ok(ParsedOutput {
         metrics,
         response_text,
+        text_blocks,
+        log,
+        bytes_read,
+        unknown_event_types,
     })
 }
 
@@ -223,6 +454,15 @@ fn process_tool_use(
     *metrics.tools_by_name.entry(name.to_string()).or_insert(0) += 1;
 
     let input = item.get("input");
+
+    let categories = &mut metrics.tool_categories;
+    match classify_tool(name, input) {
+        ToolCategory::Navigation => categories.navigation += 1,
+        ToolCategory::Mutation => categories.mutation += 1,
+        ToolCategory::Verification => categories.verification += 1,
+        ToolCategory::Other => categories.other += 1,
+    }
+
     let detail = metrics.tool_details.entry(name.to_string()).or_default();
     detail.count += 1;
 
@@ -305,6 +545,43 @@ fn process_tool_use(
     }
 }
 
+enum ToolCategory {
+    Navigation,
+    Mutation,
+    Verification,
+    Other,
+}
+
+/// Keywords in a Bash command that indicate a test or build run.
+const VERIFICATION_KEYWORDS: &[&str] = &[
+    "test", "build", "check", "pytest", "jest", "vitest", "tsc", "clippy", "make",
+];
+
+/// Classify a tool call by intent. Bash is classified by a keyword heuristic
+/// on the command string.
+fn classify_tool(name: &str, input: Option<&serde_json::Value>) -> ToolCategory {
+    match name {
+        "Read" | "View" | "Glob" | "Grep" | "LS" => ToolCategory::Navigation,
+        "Edit" | "Write" | "MultiEdit" | "NotebookEdit" => ToolCategory::Mutation,
+        "Bash" => {
+            let command = input
+                .and_then(|i| i.get("command"))
+                .and_then(|c| c.as_str())
+                .unwrap_or("");
+            let is_verification = command
+                .split(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_')
+                .any(|word| VERIFICATION_KEYWORDS.contains(&word));
+            if is_verification {
+                ToolCategory::Verification
+            } else {
+                ToolCategory::Other
+            }
+        }
+        _ if name.starts_with("fmm_") || name.starts_with("mcp__fmm") => ToolCategory::Navigation,
+        _ => ToolCategory::Other,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,6 +606,30 @@ mod tests {
         assert_eq!(parsed.metrics.duration_ms, 5000);
     }
 
+    #[test]
+    fn parse_max_turns_result() {
+        let output = r#"{"type":"result","subtype":"error_max_turns","is_error":false,"total_cost_usd":0.4,"num_turns":31,"usage":{"input_tokens":100,"output_tokens":50}}"#;
+        let parsed = parse_stream_json(output, dur(0)).unwrap();
+        assert!(parsed.metrics.hit_turn_limit);
+
+        let output = r#"{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.1,"num_turns":3,"usage":{"input_tokens":100,"output_tokens":50}}"#;
+        let parsed = parse_stream_json(output, dur(0)).unwrap();
+        assert!(!parsed.metrics.hit_turn_limit);
+    }
+
+    #[test]
+    fn parse_init_event() {
+        let output = r#"{"type":"system","subtype":"init","session_id":"abc-123","model":"claude-sonnet-4-5-20250929","tools":["Read","Edit"]}
+{"type":"result","is_error":false,"result":"Done","total_cost_usd":0.01,"num_turns":1,"usage":{"input_tokens":10,"output_tokens":5}}"#;
+        let parsed = parse_stream_json(output, dur(0)).unwrap();
+        assert_eq!(parsed.metrics.session_id.as_deref(), Some("abc-123"));
+        assert_eq!(
+            parsed.metrics.actual_model.as_deref(),
+            Some("claude-sonnet-4-5-20250929")
+        );
+        assert!(parsed.metrics.success);
+    }
+
     #[test]
     fn parse_error_result() {
         let output = r#"{"type":"result","is_error":true,"subtype":"budget_exceeded","total_cost_usd":5.0,"num_turns":30,"usage":{"input_tokens":10000,"output_tokens":5000},"duration_ms":60000}"#;
@@ -491,4 +792,261 @@ mod tests {
             vec!["createStore"]
         );
     }
+
+    #[test]
+    fn consecutive_identical_tool_calls_counted() {
+        let grep = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Grep","input":{"pattern":"createStore"}}]}}"#;
+        let output = format!(
+            "{grep}\n{grep}\n{grep}\n{}\n{}",
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Grep","input":{"pattern":"createSlice"}}]}}"#,
+            r#"{"type":"result","is_error":false,"usage":{"input_tokens":10,"output_tokens":5},"total_cost_usd":0.001,"num_turns":4,"duration_ms":100}"#
+        );
+
+        let parsed = parse_stream_json(&output, dur(100)).unwrap();
+        assert_eq!(parsed.metrics.tool_calls, 4);
+        // The 2nd and 3rd Grep repeat the one before; a new pattern doesn't
+        assert_eq!(parsed.metrics.repeated_tool_invocations, 2);
+    }
+
+    #[test]
+    fn tool_categories_tallied() {
+        let output = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Grep","input":{"pattern":"foo"}},{"type":"tool_use","name":"Read","input":{"file_path":"src/a.rs"}},{"type":"tool_use","name":"mcp__fmm__lookup_export","input":{}}]}}
+{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Edit","input":{"file_path":"src/a.rs"}},{"type":"tool_use","name":"MultiEdit","input":{"file_path":"src/b.rs"}}]}}
+{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Bash","input":{"command":"cargo test --lib"}},{"type":"tool_use","name":"Bash","input":{"command":"npm run build"}},{"type":"tool_use","name":"Bash","input":{"command":"git status"}},{"type":"tool_use","name":"TodoWrite","input":{}}]}}
+{"type":"result","is_error":false,"usage":{"input_tokens":10,"output_tokens":5},"total_cost_usd":0.001,"num_turns":3,"duration_ms":100}"#;
+
+        let parsed = parse_stream_json(output, dur(100)).unwrap();
+        let cats = &parsed.metrics.tool_categories;
+        assert_eq!(cats.navigation, 3);
+        assert_eq!(cats.mutation, 2);
+        assert_eq!(cats.verification, 2);
+        assert_eq!(cats.other, 2);
+        assert_eq!(
+            cats.navigation + cats.mutation + cats.verification + cats.other,
+            parsed.metrics.tool_calls
+        );
+    }
+
+    #[test]
+    fn all_text_blocks_retained() {
+        let output = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Plan: read config.rs, then patch the parser."},{"type":"tool_use","name":"Read","input":{"file_path":"src/config.rs"}}]}}
+{"type":"assistant","message":{"content":[{"type":"text","text":"Done."}]}}
+{"type":"result","is_error":false,"usage":{"input_tokens":10,"output_tokens":5},"total_cost_usd":0.001,"num_turns":2,"duration_ms":100}"#;
+
+        let parsed = parse_stream_json(output, dur(100)).unwrap();
+        assert_eq!(parsed.response_text, "Done.");
+        assert_eq!(parsed.text_blocks.len(), 2);
+        assert_eq!(
+            parsed.text_blocks[0],
+            "Plan: read config.rs, then patch the parser."
+        );
+        let transcript = parsed.transcript();
+        assert!(transcript.contains("Plan: read config.rs"));
+        assert!(transcript.ends_with("Done."));
+    }
+
+    #[test]
+    fn full_transcript_keeps_tool_calls_in_order() {
+        let output = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Looking for the parser."},{"type":"tool_use","name":"Grep","input":{"pattern":"fn parse"}},{"type":"tool_use","name":"Read","input":{"file_path":"src/parser.rs"}}]}}
+{"type":"user","message":{"content":[{"type":"tool_result","content":"..."}]}}
+{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Edit","input":{"file_path":"src/parser.rs"}},{"type":"text","text":"Fixed."}]}}
+{"type":"result","is_error":false,"usage":{"input_tokens":10,"output_tokens":5},"total_cost_usd":0.001,"num_turns":2,"duration_ms":100}"#;
+
+        let parsed = parse_stream_json(output, dur(100)).unwrap();
+        assert_eq!(parsed.log.len(), 5);
+        let log = parsed.full_transcript();
+        let order: Vec<usize> = [
+            "=== Turn 1 ===",
+            "Looking for the parser.",
+            r#"-> Grep {"pattern":"fn parse"}"#,
+            r#"-> Read {"file_path":"src/parser.rs"}"#,
+            "=== Turn 2 ===",
+            r#"-> Edit {"file_path":"src/parser.rs"}"#,
+            "Fixed.",
+        ]
+        .iter()
+        .map(|needle| {
+            log.find(needle)
+                .unwrap_or_else(|| panic!("missing {needle}"))
+        })
+        .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]), "{log}");
+    }
+
+    /// Generates `lines` synthetic tool_use events on demand (followed by a
+    /// result event), so the full stream never exists in memory at once.
+    struct SyntheticStream {
+        lines: usize,
+        emitted: usize,
+        pending: Vec<u8>,
+        pos: usize,
+        served: u64,
+    }
+
+    impl SyntheticStream {
+        fn new(lines: usize) -> Self {
+            Self {
+                lines,
+                emitted: 0,
+                pending: vec![],
+                pos: 0,
+                served: 0,
+            }
+        }
+    }
+
+    impl Read for SyntheticStream {
+        fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+            if self.pos == self.pending.len() {
+                self.pending = if self.emitted < self.lines {
+                    format!(
+                        "{{\"type\":\"assistant\",\"message\":{{\"content\":[{{\"type\":\"tool_use\",\"name\":\"Grep\",\"input\":{{\"pattern\":\"p{}\"}}}}]}}}}\n",
+                        self.emitted
+                    )
+                    .into_bytes()
+                } else if self.emitted == self.lines {
+                    br#"{"type":"result","is_error":false,"result":"Done"}"#.to_vec()
+                } else {
+                    return Ok(0);
+                };
+                self.emitted += 1;
+                self.pos = 0;
+            }
+            let n = out.len().min(self.pending.len() - self.pos);
+            out[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+            self.pos += n;
+            self.served += n as u64;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn parse_reader_streams_large_output_in_chunks() {
+        let lines = 100_000;
+        let reader = std::io::BufReader::with_capacity(64, SyntheticStream::new(lines));
+        let parsed = parse_stream_json_reader(reader, dur(0), DEFAULT_MAX_OUTPUT_BYTES).unwrap();
+
+        assert!(parsed.metrics.success);
+        assert_eq!(parsed.metrics.tool_calls, lines as u32);
+        assert_eq!(parsed.response_text, "Done");
+        assert!(parsed.bytes_read > 8 * 1024 * 1024);
+    }
+
+    #[test]
+    fn parse_reader_caps_output_size() {
+        let cap = 64 * 1024;
+        let mut stream = SyntheticStream::new(1_000_000);
+        let parsed =
+            parse_stream_json_reader(std::io::BufReader::new(&mut stream), dur(0), cap).unwrap();
+
+        assert!(!parsed.metrics.success);
+        assert_eq!(parsed.metrics.error.as_deref(), Some(OUTPUT_TOO_LARGE));
+        assert!(parsed.metrics.tool_calls > 0);
+        // Stopped right after the cap instead of draining the whole stream
+        assert!(stream.served < cap + 16 * 1024, "served {}", stream.served);
+    }
+
+    #[test]
+    fn parse_reader_caps_single_oversized_line() {
+        let line = format!(
+            "{{\"type\":\"assistant\",\"pad\":\"{}\"}}",
+            "x".repeat(10_000)
+        );
+        let parsed = parse_stream_json_reader(line.as_bytes(), dur(0), 1024).unwrap();
+        assert_eq!(parsed.metrics.error.as_deref(), Some(OUTPUT_TOO_LARGE));
+        assert_eq!(parsed.bytes_read, 1025);
+    }
+
+    #[test]
+    fn parse_reader_aborts_over_tool_budget() {
+        let mut stream = SyntheticStream::new(1_000);
+        let mut aborted = false;
+        let parsed = parse_stream_json_limited(
+            std::io::BufReader::with_capacity(64, &mut stream),
+            dur(0),
+            DEFAULT_MAX_OUTPUT_BYTES,
+            Some(5),
+            false,
+            || aborted = true,
+        )
+        .unwrap();
+
+        assert!(aborted);
+        assert!(!parsed.metrics.success);
+        assert_eq!(parsed.metrics.error.as_deref(), Some(TOOL_BUDGET_EXCEEDED));
+        assert_eq!(parsed.metrics.tool_calls, 6);
+        // Stopped on the call that crossed the cap, not at the end of the stream
+        assert_eq!(stream.emitted, 6);
+    }
+
+    #[test]
+    fn parse_reader_within_tool_budget_completes() {
+        let mut aborted = false;
+        let parsed = parse_stream_json_limited(
+            std::io::BufReader::new(SyntheticStream::new(5)),
+            dur(0),
+            DEFAULT_MAX_OUTPUT_BYTES,
+            Some(5),
+            false,
+            || aborted = true,
+        )
+        .unwrap();
+
+        assert!(!aborted);
+        assert!(parsed.metrics.success);
+        assert_eq!(parsed.metrics.tool_calls, 5);
+    }
+
+    #[test]
+    fn strict_mode_rejects_malformed_line() {
+        let output = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Read","input":{"file_path":"a.rs"}}]}}
+{"type":"assistant","message":{"content":[{"type":"tool_u
+{"type":"rate_limit_event","retry_after":1}
+{"type":"result","is_error":false,"total_cost_usd":0.01,"num_turns":1}"#;
+        let parse = |strict: bool| {
+            parse_stream_json_limited(
+                output.as_bytes(),
+                dur(0),
+                DEFAULT_MAX_OUTPUT_BYTES,
+                None,
+                strict,
+                || {},
+            )
+        };
+
+        // Lenient (default): the broken line and unknown event are skipped
+        let parsed = parse(false).unwrap();
+        assert!(parsed.metrics.success);
+        assert_eq!(parsed.metrics.tool_calls, 1);
+
+        let err = parse(true).unwrap_err().to_string();
+        assert!(err.contains("Malformed stream-json on line 2"), "{}", err);
+    }
+
+    #[test]
+    fn strict_mode_allows_unknown_event_types() {
+        let output = r#"{"type":"rate_limit_event","retry_after":1}
+{"type":"rate_limit_event","retry_after":2}
+{"type":"result","is_error":false,"total_cost_usd":0.01,"num_turns":1}"#;
+        let parse = |strict: bool| {
+            parse_stream_json_limited(
+                output.as_bytes(),
+                dur(0),
+                DEFAULT_MAX_OUTPUT_BYTES,
+                None,
+                strict,
+                || {},
+            )
+            .unwrap()
+        };
+
+        let parsed = parse(true);
+        assert!(parsed.metrics.success);
+        // Recorded once, at its first line
+        assert_eq!(
+            parsed.unknown_event_types,
+            vec![("rate_limit_event".to_string(), 1)]
+        );
+        assert!(parse(false).unknown_event_types.is_empty());
+    }
 }
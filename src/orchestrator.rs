@@ -3,27 +3,41 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use crate::aggregate::{BaselineReport, Spend};
 use crate::cache::{CacheKey, CacheManager};
-use crate::evaluator;
+use crate::evaluator::{self, EvalOptions};
 use crate::issue::GitHubIssue;
-use crate::report::{ComparisonReport, ReportFormat, TaskResultRow};
+use crate::profile::Profile;
+use crate::report::{
+    ComparisonReport, ErroredTask, FmmRegressionReport, RepeatSpread, ReportFormat, TaskComparison,
+    TaskOrder, TaskResultRow,
+};
 use crate::runner::{ClaudeRunner, RunResult};
-use crate::sandbox::Sandbox;
+use crate::sandbox::{
+    detect_primary_language, Sandbox, SettingsScope, SettingsTemplate, SidecarGeneration,
+};
 use crate::tasks::{Task, TaskCategory, TaskSet};
+use crate::{out, outln};
 
 /// Options for comparison run
 #[derive(Debug, Clone)]
 pub struct CompareOptions {
     /// Branch to compare (default: main)
     pub branch: Option<String>,
-    /// Path within repo to analyze (default: src/)
+    /// Commit to check out instead of the branch tip (a corpus entry's pin)
+    pub commit: Option<String>,
+    /// Subdirectory to scope sidecar generation and prompts to (default: whole repo)
     pub src_path: Option<String>,
-    /// Task set to use (standard, quick, or custom path)
+    /// Task set to use (auto, a built-in name like standard or rust-navigation, or a custom path)
     pub task_set: String,
     /// Number of runs per task (for averaging)
     pub runs: u32,
+    /// Times each task-set task is run per condition within one comparison
+    /// (`run` only); the repeats are averaged into one task comparison
+    pub task_repeats: u32,
     /// Output directory for results
     pub output: Option<PathBuf>,
     /// Output format
@@ -36,25 +50,173 @@ pub struct CompareOptions {
     pub quick: bool,
     /// Model to use
     pub model: String,
+    /// JSON file of team model aliases consulted before `model` is checked
+    pub model_alias_file: Option<PathBuf>,
+    /// Required commit message format for issue runs (e.g. `fmm-bench: <summary>`)
+    pub commit_message_template: Option<String>,
+    /// Abort when no sidecars could be generated for the FMM condition
+    pub require_sidecars: bool,
+    /// Shuffle task order with this seed (same order for both conditions)
+    pub seed: Option<u64>,
+    /// Leave degenerate tasks (zero-tool non-answers) out of the summary
+    pub exclude_degenerate: bool,
+    /// Leave tasks whose FMM run never used the treatment out of the summary
+    pub require_treatment_used: bool,
+    /// Cap on stream-json bytes parsed per run (`output_too_large` beyond it)
+    pub max_output_bytes: u64,
+    /// Directory to dump each run's raw stream-json into
+    pub export_raw: Option<PathBuf>,
+    /// Directory to save each run's readable transcript into
+    pub keep_claude_logs: Option<PathBuf>,
+    /// Fail on malformed stream-json and warn on unknown event types
+    pub strict_json: bool,
+    /// Append each finished task comparison to this file as one JSON line
+    pub ndjson: Option<PathBuf>,
+    /// Embed each condition's unified diff in the report (issue runs)
+    pub include_diff: bool,
+    /// Extra attempts for a run that errors before doing any work
+    pub task_retries: u32,
+    /// Appended to the control condition's system prompt (e.g. a neutral text
+    /// of the fmm instructions' length, to ablate the extra tokens themselves)
+    pub control_context: Option<String>,
+    /// Extra arguments appended to `fmm generate` (e.g. `--lang ts`)
+    pub sidecar_args: Vec<String>,
+    /// Per-condition tool-call cap; runs over it are aborted
+    pub max_tool_calls: Option<u32>,
+    /// Keep only tasks in these categories (task-set runs)
+    pub category_filter: Option<Vec<TaskCategory>>,
+    /// `fmm` binary for the FMM condition (default: `FMM_BIN`, then PATH)
+    pub fmm_bin: Option<PathBuf>,
+    /// Issue bodies shorter than this after sanitization are flagged (0 = off)
+    pub min_body_chars: usize,
+    /// Skip (rather than warn about) issues with a too-short body
+    pub skip_empty_body: bool,
+    /// Truncate issue bodies to roughly this many estimated tokens
+    pub max_body_tokens: Option<usize>,
+    /// Warn when the issue has none of these labels
+    pub include_labels: Vec<String>,
+    /// Warn when the issue has any of these labels
+    pub exclude_labels: Vec<String>,
+    /// Record per-stage timings in the report
+    pub profile: bool,
+    /// Commands run in both sandboxes before each issue run (from the corpus entry)
+    pub setup_commands: Vec<Vec<String>>,
+    /// Root of a batch's `RepoCache` to clone default branches from
+    pub repo_cache: Option<PathBuf>,
+    /// Result cache directory (`None` = `FMM_BENCH_CACHE_DIR`, else the
+    /// platform cache dir)
+    pub cache_dir: Option<PathBuf>,
+    /// Evaluator steps to skip (`--no-build-check`, `--no-test-check`)
+    pub eval: EvalOptions,
+    /// Wall-clock deadline for the whole job (`--max-wall-secs`); no new
+    /// task or run starts after it
+    pub deadline: Option<Instant>,
+    /// `git clone --depth` for the sandboxes (`None` = full history)
+    pub clone_depth: Option<u32>,
+    /// Kill a sandbox clone that runs longer than this (`--clone-timeout`)
+    pub clone_timeout: Duration,
+    /// Run Claude under permission checks instead of
+    /// `--dangerously-skip-permissions`
+    pub require_permissions: bool,
+    /// Settings merged into the workspaces' local settings before each run
+    pub settings_template: Option<SettingsTemplate>,
+    /// Append the raw files and gists an issue body links to (`--fetch-linked`)
+    pub fetch_linked: bool,
 }
 
 impl Default for CompareOptions {
     fn default() -> Self {
         Self {
             branch: None,
+            commit: None,
             src_path: None,
             task_set: "standard".to_string(),
             runs: 1,
+            task_repeats: 1,
             output: None,
             format: ReportFormat::Both,
             max_budget: 10.0,
             use_cache: true,
             quick: false,
             model: "sonnet".to_string(),
+            model_alias_file: None,
+            commit_message_template: None,
+            require_sidecars: false,
+            seed: None,
+            exclude_degenerate: false,
+            require_treatment_used: false,
+            max_output_bytes: crate::metrics::DEFAULT_MAX_OUTPUT_BYTES,
+            export_raw: None,
+            keep_claude_logs: None,
+            strict_json: false,
+            ndjson: None,
+            include_diff: false,
+            task_retries: 1,
+            control_context: None,
+            sidecar_args: vec![],
+            max_tool_calls: None,
+            category_filter: None,
+            fmm_bin: None,
+            min_body_chars: crate::issue::DEFAULT_MIN_BODY_CHARS,
+            skip_empty_body: false,
+            max_body_tokens: None,
+            include_labels: vec![],
+            exclude_labels: vec![],
+            profile: false,
+            setup_commands: vec![],
+            repo_cache: None,
+            cache_dir: None,
+            eval: EvalOptions::default(),
+            deadline: None,
+            clone_depth: crate::sandbox::DEFAULT_CLONE_DEPTH,
+            clone_timeout: crate::sandbox::DEFAULT_CLONE_TIMEOUT,
+            require_permissions: false,
+            settings_template: None,
+            fetch_linked: false,
         }
     }
 }
 
+impl CompareOptions {
+    /// Reject option values that would produce an empty or meaningless comparison.
+    pub fn validate(&self) -> Result<()> {
+        if self.runs == 0 {
+            anyhow::bail!("runs must be at least 1");
+        }
+        if self.task_repeats == 0 {
+            anyhow::bail!("task repeats must be at least 1");
+        }
+        if !(self.max_budget > 0.0 && self.max_budget.is_finite()) {
+            anyhow::bail!(
+                "max budget must be a positive amount of USD (got {})",
+                self.max_budget
+            );
+        }
+        if self.model.trim().is_empty() {
+            anyhow::bail!("model must not be empty");
+        }
+        if self.max_body_tokens == Some(0) {
+            anyhow::bail!("max body tokens must be at least 1");
+        }
+        if self.clone_timeout.is_zero() {
+            anyhow::bail!("clone timeout must be at least 1 second");
+        }
+        Ok(())
+    }
+}
+
+/// Settings that vary per issue within a batch (from the corpus entry and
+/// the remaining budget); everything else comes from `CompareOptions`.
+#[derive(Debug, Clone, Default)]
+pub struct IssueSettings {
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    pub max_budget: f64,
+    pub setup_commands: Vec<Vec<String>>,
+    /// Task set (built-in name or path) for a `run_next_repo` comparison
+    pub task_set: Option<String>,
+}
+
 /// Orchestrator for comparison runs
 pub struct Orchestrator {
     options: CompareOptions,
@@ -64,37 +226,96 @@ pub struct Orchestrator {
     /// Runner for FMM variant (local settings — picks up skill + MCP from workspace)
     fmm_runner: ClaudeRunner,
     total_cost: f64,
+    /// Stage timings for the current job
+    profile: Profile,
+    /// When the current job started (for the report's `wall_ms`)
+    job_started: Instant,
+    /// What the current job's fresh runs spent (for the report's `spent`)
+    spent: Spend,
+    /// Index of the `task_repeats` repeat in progress (0 outside repeats)
+    repeat: u32,
+    /// The alias-file name `options.model` was expanded from, if any
+    model_alias: Option<String>,
+    /// Hash of `options.fmm_bin`'s contents, taken when it is set rather than
+    /// on every cache lookup
+    fmm_bin_hash: Option<String>,
 }
 
 impl Orchestrator {
     /// Create a new orchestrator
-    pub fn new(options: CompareOptions) -> Result<Self> {
-        let cache = CacheManager::new(None)?;
+    pub fn new(mut options: CompareOptions) -> Result<Self> {
+        options.validate()?;
+        let mut model_alias = None;
+        if let Some(ref path) = options.model_alias_file {
+            let name = options.model.trim().to_string();
+            if let Some(id) = crate::pricing::load_model_aliases(path)?.remove(&name) {
+                options.model = id;
+                model_alias = Some(name);
+            }
+        }
+        let resolved = crate::pricing::resolve_model(&options.model);
+        options.model = match model_alias {
+            Some(ref name) => {
+                resolved.with_context(|| format!("Bad target for model alias '{}'", name))?
+            }
+            None => resolved?,
+        };
+        crate::sandbox::validate_sidecar_args(&options.sidecar_args)?;
+        let cache = CacheManager::new(options.cache_dir.clone())?;
         let mut control_runner = ClaudeRunner::new();
         let mut fmm_runner = ClaudeRunner::with_local_settings();
+        check_tool_parity(control_runner.allowed_tools(), fmm_runner.allowed_tools())?;
 
         control_runner.set_model(&options.model);
         fmm_runner.set_model(&options.model);
+        control_runner.set_max_output_bytes(options.max_output_bytes);
+        fmm_runner.set_max_output_bytes(options.max_output_bytes);
+        control_runner.set_strict_json(options.strict_json);
+        fmm_runner.set_strict_json(options.strict_json);
+        control_runner.set_require_permissions(options.require_permissions);
+        fmm_runner.set_require_permissions(options.require_permissions);
+        if let Some(ref template) = options.settings_template {
+            template.load()?;
+            // Without local settings the control run would ignore its copy
+            control_runner.set_local_settings(template.scope == SettingsScope::Both);
+        }
 
+        let fmm_bin_hash = options.fmm_bin.as_deref().map(fmm_bin_hash);
         Ok(Self {
             options,
             cache,
             control_runner,
             fmm_runner,
             total_cost: 0.0,
+            profile: Profile::default(),
+            job_started: Instant::now(),
+            spent: Spend::default(),
+            repeat: 0,
+            model_alias,
+            fmm_bin_hash,
         })
     }
 
+    /// Set the fmm binary used for sidecar generation, hashing it once for
+    /// the cache key.
+    fn set_fmm_bin(&mut self, bin: Option<PathBuf>) {
+        self.fmm_bin_hash = bin.as_deref().map(fmm_bin_hash);
+        self.options.fmm_bin = bin;
+    }
+
     /// Run comparison on a repository
     pub fn run(&mut self, url: &str) -> Result<ComparisonReport> {
         let job_id = generate_job_id();
+        self.start_job(&job_id);
 
-        println!("{} Job ID: {}", "📋".yellow(), job_id.cyan());
+        outln!("{} Job ID: {}", "📋".yellow(), job_id.cyan());
 
         // Step 1: Create sandbox and clone repo
-        println!("{} Setting up sandbox...", "🔧".yellow());
-        let sandbox = Sandbox::new(&job_id)?;
-        sandbox.clone_repo(url, self.options.branch.as_deref())?;
+        outln!("{} Setting up sandbox...", "🔧".yellow());
+        let started = Instant::now();
+        let sandbox = self.new_sandbox(&job_id)?;
+        self.clone_repo(&sandbox, url)?;
+        self.profile.record("clone", started.elapsed());
 
         let commit_sha = sandbox.get_commit_sha(&sandbox.control_dir)?;
         let sha_display = if commit_sha.len() >= 8 {
@@ -102,53 +323,81 @@ impl Orchestrator {
         } else {
             &commit_sha
         };
-        println!(
+        outln!(
             "  {} Cloned at commit {}",
             "✓".green(),
             sha_display.dimmed()
         );
 
-        // Step 2: Generate FMM sidecars + install skill + MCP for FMM variant
-        println!("{} Setting up FMM variant...", "🔧".yellow());
-        sandbox.generate_fmm_sidecars()?;
+        let src_path = self.options.src_path.as_deref();
+        if let Some(sub) = src_path {
+            sandbox.validate_src_path(sub)?;
+            outln!("  {} Scoped to {}", "✓".green(), sub.cyan());
+        }
 
-        let sidecar_count = walkdir::WalkDir::new(&sandbox.fmm_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("fmm"))
-            .count();
-        if sidecar_count > 0 {
-            println!(
+        // Step 2: Generate FMM sidecars + install skill + MCP for FMM variant
+        outln!("{} Setting up FMM variant...", "🔧".yellow());
+        let started = Instant::now();
+        let mut sidecars = sandbox.generate_fmm_sidecars(src_path)?;
+        self.profile.record("sidecar-gen", started.elapsed());
+        if sidecars.sidecar_count > 0 {
+            outln!(
                 "  {} {} sidecar files generated",
                 "✓".green(),
-                sidecar_count
+                sidecars.sidecar_count
             );
         } else {
-            println!(
-                "  {} No sidecars generated (unsupported language?)",
-                "!".yellow()
-            );
+            self.warn_no_sidecars(&sidecars)?;
         }
 
         // Install skill file + .mcp.json so Claude picks them up via --setting-sources local
-        sandbox.setup_fmm_integration()?;
-        println!(
-            "  {} Installed skill + MCP config (Exp15-proven delivery)",
-            "✓".green()
-        );
+        let started = Instant::now();
+        sidecars.missing_artifacts = sandbox.setup_fmm_integration()?;
+        self.profile.record("fmm-init", started.elapsed());
+        if sidecars.missing_artifacts.is_empty() {
+            outln!(
+                "  {} Installed skill + MCP config (Exp15-proven delivery)",
+                "✓".green()
+            );
+        } else {
+            self.warn_partial_init(&sidecars.missing_artifacts)?;
+        }
 
         // Step 3: Load tasks
-        let task_set = if self.options.quick {
-            TaskSet::quick()
-        } else {
-            match self.options.task_set.as_str() {
-                "standard" => TaskSet::standard(),
-                "quick" => TaskSet::quick(),
-                path => self.load_custom_tasks(path)?,
-            }
+        let task_dir = match src_path {
+            Some(sub) => sandbox.control_dir.join(sub),
+            None => sandbox.control_dir.clone(),
         };
+        let mut task_set = self.resolve_task_set(&task_dir)?;
+        if let Some(ref categories) = self.options.category_filter {
+            task_set.retain_categories(categories);
+            if task_set.tasks.is_empty() {
+                let names: Vec<String> = categories.iter().map(|c| c.to_string()).collect();
+                anyhow::bail!(
+                    "Task set '{}' has no tasks in categories: {}",
+                    task_set.name,
+                    names.join(", ")
+                );
+            }
+        }
+        if let Some(sub) = src_path {
+            task_set.tasks = task_set
+                .tasks
+                .into_iter()
+                .map(|t| t.scoped_to(sub))
+                .collect();
+        }
+        if let Some(cap) = self.options.max_tool_calls {
+            for task in &mut task_set.tasks {
+                task.max_tool_calls = Some(cap);
+            }
+        }
+        if let Some(seed) = self.options.seed {
+            task_set.shuffle(seed);
+            outln!("  {} Shuffled task order (seed {})", "✓".green(), seed);
+        }
 
-        println!(
+        outln!(
             "{} Running {} tasks...",
             "🚀".yellow(),
             task_set.tasks.len()
@@ -156,9 +405,18 @@ impl Orchestrator {
 
         // Step 4: Run tasks
         let mut results: Vec<TaskResultRow> = vec![];
+        let mut repeat_spreads: Vec<(String, RepeatSpread)> = vec![];
+        let mut fmm_context_bytes = 0;
+        let mut deadline_truncated = false;
 
         for (i, task) in task_set.tasks.iter().enumerate() {
-            println!(
+            // Before the header, so a task that never runs isn't announced
+            if self.deadline_reached() {
+                deadline_truncated = true;
+                break;
+            }
+
+            outln!(
                 "\n{} Task {}/{}: {}",
                 "▶".cyan(),
                 i + 1,
@@ -168,7 +426,7 @@ impl Orchestrator {
 
             // Check budget
             if self.total_cost >= self.options.max_budget {
-                println!(
+                outln!(
                     "{} Budget limit reached (${:.2} / ${:.2})",
                     "⚠".yellow(),
                     self.total_cost,
@@ -177,23 +435,42 @@ impl Orchestrator {
                 break;
             }
 
-            // Run control variant
-            let control_result =
-                self.run_task_with_cache(task, &sandbox.control_dir, "control", url, &commit_sha)?;
-
-            // Run FMM variant
-            let fmm_context = self.build_fmm_context(&sandbox.fmm_dir)?;
-            let fmm_result = self.run_task_with_fmm(
-                task,
-                &sandbox.fmm_dir,
-                "fmm",
-                url,
-                &commit_sha,
-                &fmm_context,
+            let started = Instant::now();
+            run_setup(
+                &task.setup_commands,
+                &[&sandbox.control_dir, &sandbox.fmm_dir],
             )?;
-
-            // Update cost tracking
-            self.total_cost += control_result.total_cost_usd + fmm_result.total_cost_usd;
+            self.profile.record("setup", started.elapsed());
+
+            let (control_result, fmm_result, spread) = self.repeat_task(|this| {
+                // Run control variant
+                let control_result = this.run_task_with_cache(
+                    task,
+                    &sandbox.control_dir,
+                    "control",
+                    url,
+                    &commit_sha,
+                )?;
+
+                // Run FMM variant
+                let fmm_context = this.build_fmm_context(&sandbox.fmm_dir)?;
+                fmm_context_bytes = fmm_context.len();
+                let fmm_result = this.run_task_with_fmm(
+                    task,
+                    &sandbox.fmm_dir,
+                    "fmm",
+                    url,
+                    &commit_sha,
+                    &fmm_context,
+                )?;
+
+                // Update cost tracking
+                this.total_cost += control_result.total_cost_usd + fmm_result.total_cost_usd;
+                Ok((control_result, fmm_result))
+            })?;
+            if let Some(spread) = spread {
+                repeat_spreads.push((task.id.clone(), spread));
+            }
 
             // Report progress
             let reduction = if control_result.tool_calls > 0 {
@@ -204,40 +481,84 @@ impl Orchestrator {
                 0.0
             };
 
-            println!(
+            outln!(
                 "  Control: {} tools | FMM: {} tools | Reduction: {:.1}%",
-                control_result.tool_calls, fmm_result.tool_calls, reduction
+                control_result.tool_calls,
+                fmm_result.tool_calls,
+                reduction
             );
 
-            results.push((task.clone(), control_result, fmm_result, None, None));
+            let row = (task.clone(), control_result, fmm_result, None, None);
+            self.stream_ndjson(&row)?;
+            results.push(row);
         }
 
         // Step 5: Generate report
-        println!("\n{} Generating report...", "📊".yellow());
+        outln!("\n{} Generating report...", "📊".yellow());
         let branch = self
             .options
             .branch
             .clone()
             .unwrap_or_else(|| "main".to_string());
-        let report = ComparisonReport::new(job_id, url.to_string(), commit_sha, branch, results);
+        let (results, errored) = split_errored(results);
+        let mut report =
+            ComparisonReport::new(job_id, url.to_string(), commit_sha, branch, results);
+        if !errored.is_empty() {
+            outln!(
+                "{} {} task(s) errored and were excluded from the summary",
+                "⚠".yellow(),
+                errored.len()
+            );
+        }
+        report.errored_tasks = errored;
+        for (task_id, spread) in repeat_spreads {
+            if let Some(t) = report
+                .task_results
+                .iter_mut()
+                .find(|t| t.task_id == task_id)
+            {
+                t.repeats = Some(spread);
+            }
+        }
+        report.fmm_setup = Some(sidecars);
+        report.settings_template = self.options.settings_template.clone();
+        report.model = self.options.model.clone();
+        report.model_alias = self.model_alias.clone();
+        report.deadline_truncated = deadline_truncated;
+        report.wall_ms = self.job_started.elapsed().as_millis() as u64;
+        report.spent = Some(self.spent);
+        report.control_allowed_tools = self.control_runner.allowed_tools().to_vec();
+        report.fmm_allowed_tools = self.fmm_runner.allowed_tools().to_vec();
+        if self.options.profile {
+            report.profile = Some(self.profile.clone());
+        }
+        report.control_context_bytes = self.control_context().map_or(0, str::len);
+        report.fmm_context_bytes = fmm_context_bytes;
+        report.task_order = self.options.seed.map(|seed| TaskOrder {
+            seed,
+            task_ids: task_set.tasks.iter().map(|t| t.id.clone()).collect(),
+        });
+        warn_prompt_mismatch(&report);
+        self.flag_degenerate(&mut report);
+        self.flag_unused_treatment(&mut report);
 
         // Save report
         if let Some(ref output_dir) = self.options.output {
             let saved = report.save(output_dir, self.options.format)?;
             for path in saved {
-                println!("  {} Saved: {}", "✓".green(), path.dimmed());
+                outln!("  {} Saved: {}", "✓".green(), path.dimmed());
             }
         }
 
         // Also save to cache
         let report_path = self.cache.save_report(&report)?;
-        println!(
+        outln!(
             "  {} Cached: {}",
             "✓".green(),
             report_path.display().to_string().dimmed()
         );
 
-        println!("\n{} Total cost: ${:.4}", "💰".yellow(), self.total_cost);
+        outln!("\n{} Total cost: ${:.4}", "💰".yellow(), self.total_cost);
 
         Ok(report)
     }
@@ -247,69 +568,92 @@ impl Orchestrator {
     /// Clones the repo, sets up control + fmm sandboxes, runs the issue prompt
     /// against both, and compares results.
     pub fn run_issue(&mut self, issue: &GitHubIssue) -> Result<ComparisonReport> {
+        self.check_issue_body(issue)?;
+        let mut issue = self.limit_issue_body(issue);
+        if self.options.fetch_linked {
+            self.append_linked_content(&mut issue);
+        }
+        let issue = &issue;
+        if let Some(reason) =
+            issue.label_filter_reason(&self.options.include_labels, &self.options.exclude_labels)
+        {
+            eprintln!(
+                "{} {}: {}",
+                "Warning:".yellow(),
+                issue.issue_ref.short_id(),
+                reason
+            );
+        }
         let job_id = generate_job_id();
+        self.start_job(&job_id);
         let url = &issue.issue_ref.clone_url();
         let issue_label = issue.issue_ref.short_id();
 
-        println!(
+        outln!(
             "{} Issue: {} — {}",
             ">>".yellow(),
             issue_label.cyan().bold(),
             issue.title.white()
         );
-        println!("{} Job ID: {}", ">>".yellow(), job_id.cyan());
+        outln!("{} Job ID: {}", ">>".yellow(), job_id.cyan());
 
         // Step 1: Create sandbox and clone repo
-        println!("{} Setting up sandbox...", ">>".yellow());
-        let sandbox = Sandbox::new(&job_id)?;
-        sandbox.clone_repo(url, self.options.branch.as_deref())?;
+        outln!("{} Setting up sandbox...", ">>".yellow());
+        let started = Instant::now();
+        let sandbox = self.new_sandbox(&job_id)?;
+        self.clone_repo(&sandbox, url)?;
+        self.profile.record("clone", started.elapsed());
 
         let commit_sha = sandbox.get_commit_sha(&sandbox.control_dir)?;
         let sha_short = &commit_sha[..commit_sha.len().min(8)];
-        println!("  {} Cloned at commit {}", "+".green(), sha_short.dimmed());
+        outln!("  {} Cloned at commit {}", "+".green(), sha_short.dimmed());
 
-        // Step 2: Generate FMM sidecars + init for FMM variant
-        println!("{} Setting up FMM variant...", ">>".yellow());
-        sandbox.generate_fmm_sidecars()?;
+        let src_path = self.options.src_path.clone();
+        if let Some(ref sub) = src_path {
+            sandbox.validate_src_path(sub)?;
+            outln!("  {} Scoped to {}", "+".green(), sub.cyan());
+        }
 
-        let sidecar_count = walkdir::WalkDir::new(&sandbox.fmm_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("fmm"))
-            .count();
-        if sidecar_count > 0 {
-            println!(
+        // Step 2: Generate FMM sidecars + init for FMM variant
+        outln!("{} Setting up FMM variant...", ">>".yellow());
+        let started = Instant::now();
+        let mut sidecars = sandbox.generate_fmm_sidecars(src_path.as_deref())?;
+        self.profile.record("sidecar-gen", started.elapsed());
+        if sidecars.sidecar_count > 0 {
+            outln!(
                 "  {} {} sidecar files generated",
                 "+".green(),
-                sidecar_count
+                sidecars.sidecar_count
             );
         } else {
-            println!(
-                "  {} No sidecars generated (unsupported language?)",
-                "!".yellow()
-            );
+            self.warn_no_sidecars(&sidecars)?;
         }
 
-        sandbox.setup_fmm_integration()?;
-        println!("  {} Installed CLAUDE.md + MCP config", "+".green());
+        let started = Instant::now();
+        sidecars.missing_artifacts = sandbox.setup_fmm_integration()?;
+        self.profile.record("fmm-init", started.elapsed());
+        if sidecars.missing_artifacts.is_empty() {
+            outln!("  {} Installed CLAUDE.md + MCP config", "+".green());
+        } else {
+            self.warn_partial_init(&sidecars.missing_artifacts)?;
+        }
 
         // Step 3: Build task from issue prompt
-        let task = Task {
-            id: format!("issue-{}", issue.issue_ref.number),
-            name: issue.title.clone(),
-            prompt: issue.to_prompt(),
-            category: TaskCategory::Exploration,
-            expected_patterns: vec![],
-            max_turns: 50,
-            max_budget_usd: self.options.max_budget,
-        };
+        let task = self.issue_task(issue);
 
         // Step 4: Run N times
         let mut all_results: Vec<TaskResultRow> = vec![];
+        let mut fmm_context_bytes = 0;
+        let mut deadline_truncated = false;
 
         for run_idx in 0..self.options.runs {
+            if self.deadline_reached() {
+                deadline_truncated = true;
+                break;
+            }
+
             if self.options.runs > 1 {
-                println!(
+                outln!(
                     "\n{} Run {}/{}",
                     ">>".yellow(),
                     run_idx + 1,
@@ -318,8 +662,8 @@ impl Orchestrator {
             }
 
             // Check budget
-            if self.total_cost >= self.options.max_budget * 2.0 * self.options.runs as f64 {
-                println!(
+            if self.issue_budget_reached() {
+                outln!(
                     "{} Budget limit reached (${:.2})",
                     "!".yellow(),
                     self.total_cost
@@ -327,12 +671,20 @@ impl Orchestrator {
                 break;
             }
 
+            let started = Instant::now();
+            run_setup(
+                &task.setup_commands,
+                &[&sandbox.control_dir, &sandbox.fmm_dir],
+            )?;
+            self.profile.record("setup", started.elapsed());
+
             // Run control
             let control_result =
                 self.run_task_with_cache(&task, &sandbox.control_dir, "control", url, &commit_sha)?;
 
             // Run FMM
             let fmm_context = self.build_fmm_context(&sandbox.fmm_dir)?;
+            fmm_context_bytes = fmm_context.len();
             let fmm_result = self.run_task_with_fmm(
                 &task,
                 &sandbox.fmm_dir,
@@ -352,7 +704,7 @@ impl Orchestrator {
                 0.0
             };
 
-            println!(
+            outln!(
                 "  Control: {} tools, ${:.4} | FMM: {} tools, ${:.4} | Reduction: {:.1}%",
                 control_result.tool_calls,
                 control_result.total_cost_usd,
@@ -362,12 +714,29 @@ impl Orchestrator {
             );
 
             // Post-run evaluation
-            println!("  {} Evaluating...", ">>".yellow());
-            let control_eval = evaluator::evaluate(&sandbox.control_dir).ok();
-            let fmm_eval = evaluator::evaluate(&sandbox.fmm_dir).ok();
+            outln!("  {} Evaluating...", ">>".yellow());
+            let started = Instant::now();
+            let mut control_eval =
+                evaluator::evaluate_since(&sandbox.control_dir, &commit_sha, self.options.eval)
+                    .ok();
+            let mut fmm_eval =
+                evaluator::evaluate_since(&sandbox.fmm_dir, &commit_sha, self.options.eval).ok();
+            if self.options.include_diff {
+                for (eval, dir) in [
+                    (&mut control_eval, &sandbox.control_dir),
+                    (&mut fmm_eval, &sandbox.fmm_dir),
+                ] {
+                    if let Some(eval) = eval {
+                        eval.diff =
+                            evaluator::capture_diff(dir, &commit_sha, evaluator::MAX_DIFF_BYTES)
+                                .ok();
+                    }
+                }
+            }
+            self.profile.record("eval", started.elapsed());
 
             if let (Some(ce), Some(fe)) = (&control_eval, &fmm_eval) {
-                println!(
+                outln!(
                     "  Control: grade {} (+{}/-{}) | FMM: grade {} (+{}/-{})",
                     ce.grade,
                     ce.diff_lines_added,
@@ -378,53 +747,288 @@ impl Orchestrator {
                 );
             }
 
-            all_results.push((
+            let row = (
                 task.clone(),
                 control_result,
                 fmm_result,
                 control_eval,
                 fmm_eval,
-            ));
+            );
+            self.stream_ndjson(&row)?;
+            all_results.push(row);
 
-            // Reset sandbox git state between runs so each starts fresh.
-            // Must re-setup FMM after reset because git clean -fd removes
-            // untracked files (sidecars, .claude/, .mcp.json).
+            // Reset sandbox git state between runs so each starts fresh, with
+            // the fmm treatment re-established for the next run.
             if run_idx + 1 < self.options.runs {
-                sandbox.reset_git_state()?;
-                sandbox.generate_fmm_sidecars()?;
-                sandbox.setup_fmm_integration()?;
+                let started = Instant::now();
+                let regenerated = sandbox.reset_for_next_run(&commit_sha, src_path.as_deref())?;
+                self.profile.record("reset", started.elapsed());
+                if regenerated.sidecar_count == 0 {
+                    self.warn_no_sidecars(&regenerated)?;
+                }
+                if !regenerated.missing_artifacts.is_empty() {
+                    self.warn_partial_init(&regenerated.missing_artifacts)?;
+                }
             }
         }
 
         // Step 5: Generate report
-        println!("\n{} Generating report...", ">>".yellow());
+        outln!("\n{} Generating report...", ">>".yellow());
         let branch = self
             .options
             .branch
             .clone()
             .unwrap_or_else(|| "main".to_string());
-        let report =
-            ComparisonReport::new(job_id, url.to_string(), commit_sha, branch, all_results);
+        let mut report = issue_report(issue, job_id, commit_sha, branch, all_results);
+        report.fmm_setup = Some(sidecars);
+        report.settings_template = self.options.settings_template.clone();
+        report.model = self.options.model.clone();
+        report.model_alias = self.model_alias.clone();
+        report.deadline_truncated = deadline_truncated;
+        report.wall_ms = self.job_started.elapsed().as_millis() as u64;
+        report.spent = Some(self.spent);
+        report.control_allowed_tools = self.control_runner.allowed_tools().to_vec();
+        report.fmm_allowed_tools = self.fmm_runner.allowed_tools().to_vec();
+        if self.options.profile {
+            report.profile = Some(self.profile.clone());
+        }
+        report.control_context_bytes = self.control_context().map_or(0, str::len);
+        report.fmm_context_bytes = fmm_context_bytes;
+        warn_prompt_mismatch(&report);
+        self.flag_degenerate(&mut report);
+        self.flag_unused_treatment(&mut report);
 
         if let Some(ref output_dir) = self.options.output {
             let saved = report.save(output_dir, self.options.format)?;
             for path in saved {
-                println!("  {} Saved: {}", "+".green(), path.dimmed());
+                outln!("  {} Saved: {}", "+".green(), path.dimmed());
             }
         }
 
         let report_path = self.cache.save_report(&report)?;
-        println!(
+        outln!(
             "  {} Cached: {}",
             "+".green(),
             report_path.display().to_string().dimmed()
         );
 
-        println!("\n{} Total cost: ${:.4}", ">>".yellow(), self.total_cost);
+        outln!("\n{} Total cost: ${:.4}", ">>".yellow(), self.total_cost);
+
+        Ok(report)
+    }
+
+    /// The result cache, whose directory also holds batch failure markers.
+    pub fn cache(&self) -> &CacheManager {
+        &self.cache
+    }
+
+    /// Run another issue on this orchestrator, as `run_batch` does for each
+    /// corpus entry. Only per-issue state is reset (settings and spend); the
+    /// runners and the in-memory result cache carry over between issues.
+    pub fn run_next_issue(
+        &mut self,
+        issue: &GitHubIssue,
+        settings: IssueSettings,
+    ) -> Result<ComparisonReport> {
+        self.begin_issue(settings);
+        self.run_issue(issue)
+    }
+
+    /// Run a task-set comparison on another repo, as `run_batch` does for
+    /// corpus entries with a `task_set`. Per-issue state is reset as for
+    /// `run_next_issue`.
+    pub fn run_next_repo(
+        &mut self,
+        url: &str,
+        settings: IssueSettings,
+    ) -> Result<ComparisonReport> {
+        self.begin_issue(settings);
+        self.run(url)
+    }
+
+    /// Whether the `--max-wall-secs` deadline has passed (announced once per
+    /// check, since callers stop right after).
+    fn deadline_reached(&self) -> bool {
+        let reached = deadline_passed(self.options.deadline);
+        if reached {
+            outln!(
+                "{} Wall-clock deadline reached, stopping with partial results",
+                "!".yellow()
+            );
+        }
+        reached
+    }
+
+    /// Clone `url` into both of the sandbox's workspaces, at the pinned
+    /// commit when there is one.
+    fn clone_repo(&self, sandbox: &Sandbox, url: &str) -> Result<()> {
+        let branch = self.options.branch.as_deref();
+        match self.options.commit {
+            Some(ref commit) => sandbox.clone_repo_at_commit(url, commit, branch),
+            None => sandbox.clone_repo(url, branch),
+        }
+    }
+
+    /// Whether `run_issue` has spent its budget: both conditions, every run.
+    fn issue_budget_reached(&self) -> bool {
+        self.total_cost >= self.options.max_budget * 2.0 * self.options.runs as f64
+    }
+
+    fn begin_issue(&mut self, settings: IssueSettings) {
+        self.options.branch = settings.branch;
+        self.options.commit = settings.commit;
+        self.options.max_budget = settings.max_budget;
+        self.options.setup_commands = settings.setup_commands;
+        if let Some(task_set) = settings.task_set {
+            self.options.task_set = task_set;
+        }
+        self.total_cost = 0.0;
+    }
+
+    /// Run an issue once per fmm build (`bin_a`, then `bin_b`) to see
+    /// whether a change to fmm improved the benchmark. Everything but the
+    /// fmm binary is held fixed; the control condition may come from cache.
+    pub fn run_fmm_regression(
+        &mut self,
+        issue: &GitHubIssue,
+        bin_a: &Path,
+        bin_b: &Path,
+    ) -> Result<FmmRegressionReport> {
+        let mut reports = self.for_each_fmm_bin([bin_a, bin_b], |o| o.run_issue(issue))?;
+        let b = reports.pop().expect("two reports");
+        let a = reports.pop().expect("two reports");
+        Ok(FmmRegressionReport::new(bin_a, bin_b, a, b))
+    }
+
+    /// Call `run` once per binary with `fmm_bin` set to it, restoring the
+    /// configured binary afterwards. Each binary gets the full budget, so
+    /// `bin_b` isn't cut short by what `bin_a` spent.
+    fn for_each_fmm_bin<T>(
+        &mut self,
+        bins: [&Path; 2],
+        mut run: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let configured = self.options.fmm_bin.take();
+        let mut results = vec![];
+        let mut outcome = Ok(());
+        for bin in bins {
+            outln!("\n{} fmm binary: {}", ">>".yellow(), bin.display());
+            self.set_fmm_bin(Some(bin.to_path_buf()));
+            self.total_cost = 0.0;
+            match run(self) {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
+                }
+            }
+        }
+        self.set_fmm_bin(configured);
+        outcome.map(|()| results)
+    }
+
+    /// Run the control condition `runs` times on an issue to measure its
+    /// intrinsic run-to-run variance. Results bypass the cache, since repeated
+    /// cache hits would show zero variance.
+    pub fn run_baselines(&mut self, issue: &GitHubIssue, runs: u32) -> Result<BaselineReport> {
+        let job_id = generate_job_id();
+        self.start_job(&job_id);
+        let url = &issue.issue_ref.clone_url();
+
+        outln!(
+            "{} Baseline: {} — {} ({} control runs)",
+            ">>".yellow(),
+            issue.issue_ref.short_id().cyan().bold(),
+            issue.title.white(),
+            runs
+        );
+
+        outln!("{} Setting up sandbox...", ">>".yellow());
+        let sandbox = Sandbox::new(&job_id)?;
+        sandbox.clone_control(url, self.options.branch.as_deref())?;
+        if let Some(ref sub) = self.options.src_path {
+            sandbox.validate_src_path(sub)?;
+        }
+        let commit_sha = sandbox.get_commit_sha(&sandbox.control_dir)?;
+
+        let task = self.issue_task(issue);
+        let mut results = vec![];
+
+        for run_idx in 0..runs {
+            if self.total_cost >= self.options.max_budget * runs as f64 {
+                outln!(
+                    "{} Budget limit reached (${:.2})",
+                    "!".yellow(),
+                    self.total_cost
+                );
+                break;
+            }
+
+            out!("  {} control {}/{}...", "●".cyan(), run_idx + 1, runs);
+            let result = self.control_runner.run_task(
+                &task,
+                &sandbox.control_dir,
+                "control",
+                self.control_context(),
+            )?;
+            outln!(
+                " {} ({} tools, ${:.4})",
+                if result.success {
+                    "✓".green()
+                } else {
+                    "✗".red()
+                },
+                result.tool_calls,
+                result.total_cost_usd
+            );
+            self.total_cost += result.total_cost_usd;
+            results.push(result);
+
+            if run_idx + 1 < runs {
+                sandbox.reset_git_state(&commit_sha)?;
+            }
+        }
 
+        let report = BaselineReport::from_runs(&task.id, &results);
+        if report.errored > 0 {
+            outln!(
+                "{} {} run(s) errored before doing any work; left out of the spread",
+                "!".yellow(),
+                report.errored
+            );
+        }
         Ok(report)
     }
 
+    /// Build the benchmark task for an issue (prompt, budget, scoping).
+    fn issue_task(&self, issue: &GitHubIssue) -> Task {
+        let task = Task {
+            id: format!("issue-{}", issue.issue_ref.number),
+            name: issue.title.clone(),
+            prompt: issue
+                .to_prompt_with_commit_template(self.options.commit_message_template.as_deref()),
+            category: TaskCategory::Exploration,
+            expected_patterns: vec![],
+            max_turns: 50,
+            max_budget_usd: self.options.max_budget,
+            max_tool_calls: self.options.max_tool_calls,
+            setup_commands: self.options.setup_commands.clone(),
+        };
+        match self.options.src_path {
+            Some(ref sub) => task.scoped_to(sub),
+            None => task,
+        }
+    }
+
+    /// Append a finished task to the `--ndjson` stream, if one is configured.
+    fn stream_ndjson(&self, row: &TaskResultRow) -> Result<()> {
+        let Some(path) = &self.options.ndjson else {
+            return Ok(());
+        };
+        let (task, control, fmm, control_eval, fmm_eval) = row.clone();
+        TaskComparison::new(task, control, fmm, control_eval, fmm_eval).append_ndjson(path)
+    }
+
     fn run_task_with_cache(
         &mut self,
         task: &Task,
@@ -433,28 +1037,39 @@ impl Orchestrator {
         repo_url: &str,
         commit_sha: &str,
     ) -> Result<RunResult> {
+        let cache_variant = self.control_cache_variant(task, variant)?;
+
         // Check cache
         if self.options.use_cache {
-            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, variant);
+            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, &cache_variant);
             if let Some(cached) = self.cache.get(&cache_key) {
-                println!("  {} {} (cached)", "●".dimmed(), variant.dimmed());
+                outln!("  {} {} (cached)", "●".dimmed(), variant.dimmed());
                 return Ok(cached);
             }
         }
 
         // Run task (control runner: fully isolated, no skill/MCP)
-        print!("  {} {}...", "●".cyan(), variant);
-        let result = self
-            .control_runner
-            .run_task(task, working_dir, variant, None)?;
+        out!("  {} {}...", "●".cyan(), variant);
+        let started = Instant::now();
+        let result = run_with_retries(
+            &self.control_runner,
+            self.options.task_retries,
+            task,
+            working_dir,
+            variant,
+            self.control_context(),
+        )?;
+        self.profile
+            .record(&format!("run:{}", variant), started.elapsed());
+        self.spent.add_run(&result);
 
         // Cache result
         if self.options.use_cache && result.success {
-            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, variant);
+            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, &cache_variant);
             self.cache.set(cache_key, result.clone())?;
         }
 
-        println!(
+        outln!(
             " {} ({} tools, ${:.4})",
             if result.success {
                 "✓".green()
@@ -477,33 +1092,44 @@ impl Orchestrator {
         commit_sha: &str,
         fmm_context: &str,
     ) -> Result<RunResult> {
+        let cache_variant = self.fmm_cache_variant(task, variant)?;
+
         // Check cache
         if self.options.use_cache {
-            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, variant);
+            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, &cache_variant);
             if let Some(cached) = self.cache.get(&cache_key) {
-                println!("  {} {} (cached)", "●".dimmed(), variant.dimmed());
+                outln!("  {} {} (cached)", "●".dimmed(), variant.dimmed());
                 return Ok(cached);
             }
         }
 
         // Run task (FMM runner: local settings enabled — picks up skill + MCP)
-        print!("  {} {}...", "●".cyan(), variant);
+        out!("  {} {}...", "●".cyan(), variant);
         let context = if fmm_context.is_empty() {
             None
         } else {
             Some(fmm_context)
         };
-        let result = self
-            .fmm_runner
-            .run_task(task, working_dir, variant, context)?;
+        let started = Instant::now();
+        let result = run_with_retries(
+            &self.fmm_runner,
+            self.options.task_retries,
+            task,
+            working_dir,
+            variant,
+            context,
+        )?;
+        self.profile
+            .record(&format!("run:{}", variant), started.elapsed());
+        self.spent.add_run(&result);
 
         // Cache result
         if self.options.use_cache && result.success {
-            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, variant);
+            let cache_key = CacheKey::new(repo_url, commit_sha, &task.id, &cache_variant);
             self.cache.set(cache_key, result.clone())?;
         }
 
-        println!(
+        outln!(
             " {} ({} tools, ${:.4})",
             if result.success {
                 "✓".green()
@@ -517,6 +1143,308 @@ impl Orchestrator {
         Ok(result)
     }
 
+    /// Warn about degenerate tasks and drop them from the summary when
+    /// `exclude_degenerate` is set.
+    fn flag_degenerate(&self, report: &mut ComparisonReport) {
+        let degenerate: Vec<&str> = report
+            .task_results
+            .iter()
+            .filter(|t| t.degenerate)
+            .map(|t| t.task_id.as_str())
+            .collect();
+        if degenerate.is_empty() {
+            return;
+        }
+        outln!(
+            "  {} Degenerate result (no tool calls, no answer) for: {}",
+            "!".yellow(),
+            degenerate.join(", ")
+        );
+        if self.options.exclude_degenerate {
+            report.exclude_degenerate();
+            outln!(
+                "  {} Excluded {} degenerate task(s) from the summary",
+                "!".yellow(),
+                report.summary.degenerate_excluded
+            );
+        }
+    }
+
+    /// Warn about FMM runs that never used the generated sidecars or fmm's
+    /// MCP tools, and drop them from the summary when
+    /// `require_treatment_used` is set.
+    fn flag_unused_treatment(&self, report: &mut ComparisonReport) {
+        let unused = report.flag_unused_treatment();
+        if unused.is_empty() {
+            return;
+        }
+        outln!(
+            "  {} FMM treatment unused (no sidecar reads or fmm MCP calls) for: {}",
+            "!".yellow(),
+            unused.join(", ")
+        );
+        if self.options.require_treatment_used {
+            report.exclude_unused_treatment();
+            outln!(
+                "  {} Excluded {} task(s) with an unused treatment from the summary",
+                "!".yellow(),
+                report.summary.treatment_unused_excluded
+            );
+        }
+    }
+
+    /// Point raw stream-json exports and transcript logs (if enabled) at the
+    /// new job.
+    fn start_job(&mut self, job_id: &str) {
+        self.profile = Profile::default();
+        self.job_started = Instant::now();
+        self.spent = Spend::default();
+        if let Some(dir) = &self.options.export_raw {
+            self.control_runner.set_export_raw(dir, job_id);
+            self.fmm_runner.set_export_raw(dir, job_id);
+        }
+        if let Some(dir) = &self.options.keep_claude_logs {
+            self.control_runner.set_keep_logs(dir, job_id);
+            self.fmm_runner.set_keep_logs(dir, job_id);
+        }
+    }
+
+    /// Create a sandbox configured from the options (sidecar args, fmm binary,
+    /// repo cache, clone depth, settings template).
+    fn new_sandbox(&self, job_id: &str) -> Result<Sandbox> {
+        let mut sandbox = Sandbox::new(job_id)?;
+        sandbox.set_sidecar_args(&self.options.sidecar_args)?;
+        if let Some(ref bin) = self.options.fmm_bin {
+            sandbox.set_fmm_bin(bin);
+        }
+        if let Some(ref root) = self.options.repo_cache {
+            sandbox.set_repo_cache(root);
+        }
+        sandbox.set_clone_depth(self.options.clone_depth);
+        sandbox.set_clone_timeout(self.options.clone_timeout);
+        if let Some(ref template) = self.options.settings_template {
+            sandbox.set_settings_template(template)?;
+        }
+        Ok(sandbox)
+    }
+
+    /// Warn about an issue too thin to benchmark, or refuse it with
+    /// `--skip-empty-body` (the error carries the reason).
+    fn check_issue_body(&self, issue: &GitHubIssue) -> Result<()> {
+        let Some(reason) = issue.thin_body_reason(self.options.min_body_chars) else {
+            return Ok(());
+        };
+        if self.options.skip_empty_body {
+            anyhow::bail!("Skipped {}: {}", issue.issue_ref.short_id(), reason);
+        }
+        eprintln!(
+            "{} {}: {}; results may be noisy",
+            "Warning:".yellow(),
+            issue.issue_ref.short_id(),
+            reason
+        );
+        Ok(())
+    }
+
+    /// Apply `max_body_tokens`, so both conditions get the same shortened body.
+    fn limit_issue_body(&self, issue: &GitHubIssue) -> GitHubIssue {
+        let mut issue = issue.clone();
+        if let Some(max_tokens) = self.options.max_body_tokens {
+            let original = crate::issue::estimate_tokens(&issue.body);
+            if issue.truncate_body(max_tokens) {
+                outln!(
+                    "{} Issue body truncated from ~{} to ~{} tokens",
+                    ">>".yellow(),
+                    original,
+                    max_tokens
+                );
+            }
+        }
+        issue
+    }
+
+    /// Append the issue's linked files (`--fetch-linked`) to its body, within
+    /// what the prompt size limit leaves room for, warning about links left out.
+    fn append_linked_content(&self, issue: &mut GitHubIssue) {
+        let room =
+            ClaudeRunner::MAX_PROMPT_SIZE.saturating_sub(self.issue_task(issue).prompt.len());
+        let linked = issue.append_linked_content(room, crate::issue::fetch_linked);
+        if !linked.appended.is_empty() {
+            outln!(
+                "{} Appended {} linked file(s) to the issue body",
+                ">>".yellow(),
+                linked.appended.len()
+            );
+        }
+        for (url, reason) in &linked.skipped {
+            eprintln!("{} skipped linked {}: {}", "Warning:".yellow(), url, reason);
+        }
+    }
+
+    /// Cache variant of a control run. An ablation prompt or a changed task
+    /// prompt changes the run, so it gets its own cache entries.
+    fn control_cache_variant(&self, task: &Task, variant: &str) -> Result<String> {
+        let base = match self.control_context() {
+            Some(ctx) => format!("{}-ctx{}", variant, &crate::runner::prompt_hash(ctx)[..8]),
+            None => variant.to_string(),
+        };
+        Ok(base
+            + &tool_cap_suffix(task)
+            + &self.cache_suffix()
+            + &self.settings_cache_suffix(SettingsScope::Both)?
+            + &prompt_suffix(task))
+    }
+
+    /// Cache variant of an FMM run. A specific fmm build, sidecar args, a
+    /// `--src-path` scope or a changed task prompt change the run, so they get
+    /// their own entries.
+    fn fmm_cache_variant(&self, task: &Task, variant: &str) -> Result<String> {
+        let base = match &self.fmm_bin_hash {
+            Some(hash) => format!("{}-bin{}", variant, &hash[..8]),
+            None => variant.to_string(),
+        };
+        Ok(base
+            + &self.sidecar_args_cache_suffix()
+            + &self.src_path_cache_suffix()
+            + &tool_cap_suffix(task)
+            + &self.cache_suffix()
+            + &self.settings_cache_suffix(SettingsScope::Fmm)?
+            + &prompt_suffix(task))
+    }
+
+    /// Cache-variant suffix for permission checks, and for each
+    /// `task_repeats` repeat after the first, which must be a fresh run
+    /// rather than the first one's cached result.
+    fn cache_suffix(&self) -> String {
+        let mut suffix = String::new();
+        // Denied or stalled tools change the outcome
+        if self.options.require_permissions {
+            suffix.push_str("-perm");
+        }
+        if self.repeat > 0 {
+            suffix.push_str(&format!("-rep{}", self.repeat));
+        }
+        suffix
+    }
+
+    /// Cache-variant suffix hashing `--sidecar-args`, which change the
+    /// sidecars the FMM condition reads.
+    fn sidecar_args_cache_suffix(&self) -> String {
+        if self.options.sidecar_args.is_empty() {
+            return String::new();
+        }
+        let args = self.options.sidecar_args.join("\0");
+        format!("-sidecar{}", &crate::runner::prompt_hash(&args)[..8])
+    }
+
+    /// Cache-variant suffix hashing `--src-path`, which limits the sidecars
+    /// `fmm generate` writes. The scoped prompt is keyed by `prompt_suffix`.
+    fn src_path_cache_suffix(&self) -> String {
+        match self.options.src_path {
+            Some(ref sub) => format!("-src{}", &crate::runner::prompt_hash(sub)[..8]),
+            None => String::new(),
+        }
+    }
+
+    /// Cache-variant suffix hashing the settings template's contents. `min_scope`
+    /// is the narrowest scope that installs it for the condition: `Fmm` for the
+    /// FMM condition, `Both` for control.
+    fn settings_cache_suffix(&self, min_scope: SettingsScope) -> Result<String> {
+        let Some(ref template) = self.options.settings_template else {
+            return Ok(String::new());
+        };
+        if min_scope == SettingsScope::Both && template.scope != SettingsScope::Both {
+            return Ok(String::new());
+        }
+        let contents = fs::read_to_string(&template.path).with_context(|| {
+            format!(
+                "Failed to read settings template {}",
+                template.path.display()
+            )
+        })?;
+        Ok(format!(
+            "-settings{}",
+            &crate::runner::prompt_hash(&contents)[..8]
+        ))
+    }
+
+    /// Run one task `task_repeats` times and average the results. The spread
+    /// is only returned when the task was actually repeated.
+    fn repeat_task(
+        &mut self,
+        mut run_once: impl FnMut(&mut Self) -> Result<(RunResult, RunResult)>,
+    ) -> Result<(RunResult, RunResult, Option<RepeatSpread>)> {
+        let repeats = self.options.task_repeats.max(1);
+        let mut control = vec![];
+        let mut fmm = vec![];
+        for repeat in 0..repeats {
+            if repeats > 1 {
+                outln!("  {} repeat {}/{}", "↻".dimmed(), repeat + 1, repeats);
+            }
+            self.repeat = repeat;
+            let ran = run_once(self);
+            self.repeat = 0;
+            let (c, f) = ran?;
+            control.push(c);
+            fmm.push(f);
+        }
+        let spread = (repeats > 1).then(|| RepeatSpread::from_runs(&control, &fmm));
+        Ok((average_runs(control), average_runs(fmm), spread))
+    }
+
+    /// Prominently warn that the FMM condition has no sidecars, and fail when
+    /// `require_sidecars` is set. The warning goes to stderr so `--quiet`
+    /// can't hide a weak treatment.
+    fn warn_no_sidecars(&self, sidecars: &SidecarGeneration) -> Result<()> {
+        eprintln!(
+            "  {} {}",
+            "!!".red().bold(),
+            "No sidecars generated — the FMM condition is running without sidecars"
+                .red()
+                .bold()
+        );
+        if !sidecars.succeeded {
+            eprintln!(
+                "     fmm generate failed after {} attempts",
+                sidecars.attempts
+            );
+        }
+        if let Some(stderr) = sidecars.stderr.last() {
+            eprintln!("     fmm stderr: {}", stderr.dimmed());
+        }
+        if self.options.require_sidecars {
+            anyhow::bail!("No sidecars were generated and --require-sidecars is set");
+        }
+        Ok(())
+    }
+
+    /// `fmm init` succeeded but left out some of its files, so the FMM
+    /// condition runs with a partial treatment. Fatal with `--require-sidecars`.
+    fn warn_partial_init(&self, missing: &[String]) -> Result<()> {
+        eprintln!(
+            "  {} {}",
+            "!!".red().bold(),
+            format!("fmm init did not install: {}", missing.join(", "))
+                .red()
+                .bold()
+        );
+        if self.options.require_sidecars {
+            anyhow::bail!(
+                "fmm init did not install {} and --require-sidecars is set",
+                missing.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// The control condition's appended system prompt, if configured.
+    fn control_context(&self) -> Option<&str> {
+        self.options
+            .control_context
+            .as_deref()
+            .filter(|c| !c.is_empty())
+    }
+
     fn build_fmm_context(&self, fmm_dir: &std::path::Path) -> Result<String> {
         // Check if sidecars exist
         let has_sidecars = walkdir::WalkDir::new(fmm_dir)
@@ -542,16 +1470,265 @@ Only open source files you need to edit."#;
         Ok(context.to_string())
     }
 
-    fn load_custom_tasks(&self, path: &str) -> Result<TaskSet> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to load custom tasks from {}", path))?;
-
-        serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse custom tasks from {}", path))
-    }
+    /// Resolve the configured task set: `auto` (detected from the checkout),
+    /// a built-in name, or a path to a custom file.
+    fn resolve_task_set(&self, repo_dir: &Path) -> Result<TaskSet> {
+        if self.options.quick {
+            return Ok(TaskSet::quick());
+        }
+        if self.options.task_set == "auto" {
+            let language = detect_primary_language(repo_dir);
+            let set = TaskSet::for_language(language.as_deref());
+            outln!(
+                "  {} Detected language: {} (task set: {})",
+                "✓".green(),
+                language.as_deref().unwrap_or("unknown").cyan(),
+                set.name
+            );
+            return Ok(set);
+        }
+        match TaskSet::builtin(&self.options.task_set) {
+            Some(set) => Ok(set),
+            None => self
+                .load_custom_tasks(&self.options.task_set)
+                .with_context(|| {
+                    format!(
+                        "'{}' is not a built-in task set ({})",
+                        self.options.task_set,
+                        TaskSet::BUILTIN_NAMES.join(", ")
+                    )
+                }),
+        }
+    }
+
+    fn load_custom_tasks(&self, path: &str) -> Result<TaskSet> {
+        TaskSet::from_file(path)
+    }
+}
+
+/// Check a custom task file without running it (`tasks validate`): load it as
+/// `--tasks <path>` would and return its task count and every structural
+/// problem found (see `TaskSet::problems`). Fails if the file can't be loaded.
+pub fn validate_custom_tasks(path: &str) -> Result<(usize, Vec<String>)> {
+    let set = TaskSet::from_file(path)?;
+    let problems = set.problems(ClaudeRunner::MAX_PROMPT_SIZE);
+    Ok((set.tasks.len(), problems))
+}
+
+/// Build the report for an issue run, carrying the issue's labels so results
+/// can be sliced by label in aggregate.
+fn issue_report(
+    issue: &GitHubIssue,
+    job_id: String,
+    commit_sha: String,
+    branch: String,
+    results: Vec<TaskResultRow>,
+) -> ComparisonReport {
+    let mut report = ComparisonReport::new(
+        job_id,
+        issue.issue_ref.clone_url(),
+        commit_sha,
+        branch,
+        results,
+    );
+    report.labels = issue.labels.clone();
+    report.issue_body_tokens = Some(crate::issue::estimate_tokens(&issue.body));
+    report
+}
+
+/// Warn about tasks whose conditions received different prompts (recorded
+/// as `prompt_mismatch` on the report), naming both prompt hashes.
+fn warn_prompt_mismatch(report: &ComparisonReport) {
+    for t in report.task_results.iter().filter(|t| t.prompt_mismatch) {
+        eprintln!(
+            "{} control and FMM prompts differ for task {} ({} vs {})",
+            "Warning:".yellow(),
+            t.task_id,
+            t.control.prompt_hash,
+            t.fmm.prompt_hash
+        );
+    }
+}
+
+/// Both conditions must allow the same base tools, or tool-call counts aren't
+/// comparable. FMM's MCP tools (`mcp__*`) are the intended difference and
+/// are ignored.
+fn check_tool_parity(control: &[String], fmm: &[String]) -> Result<()> {
+    let base = |tools: &[String]| -> Vec<String> {
+        let mut base: Vec<String> = tools
+            .iter()
+            .filter(|t| !t.starts_with("mcp__"))
+            .cloned()
+            .collect();
+        base.sort();
+        base.dedup();
+        base
+    };
+    let (control, fmm) = (base(control), base(fmm));
+    if control != fmm {
+        let only = |a: &[String], b: &[String]| -> String {
+            let diff: Vec<&str> = a
+                .iter()
+                .filter(|t| !b.contains(t))
+                .map(String::as_str)
+                .collect();
+            if diff.is_empty() {
+                "none".to_string()
+            } else {
+                diff.join(", ")
+            }
+        };
+        anyhow::bail!(
+            "Control and FMM runners allow different base tools (control only: {}; FMM only: {})",
+            only(&control, &fmm),
+            only(&fmm, &control)
+        );
+    }
+    Ok(())
+}
+
+/// Run the task's setup commands in each sandbox, in order. Both variants get
+/// identical setup so it never skews the comparison.
+fn run_setup(commands: &[Vec<String>], dirs: &[&Path]) -> Result<()> {
+    for cmd in commands.iter().filter(|c| !c.is_empty()) {
+        for dir in dirs {
+            if !crate::evaluator::run_command_ok(dir, cmd) {
+                anyhow::bail!(
+                    "Setup command `{}` failed in {}",
+                    cmd.join(" "),
+                    dir.display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Cache-variant suffix for a task's tool-call cap (`--tool-budget` or the
+/// task's own `max_tool_calls`), which can end a run early.
+fn tool_cap_suffix(task: &Task) -> String {
+    task.max_tool_calls
+        .map(|n| format!("-tools{}", n))
+        .unwrap_or_default()
+}
+
+/// Cache-variant suffix hashing the task prompt, so every option that
+/// rewrites it (`--commit-message-template`, `--src-path`, `--max-body-tokens`,
+/// `--fetch-linked`) misses entries cached from a different prompt.
+fn prompt_suffix(task: &Task) -> String {
+    format!("-prompt{}", &crate::runner::prompt_hash(&task.prompt)[..8])
+}
+
+/// Hash of an fmm binary's contents, so rebuilding it in place (e.g.
+/// `./target/release/fmm`) misses the cache. Falls back to the path when the
+/// binary can't be read; sidecar generation will then fail anyway.
+fn fmm_bin_hash(bin: &Path) -> String {
+    match fs::read(bin) {
+        Ok(bytes) => crate::runner::bytes_hash(&bytes),
+        Err(_) => crate::runner::prompt_hash(&bin.to_string_lossy()),
+    }
+}
+
+/// Run a task, retrying up to `retries` times while the CLI errors before
+/// doing any work (see `RunResult::is_errored`) or can't be run at all
+/// (spawn or IO failure). Such attempts cost nothing, so retrying can't
+/// double-spend.
+fn run_with_retries(
+    runner: &ClaudeRunner,
+    retries: u32,
+    task: &Task,
+    working_dir: &Path,
+    variant: &str,
+    fmm_context: Option<&str>,
+) -> Result<RunResult> {
+    retry_errored(retries, || {
+        runner.run_task(task, working_dir, variant, fmm_context)
+    })
+}
+
+/// Call `attempt` until it returns a result that isn't errored, at most
+/// `retries` extra times. The last attempt's outcome, `Err` included, is
+/// returned.
+fn retry_errored(
+    retries: u32,
+    mut attempt: impl FnMut() -> Result<RunResult>,
+) -> Result<RunResult> {
+    let mut result = attempt();
+    for n in 1..=retries {
+        if matches!(&result, Ok(r) if !r.is_errored()) {
+            break;
+        }
+        out!(" {} retry {}/{}...", "↻".yellow(), n, retries);
+        result = attempt();
+    }
+    result
+}
+
+/// Separate rows where a condition errored outright; their zero metrics
+/// would otherwise count as (spurious) wins or losses.
+fn split_errored(rows: Vec<TaskResultRow>) -> (Vec<TaskResultRow>, Vec<ErroredTask>) {
+    let mut kept = vec![];
+    let mut errored = vec![];
+    for row in rows {
+        let failed = [&row.1, &row.2].into_iter().find(|r| r.is_errored());
+        match failed {
+            Some(r) => errored.push(ErroredTask {
+                task_id: row.0.id.clone(),
+                variant: r.variant.clone(),
+                error: r
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| "unknown error".to_string()),
+            }),
+            None => kept.push(row),
+        }
+    }
+    (kept, errored)
+}
+
+/// Average repeated runs of one condition into a single result. Counts,
+/// tokens, cost, and duration are averaged (rounded for integer fields);
+/// everything else comes from the first run. An errored repeat is returned
+/// as-is so the task is excluded like any other errored task.
+fn average_runs(mut runs: Vec<RunResult>) -> RunResult {
+    if let Some(i) = runs.iter().position(RunResult::is_errored) {
+        return runs.swap_remove(i);
+    }
+    let n = runs.len() as f64;
+    let avg = |f: &dyn Fn(&RunResult) -> f64| runs.iter().map(f).sum::<f64>() / n;
+    let tool_calls = avg(&|r| r.tool_calls as f64).round() as u32;
+    let read_calls = avg(&|r| r.read_calls as f64).round() as u32;
+    let input_tokens = avg(&|r| r.input_tokens as f64).round() as u64;
+    let output_tokens = avg(&|r| r.output_tokens as f64).round() as u64;
+    let cache_read_tokens = avg(&|r| r.cache_read_tokens as f64).round() as u64;
+    let total_cost_usd = avg(&|r| r.total_cost_usd);
+    let duration_ms = avg(&|r| r.duration_ms as f64).round() as u64;
+    let num_turns = avg(&|r| r.num_turns as f64).round() as u32;
+    let repeated_tool_invocations = avg(&|r| r.repeated_tool_invocations as f64).round() as u32;
+    let success = runs.iter().all(|r| r.success);
+    let hit_turn_limit = runs.iter().any(|r| r.hit_turn_limit);
+
+    let mut averaged = runs.swap_remove(0);
+    averaged.tool_calls = tool_calls;
+    averaged.read_calls = read_calls;
+    averaged.input_tokens = input_tokens;
+    averaged.output_tokens = output_tokens;
+    averaged.cache_read_tokens = cache_read_tokens;
+    averaged.total_cost_usd = total_cost_usd;
+    averaged.duration_ms = duration_ms;
+    averaged.num_turns = num_turns;
+    averaged.repeated_tool_invocations = repeated_tool_invocations;
+    averaged.success = success;
+    averaged.hit_turn_limit = hit_turn_limit;
+    averaged
 }
 
-fn generate_job_id() -> String {
+/// Whether an optional wall-clock deadline has passed.
+pub(crate) fn deadline_passed(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+pub(crate) fn generate_job_id() -> String {
     use std::io::Read;
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -581,8 +1758,41 @@ fn generate_job_id() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::report::test_report;
+    use crate::tasks::test_task;
     use std::collections::HashMap;
 
+    #[cfg(unix)]
+    #[test]
+    fn test_issue_labels_flow_into_report() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let gh = dir.path().join("gh");
+        fs::write(
+            &gh,
+            "#!/bin/sh\necho '{\"title\":\"Crash\",\"body\":\"b\",\"state\":\"OPEN\",\"labels\":[{\"name\":\"bug\"},{\"name\":\"p1\"}]}'\n",
+        )
+        .unwrap();
+        fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let issue_ref = crate::issue::parse_issue_identifier("owner/repo#7").unwrap();
+        let issue = crate::issue::fetch_issue_with(&gh, &issue_ref).unwrap();
+        let report = issue_report(
+            &issue,
+            "job".to_string(),
+            "abc".to_string(),
+            "main".to_string(),
+            vec![],
+        );
+
+        assert_eq!(report.labels, vec!["bug", "p1"]);
+        assert_eq!(report.repo_url, issue_ref.clone_url());
+        let json = report.to_json().unwrap();
+        let back: ComparisonReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.labels, vec!["bug", "p1"]);
+    }
+
     #[test]
     fn test_job_id_generation() {
         let id1 = generate_job_id();
@@ -624,6 +1834,639 @@ mod tests {
         assert!((orchestrator.total_cost - 0.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn test_rejects_zero_runs_and_budget() {
+        let zero_runs = CompareOptions {
+            runs: 0,
+            ..Default::default()
+        };
+        let err = Orchestrator::new(zero_runs).err().unwrap();
+        assert!(err.to_string().contains("runs must be at least 1"));
+
+        for max_budget in [0.0, -1.0, f64::NAN] {
+            let opts = CompareOptions {
+                max_budget,
+                ..Default::default()
+            };
+            let err = Orchestrator::new(opts).err().unwrap();
+            assert!(err.to_string().contains("max budget must be a positive"));
+        }
+
+        let no_model = CompareOptions {
+            model: " ".to_string(),
+            ..Default::default()
+        };
+        assert!(Orchestrator::new(no_model).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_deadline_stops_before_first_task() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let fmm = dir.path().join("fmm");
+        fs::write(&fmm, "#!/bin/sh\nexit 0\n").unwrap();
+        fs::set_permissions(&fmm, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Serve the repo from a repo cache so nothing touches the network
+        let url = "https://example.com/o/r";
+        let repo = crate::sandbox::cached_repo_dir(dir.path(), url);
+        fs::create_dir_all(&repo).unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        ] {
+            assert!(std::process::Command::new("git")
+                .args(args)
+                .current_dir(&repo)
+                .status()
+                .unwrap()
+                .success());
+        }
+
+        let mut orchestrator = Orchestrator::new(CompareOptions {
+            fmm_bin: Some(fmm),
+            repo_cache: Some(dir.path().to_path_buf()),
+            use_cache: false,
+            deadline: Some(Instant::now()),
+            ..Default::default()
+        })
+        .unwrap();
+        orchestrator.cache = CacheManager::new(Some(dir.path().join("cache"))).unwrap();
+
+        let report = orchestrator.run(url).unwrap();
+        assert!(report.deadline_truncated);
+        assert!(report.task_results.is_empty());
+        assert!((orchestrator.total_cost - 0.0).abs() < f64::EPSILON);
+        assert!(report.to_markdown().contains("results are partial"));
+    }
+
+    #[test]
+    fn test_bogus_model_rejected_up_front() {
+        let typo = CompareOptions {
+            model: "sonnett".to_string(),
+            ..Default::default()
+        };
+        let err = Orchestrator::new(typo).err().unwrap().to_string();
+        assert!(err.contains("Unknown model 'sonnett'"), "{}", err);
+
+        let escaped = CompareOptions {
+            model: "any:claude-next-7".to_string(),
+            ..Default::default()
+        };
+        let orchestrator = Orchestrator::new(escaped).unwrap();
+        assert_eq!(orchestrator.options.model, "claude-next-7");
+    }
+
+    #[test]
+    fn test_model_alias_file_expands_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let aliases = dir.path().join("models.json");
+        fs::write(
+            &aliases,
+            r#"{"smart": "claude-opus-4-1-20250805", "broken": "sonnett"}"#,
+        )
+        .unwrap();
+        let with_model = |model: &str| CompareOptions {
+            model: model.to_string(),
+            model_alias_file: Some(aliases.clone()),
+            ..Default::default()
+        };
+
+        let orchestrator = Orchestrator::new(with_model("smart")).unwrap();
+        assert_eq!(orchestrator.options.model, "claude-opus-4-1-20250805");
+        assert_eq!(orchestrator.model_alias.as_deref(), Some("smart"));
+
+        let mut report = test_report(vec![]);
+        report.model = orchestrator.options.model.clone();
+        report.model_alias = orchestrator.model_alias.clone();
+        assert!(report
+            .to_markdown()
+            .contains("**Model:** claude-opus-4-1-20250805 (alias `smart`)"));
+
+        // Names not in the file are checked as usual
+        let plain = Orchestrator::new(with_model("haiku")).unwrap();
+        assert_eq!(plain.options.model, "haiku");
+        assert_eq!(plain.model_alias, None);
+
+        let err = Orchestrator::new(with_model("broken")).err().unwrap();
+        assert!(
+            format!("{:#}", err).contains("model alias 'broken'"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_tool_parity() {
+        let tools =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|s| s.to_string()).collect() };
+
+        assert!(check_tool_parity(&tools(&["Read", "Grep"]), &tools(&["Grep", "Read"])).is_ok());
+        // FMM's MCP tools are the intended difference
+        assert!(check_tool_parity(
+            &tools(&["Read", "Grep"]),
+            &tools(&["Read", "Grep", "mcp__fmm__lookup"])
+        )
+        .is_ok());
+
+        let err = check_tool_parity(&tools(&["Read", "Bash"]), &tools(&["Read", "Edit"]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("control only: Bash"));
+        assert!(err.contains("FMM only: Edit"));
+
+        // The stock runners agree
+        let orchestrator = Orchestrator::new(CompareOptions::default()).unwrap();
+        assert_eq!(
+            orchestrator.control_runner.allowed_tools(),
+            orchestrator.fmm_runner.allowed_tools()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fmm_regression_runs_use_each_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bins = tempfile::tempdir().unwrap();
+        let log = bins.path().join("calls.log");
+        let shim = |name: &str| {
+            let path = bins.path().join(name);
+            fs::write(
+                &path,
+                format!("#!/bin/sh\necho \"$0\" >> {}\n", log.display()),
+            )
+            .unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            path
+        };
+        let (bin_a, bin_b) = (shim("fmm-a"), shim("fmm-b"));
+
+        let mut orchestrator = Orchestrator::new(CompareOptions::default()).unwrap();
+        let mut n = 0;
+        let used = orchestrator
+            .for_each_fmm_bin([&bin_a, &bin_b], |o| {
+                n += 1;
+                // Stubbed run: just the sandbox's sidecar generation
+                let sandbox = o.new_sandbox(&format!("fmm-regression-{}", n))?;
+                fs::create_dir_all(&sandbox.fmm_dir)?;
+                sandbox.generate_fmm_sidecars(None)?;
+                Ok(o.options.fmm_bin.clone())
+            })
+            .unwrap();
+
+        assert_eq!(used, vec![Some(bin_a.clone()), Some(bin_b.clone())]);
+        let calls = fs::read_to_string(&log).unwrap();
+        let calls: Vec<&str> = calls.lines().collect();
+        assert_eq!(
+            calls,
+            vec![bin_a.to_str().unwrap(), bin_b.to_str().unwrap()]
+        );
+        // The configured binary is restored afterwards
+        assert!(orchestrator.options.fmm_bin.is_none());
+    }
+
+    #[test]
+    fn test_fmm_regression_budget_is_per_binary() {
+        let mut orchestrator = Orchestrator::new(CompareOptions {
+            runs: 3,
+            max_budget: 1.0,
+            ..CompareOptions::default()
+        })
+        .unwrap();
+        let runs = orchestrator
+            .for_each_fmm_bin([Path::new("fmm-a"), Path::new("fmm-b")], |o| {
+                // Stubbed run_issue loop: each run spends its full share
+                let mut done = 0;
+                for _ in 0..o.options.runs {
+                    if o.issue_budget_reached() {
+                        break;
+                    }
+                    o.total_cost += 2.0 * o.options.max_budget;
+                    done += 1;
+                }
+                Ok(done)
+            })
+            .unwrap();
+        assert_eq!(runs, vec![3, 3]);
+    }
+
+    #[test]
+    fn test_setup_runs_in_both_dirs_before_run() {
+        let control = tempfile::tempdir().unwrap();
+        let fmm = tempfile::tempdir().unwrap();
+        let commands = vec![vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo ok > setup.txt".to_string(),
+        ]];
+
+        run_setup(&commands, &[control.path(), fmm.path()]).unwrap();
+        // Stubbed run: both sandboxes must already be set up
+        let run = |dir: &Path| dir.join("setup.txt").exists();
+        assert!(run(control.path()));
+        assert!(run(fmm.path()));
+
+        let failing = vec![vec!["false".to_string()]];
+        let err = run_setup(&failing, &[control.path()])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Setup command `false` failed"));
+    }
+
+    #[test]
+    fn test_ndjson_streams_one_line_per_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("live").join("run.ndjson");
+        let orchestrator = Orchestrator::new(CompareOptions {
+            ndjson: Some(path.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+        let row = |id: &str| {
+            let run = |variant: &str, tools: u32| {
+                RunResult::from_metrics(
+                    crate::metrics::RunMetrics {
+                        tool_calls: tools,
+                        ..Default::default()
+                    },
+                    "done".to_string(),
+                    id,
+                    variant,
+                )
+            };
+            let task = test_task(id);
+            (task, run("control", 10), run("fmm", 4), None, None)
+        };
+
+        for id in ["t1", "t2", "t3"] {
+            orchestrator.stream_ndjson(&row(id)).unwrap();
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<TaskComparison> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let ids: Vec<&str> = lines.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(ids, ["t1", "t2", "t3"]);
+        assert!((lines[0].savings.tool_calls_reduction_pct - 60.0).abs() < 1e-10);
+
+        // Without --ndjson nothing is written
+        let quiet = Orchestrator::new(CompareOptions::default()).unwrap();
+        quiet.stream_ndjson(&row("t4")).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_task_repeats_average_underlying_runs() {
+        let mut orchestrator = Orchestrator::new(CompareOptions {
+            task_repeats: 3,
+            ..Default::default()
+        })
+        .unwrap();
+        let run = |variant: &str, tools: u32, cost: f64| {
+            let mut r = RunResult::from_metrics(
+                crate::metrics::RunMetrics {
+                    tool_calls: tools,
+                    success: true,
+                    ..Default::default()
+                },
+                "done".to_string(),
+                "t1",
+                variant,
+            );
+            r.total_cost_usd = cost;
+            r
+        };
+        let control_tools = [10, 12, 14];
+        let fmm_tools = [4, 5, 9];
+
+        let mut repeats_seen = vec![];
+        let (control, fmm, spread) = orchestrator
+            .repeat_task(|this| {
+                repeats_seen.push(this.repeat);
+                // Each repeat after the first gets its own cache entries
+                assert_eq!(this.cache_suffix().is_empty(), this.repeat == 0);
+                let i = this.repeat as usize;
+                Ok((
+                    run("control", control_tools[i], 0.3),
+                    run("fmm", fmm_tools[i], 0.1 * (i + 1) as f64),
+                ))
+            })
+            .unwrap();
+
+        assert_eq!(repeats_seen, [0, 1, 2]);
+        assert_eq!(orchestrator.repeat, 0);
+        assert_eq!(control.tool_calls, 12);
+        assert_eq!(fmm.tool_calls, 6);
+        assert!((fmm.total_cost_usd - 0.2).abs() < 1e-10);
+        let spread = spread.unwrap();
+        assert_eq!(spread.runs, 3);
+        assert!((spread.control_tool_calls.std_dev - 2.0).abs() < 1e-10);
+        assert!((spread.fmm_tool_calls.mean - 6.0).abs() < 1e-10);
+        assert!(spread.control_cost.std_dev.abs() < 1e-10);
+
+        // The averaged pair feeds the task comparison as usual
+        let comparison = TaskComparison::new(test_task("t1"), control, fmm, None, None);
+        assert!((comparison.savings.tool_calls_reduction_pct - 50.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_task_repeat_error_excludes_task() {
+        let ok = RunResult::from_metrics(
+            crate::metrics::RunMetrics {
+                tool_calls: 3,
+                success: true,
+                ..Default::default()
+            },
+            "done".to_string(),
+            "t1",
+            "fmm",
+        );
+        let crashed = RunResult::from_metrics(
+            crate::metrics::RunMetrics {
+                error: Some("CLI exited with status 1".to_string()),
+                ..Default::default()
+            },
+            String::new(),
+            "t1",
+            "fmm",
+        );
+        assert!(average_runs(vec![ok.clone(), crashed, ok]).is_errored());
+    }
+
+    #[test]
+    fn test_sidecar_args_key_fmm_cache_only() {
+        let plain = Orchestrator::new(CompareOptions::default()).unwrap();
+        let with_args = |args: &[&str]| {
+            Orchestrator::new(CompareOptions {
+                sidecar_args: args.iter().map(|a| a.to_string()).collect(),
+                ..CompareOptions::default()
+            })
+            .unwrap()
+        };
+        let a = with_args(&["--include-private"]);
+        let b = with_args(&["--max-depth=2"]);
+
+        let task = test_task("t1");
+        let prompt = prompt_suffix(&task);
+        assert_eq!(
+            plain.fmm_cache_variant(&task, "fmm").unwrap(),
+            format!("fmm{}", prompt)
+        );
+        assert_ne!(
+            a.fmm_cache_variant(&task, "fmm").unwrap(),
+            plain.fmm_cache_variant(&task, "fmm").unwrap()
+        );
+        assert_ne!(
+            a.fmm_cache_variant(&task, "fmm").unwrap(),
+            b.fmm_cache_variant(&task, "fmm").unwrap()
+        );
+        // Control never reads sidecars
+        assert_eq!(
+            a.control_cache_variant(&task, "control").unwrap(),
+            format!("control{}", prompt)
+        );
+    }
+
+    #[test]
+    fn test_fmm_bin_rebuilt_in_place_misses_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("fmm");
+        std::fs::write(&bin, "build 1").unwrap();
+        let orchestrator = || {
+            Orchestrator::new(CompareOptions {
+                fmm_bin: Some(bin.clone()),
+                ..CompareOptions::default()
+            })
+            .unwrap()
+        };
+        let task = test_task("t1");
+        let first = orchestrator();
+        let before = first.fmm_cache_variant(&task, "fmm").unwrap();
+
+        // Hashed once per orchestrator, so the next invocation sees the rebuild
+        std::fs::write(&bin, "build 2").unwrap();
+        assert_eq!(first.fmm_cache_variant(&task, "fmm").unwrap(), before);
+        assert_ne!(
+            orchestrator().fmm_cache_variant(&task, "fmm").unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_tool_cap_keys_both_caches() {
+        let orchestrator = Orchestrator::new(CompareOptions::default()).unwrap();
+        let uncapped = test_task("t1");
+        let capped = Task {
+            max_tool_calls: Some(20),
+            ..test_task("t1")
+        };
+        let prompt = prompt_suffix(&uncapped);
+        assert_eq!(
+            orchestrator
+                .control_cache_variant(&uncapped, "control")
+                .unwrap(),
+            format!("control{}", prompt)
+        );
+        assert_eq!(
+            orchestrator
+                .control_cache_variant(&capped, "control")
+                .unwrap(),
+            format!("control-tools20{}", prompt)
+        );
+        assert_eq!(
+            orchestrator.fmm_cache_variant(&capped, "fmm").unwrap(),
+            format!("fmm-tools20{}", prompt)
+        );
+    }
+
+    #[test]
+    fn test_require_permissions_keys_both_caches() {
+        let orchestrator = Orchestrator::new(CompareOptions {
+            require_permissions: true,
+            ..CompareOptions::default()
+        })
+        .unwrap();
+        let task = test_task("t1");
+        let prompt = prompt_suffix(&task);
+        assert_eq!(
+            orchestrator
+                .control_cache_variant(&task, "control")
+                .unwrap(),
+            format!("control-perm{}", prompt)
+        );
+        assert_eq!(
+            orchestrator.fmm_cache_variant(&task, "fmm").unwrap(),
+            format!("fmm-perm{}", prompt)
+        );
+    }
+
+    #[test]
+    fn test_src_path_keys_both_caches() {
+        let plain = Orchestrator::new(CompareOptions::default()).unwrap();
+        let scoped = Orchestrator::new(CompareOptions {
+            src_path: Some("src/core".to_string()),
+            ..CompareOptions::default()
+        })
+        .unwrap();
+        let task = test_task("t1");
+        let scoped_task = test_task("t1").scoped_to("src/core");
+        assert_ne!(
+            plain.control_cache_variant(&task, "control").unwrap(),
+            scoped
+                .control_cache_variant(&scoped_task, "control")
+                .unwrap()
+        );
+        // Sidecars differ even when the prompt doesn't
+        assert_ne!(
+            plain.fmm_cache_variant(&task, "fmm").unwrap(),
+            scoped.fmm_cache_variant(&task, "fmm").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unreadable_settings_template_fails_cache_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        std::fs::write(&path, "{}").unwrap();
+        let orchestrator = Orchestrator::new(CompareOptions {
+            settings_template: Some(SettingsTemplate {
+                path: path.clone(),
+                scope: SettingsScope::Both,
+            }),
+            ..CompareOptions::default()
+        })
+        .unwrap();
+        let task = test_task("t1");
+        assert!(orchestrator.fmm_cache_variant(&task, "fmm").is_ok());
+
+        // Gone mid-batch: an error, not the key of an empty template
+        std::fs::remove_file(&path).unwrap();
+        let err = orchestrator.fmm_cache_variant(&task, "fmm").unwrap_err();
+        assert!(format!("{:#}", err).contains("settings template"));
+        assert!(orchestrator
+            .control_cache_variant(&task, "control")
+            .is_err());
+    }
+
+    #[test]
+    fn test_commit_message_template_keys_both_caches() {
+        let issue = GitHubIssue {
+            issue_ref: crate::issue::parse_issue_identifier("o/r#1").unwrap(),
+            title: "It's broken".to_string(),
+            body: String::new(),
+            state: "OPEN".to_string(),
+            labels: vec![],
+        };
+        let plain = Orchestrator::new(CompareOptions::default()).unwrap();
+        let templated = Orchestrator::new(CompareOptions {
+            commit_message_template: Some("fix: <summary>".to_string()),
+            ..CompareOptions::default()
+        })
+        .unwrap();
+        let (a, b) = (plain.issue_task(&issue), templated.issue_task(&issue));
+        assert_ne!(
+            plain.control_cache_variant(&a, "control").unwrap(),
+            templated.control_cache_variant(&b, "control").unwrap()
+        );
+        assert_ne!(
+            plain.fmm_cache_variant(&a, "fmm").unwrap(),
+            templated.fmm_cache_variant(&b, "fmm").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_next_issue_keeps_memory_cache_and_resets_spend() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut orchestrator = Orchestrator::new(CompareOptions::default()).unwrap();
+        orchestrator.cache = CacheManager::new(Some(dir.path().to_path_buf())).unwrap();
+
+        // First issue: a cached control run and some spend
+        let key = CacheKey::new("https://github.com/o/r.git", "abc", "issue-1", "control");
+        let result = RunResult::from_metrics(
+            crate::metrics::RunMetrics {
+                tool_calls: 7,
+                success: true,
+                ..Default::default()
+            },
+            "done".to_string(),
+            "issue-1",
+            "control",
+        );
+        orchestrator.cache.set(key.clone(), result).unwrap();
+        orchestrator.total_cost = 3.5;
+
+        // Second issue: only the in-memory cache can still answer
+        std::fs::remove_dir_all(dir.path()).unwrap();
+        orchestrator.begin_issue(IssueSettings {
+            branch: Some("dev".to_string()),
+            commit: Some("abc123".to_string()),
+            max_budget: 2.0,
+            setup_commands: vec![vec!["true".to_string()]],
+            task_set: Some("quick".to_string()),
+        });
+
+        assert_eq!(orchestrator.total_cost, 0.0);
+        assert_eq!(orchestrator.options.branch.as_deref(), Some("dev"));
+        assert_eq!(orchestrator.options.commit.as_deref(), Some("abc123"));
+        assert_eq!(orchestrator.options.max_budget, 2.0);
+        assert_eq!(orchestrator.options.setup_commands.len(), 1);
+        assert_eq!(orchestrator.options.task_set, "quick");
+        assert_eq!(orchestrator.cache.get(&key).unwrap().tool_calls, 7);
+    }
+
+    #[test]
+    fn test_cache_hits_add_no_spend() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut orchestrator = Orchestrator::new(CompareOptions {
+            use_cache: true,
+            ..Default::default()
+        })
+        .unwrap();
+        orchestrator.cache = CacheManager::new(Some(dir.path().to_path_buf())).unwrap();
+
+        let url = "https://github.com/o/r.git";
+        let task = test_task("t");
+        let variant = orchestrator
+            .control_cache_variant(&task, "control")
+            .unwrap();
+        let cached = RunResult::from_metrics(
+            crate::metrics::RunMetrics {
+                tool_calls: 7,
+                cost_usd: 0.5,
+                success: true,
+                ..Default::default()
+            },
+            "done".to_string(),
+            "t",
+            "control",
+        );
+        orchestrator
+            .cache
+            .set(CacheKey::new(url, "abc", &task.id, &variant), cached)
+            .unwrap();
+
+        let result = orchestrator
+            .run_task_with_cache(&task, dir.path(), "control", url, "abc")
+            .unwrap();
+        assert_eq!(result.tool_calls, 7);
+        assert_eq!(orchestrator.spent, Spend::default());
+    }
+
     #[test]
     fn test_budget_tracking_logic() {
         // Test that the budget check logic works correctly
@@ -638,6 +2481,150 @@ mod tests {
         assert!(orchestrator.total_cost < orchestrator.options.max_budget);
     }
 
+    #[test]
+    fn test_empty_body_warns_or_skips() {
+        let issue = GitHubIssue {
+            issue_ref: crate::issue::parse_issue_identifier("o/r#7").unwrap(),
+            title: "It's broken".to_string(),
+            body: String::new(),
+            state: "OPEN".to_string(),
+            labels: vec![],
+        };
+
+        let warn = Orchestrator::new(CompareOptions::default()).unwrap();
+        assert!(warn.check_issue_body(&issue).is_ok());
+
+        let skip = Orchestrator::new(CompareOptions {
+            skip_empty_body: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let err = skip.check_issue_body(&issue).unwrap_err().to_string();
+        assert!(err.contains("Skipped o/r#7"));
+        assert!(err.contains("0 characters after sanitization"));
+    }
+
+    #[test]
+    fn test_retry_covers_spawn_failures() {
+        let ok = || RunResult {
+            task_id: "t".to_string(),
+            variant: "control".to_string(),
+            tool_calls: 3,
+            tools_by_name: HashMap::new(),
+            files_accessed: vec![],
+            read_calls: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            total_cost_usd: 0.0,
+            cost_estimated: false,
+            prompt_hash: String::new(),
+            duration_ms: 0,
+            num_turns: 1,
+            response: String::new(),
+            transcript: String::new(),
+            success: true,
+            error: None,
+            session_id: None,
+            actual_model: None,
+            hit_turn_limit: false,
+            repeated_tool_invocations: 0,
+            tool_details: HashMap::new(),
+            navigation: Default::default(),
+            fmm_usage: Default::default(),
+            tool_categories: Default::default(),
+        };
+
+        // A spawn failure, then a run that works
+        let mut calls = 0;
+        let result = retry_errored(1, || {
+            calls += 1;
+            if calls == 1 {
+                anyhow::bail!("Failed to spawn claude")
+            }
+            Ok(ok())
+        })
+        .unwrap();
+        assert_eq!(calls, 2);
+        assert_eq!(result.tool_calls, 3);
+
+        // Failures past the retry budget surface as the last error
+        let mut calls = 0;
+        let err = retry_errored(2, || {
+            calls += 1;
+            anyhow::bail!("Failed to spawn claude (attempt {})", calls)
+        })
+        .unwrap_err();
+        assert_eq!(calls, 3);
+        assert!(err.to_string().contains("attempt 3"));
+
+        // A good first run isn't repeated
+        let mut calls = 0;
+        retry_errored(2, || {
+            calls += 1;
+            Ok(ok())
+        })
+        .unwrap();
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_errored_task_excluded_and_listed() {
+        let ok = |id: &str, variant: &str, tools: u32| {
+            RunResult::from_metrics(
+                crate::metrics::RunMetrics {
+                    tool_calls: tools,
+                    turns: 2,
+                    success: true,
+                    ..Default::default()
+                },
+                "done".to_string(),
+                id,
+                variant,
+            )
+        };
+        let crashed = RunResult::from_metrics(
+            crate::metrics::RunMetrics {
+                error: Some("CLI exited with status 1".to_string()),
+                ..Default::default()
+            },
+            String::new(),
+            "t2",
+            "fmm",
+        );
+
+        let rows = vec![
+            (
+                test_task("t1"),
+                ok("t1", "control", 10),
+                ok("t1", "fmm", 5),
+                None,
+                None,
+            ),
+            (
+                test_task("t2"),
+                ok("t2", "control", 10),
+                crashed,
+                None,
+                None,
+            ),
+        ];
+        let (kept, errored) = split_errored(rows);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(errored.len(), 1);
+        assert_eq!(errored[0].task_id, "t2");
+        assert_eq!(errored[0].variant, "fmm");
+
+        let mut report = test_report(kept);
+        report.errored_tasks = errored;
+        // The crashed FMM run's zero tool calls don't count as a win
+        assert_eq!(report.summary.tasks_run, 1);
+        assert_eq!(report.summary.fmm_totals.total_tool_calls, 5);
+        assert!(report
+            .to_markdown()
+            .contains("- t2 (fmm): CLI exited with status 1"));
+    }
+
     // Integration test: report generation with real data structures
     #[test]
     fn test_report_generation_integration() {
@@ -653,6 +2640,8 @@ mod tests {
             expected_patterns: vec!["main".to_string()],
             max_turns: 10,
             max_budget_usd: 1.0,
+            max_tool_calls: None,
+            setup_commands: vec![],
         };
 
         let control = RunResult {
@@ -670,14 +2659,22 @@ mod tests {
             output_tokens: 1200,
             cache_read_tokens: 0,
             total_cost_usd: 0.02,
+            cost_estimated: false,
+            prompt_hash: String::new(),
             duration_ms: 15000,
             num_turns: 4,
             response: "The main entry point is src/main.rs".to_string(),
+            transcript: String::new(),
             success: true,
             error: None,
+            session_id: None,
+            actual_model: None,
+            hit_turn_limit: false,
+            repeated_tool_invocations: 0,
             tool_details: HashMap::new(),
             navigation: Default::default(),
             fmm_usage: Default::default(),
+            tool_categories: Default::default(),
         };
 
         let fmm = RunResult {
@@ -691,14 +2688,22 @@ mod tests {
             output_tokens: 800,
             cache_read_tokens: 500,
             total_cost_usd: 0.005,
+            cost_estimated: false,
+            prompt_hash: String::new(),
             duration_ms: 5000,
             num_turns: 1,
             response: "The main entry point is src/main.rs".to_string(),
+            transcript: String::new(),
             success: true,
             error: None,
+            session_id: None,
+            actual_model: None,
+            hit_turn_limit: false,
+            repeated_tool_invocations: 0,
             tool_details: HashMap::new(),
             navigation: Default::default(),
             fmm_usage: Default::default(),
+            tool_categories: Default::default(),
         };
 
         let report = ComparisonReport::new(
@@ -720,6 +2725,13 @@ mod tests {
         assert!((savings.tool_calls_reduction_pct - 87.5).abs() < 0.1);
         assert!((savings.read_calls_reduction_pct - 80.0).abs() < 0.1);
 
+        // Accessors mirror the nested summary
+        assert!((report.tool_call_reduction() - 87.5).abs() < 0.1);
+        assert!((report.cost_reduction() - 75.0).abs() < 0.1);
+        assert_eq!(report.fmm_win_rate(), 1.0);
+        assert_eq!(report.best_task().unwrap().task_id, "find_entry");
+        assert_eq!(report.worst_task().unwrap().task_id, "find_entry");
+
         // Verify markdown generation doesn't panic
         let md = report.to_markdown();
         assert!(md.contains("integration-test"));
@@ -785,4 +2797,89 @@ mod tests {
             .load_custom_tasks(task_file.to_str().unwrap())
             .is_err());
     }
+
+    #[test]
+    fn test_validate_custom_tasks_reports_every_problem() {
+        let temp = tempfile::tempdir().unwrap();
+        let task_file = temp.path().join("tasks.json");
+        let task = |id: &str, prompt: String| {
+            serde_json::json!({
+                "id": id,
+                "name": id,
+                "prompt": prompt,
+                "category": "exploration",
+                "max_turns": 5,
+                "max_budget_usd": 0.5
+            })
+        };
+        let tasks_json = serde_json::json!({
+            "name": "custom",
+            "description": "Problems",
+            "tasks": [
+                task("find_entry", "Where is main?".to_string()),
+                task("find_entry", "x".repeat(ClaudeRunner::MAX_PROMPT_SIZE + 1)),
+            ]
+        });
+        fs::write(&task_file, tasks_json.to_string()).unwrap();
+
+        let (tasks, problems) = validate_custom_tasks(task_file.to_str().unwrap()).unwrap();
+        assert_eq!(tasks, 2);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("Duplicate task id 'find_entry' (tasks 1 and 2)"));
+        assert!(problems[1].contains("prompt is 102401 bytes (max 102400)"));
+
+        let unknown = tasks_json.to_string().replace("exploration", "explore");
+        fs::write(&task_file, unknown).unwrap();
+        let err = validate_custom_tasks(task_file.to_str().unwrap()).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("unknown variant `explore`"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_resolve_builtin_task_sets() {
+        for name in TaskSet::BUILTIN_NAMES {
+            let orchestrator = Orchestrator::new(CompareOptions {
+                task_set: name.to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+            let set = orchestrator.resolve_task_set(Path::new("/tmp")).unwrap();
+            assert_eq!(set.name, *name);
+            assert!(!set.tasks.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_resolve_unknown_task_set_loads_file() {
+        let orchestrator = Orchestrator::new(CompareOptions {
+            task_set: "/nonexistent/rust-navigation.json".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let err = format!(
+            "{:#}",
+            orchestrator
+                .resolve_task_set(Path::new("/tmp"))
+                .unwrap_err()
+        );
+        assert!(err.contains("not a built-in task set"));
+        assert!(err.contains("Failed to load custom tasks"));
+    }
+
+    #[test]
+    fn test_resolve_auto_task_set() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("lib.rs"), "").unwrap();
+
+        let orchestrator = Orchestrator::new(CompareOptions {
+            task_set: "auto".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let set = orchestrator.resolve_task_set(repo.path()).unwrap();
+        assert_eq!(set.name, "rust-navigation");
+    }
 }
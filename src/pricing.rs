@@ -0,0 +1,253 @@
+//! Per-model token pricing, used to estimate cost when the CLI reports none.
+//!
+//! Some `claude` configurations (e.g. API-key billing) report
+//! `total_cost_usd: 0` or omit it entirely; without an estimate the cost
+//! comparison silently collapses to zero.
+
+use crate::metrics::RunMetrics;
+use anyhow::Context;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+/// Rates keyed by a substring of the model name; first match wins, so more
+/// specific names come first.
+const PRICING: &[(&str, ModelPricing)] = &[
+    ("opus-4-1", OPUS_LEGACY),
+    ("opus-4-2025", OPUS_LEGACY),
+    ("3-opus", OPUS_LEGACY),
+    (
+        "opus",
+        ModelPricing {
+            input: 5.0,
+            output: 25.0,
+            cache_write: 6.25,
+            cache_read: 0.50,
+        },
+    ),
+    (
+        "sonnet",
+        ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            cache_write: 3.75,
+            cache_read: 0.30,
+        },
+    ),
+    (
+        "haiku",
+        ModelPricing {
+            input: 1.0,
+            output: 5.0,
+            cache_write: 1.25,
+            cache_read: 0.10,
+        },
+    ),
+];
+
+const OPUS_LEGACY: ModelPricing = ModelPricing {
+    input: 15.0,
+    output: 75.0,
+    cache_write: 18.75,
+    cache_read: 1.50,
+};
+
+/// Look up pricing for a model alias (`sonnet`) or full id (`claude-sonnet-4-5`).
+pub fn pricing_for(model: &str) -> Option<ModelPricing> {
+    let model = model.to_lowercase();
+    PRICING
+        .iter()
+        .find(|(key, _)| model.contains(key))
+        .map(|(_, p)| *p)
+}
+
+/// Model aliases the `claude` CLI accepts.
+pub const MODEL_ALIASES: &[&str] = &["sonnet", "opus", "haiku"];
+
+/// Full model ids shown by `--list-models`; any `claude-*` id of a priced
+/// family is accepted, not just these.
+pub const KNOWN_MODEL_IDS: &[&str] = &[
+    "claude-opus-4-5",
+    "claude-opus-4-1",
+    "claude-sonnet-4-5",
+    "claude-haiku-4-5",
+];
+
+/// `--model` value that skips validation and runs the CLI's own default model.
+pub const ANY_MODEL: &str = "any";
+
+/// Prefix that skips model validation, for models newer than this table.
+pub const ANY_MODEL_PREFIX: &str = "any:";
+
+/// Check a `--model` value up front and return the name to pass to the CLI.
+///
+/// Accepts an alias (`sonnet`), a full id of a known family
+/// (`claude-sonnet-4-5-20250929`), `any` to run whatever model the CLI
+/// defaults to, or `any:<name>` to pass `<name>` through unchecked. A typo
+/// would otherwise only surface after the sandbox is built.
+pub fn resolve_model(model: &str) -> anyhow::Result<String> {
+    let model = model.trim();
+    if model.eq_ignore_ascii_case(ANY_MODEL) {
+        return Ok(ANY_MODEL.to_string());
+    }
+    if let Some(name) = model.strip_prefix(ANY_MODEL_PREFIX) {
+        if name.trim().is_empty() {
+            anyhow::bail!("--model {}<name> needs a model name", ANY_MODEL_PREFIX);
+        }
+        return Ok(name.trim().to_string());
+    }
+    let lower = model.to_lowercase();
+    let known = MODEL_ALIASES.contains(&lower.as_str())
+        || (lower.starts_with("claude-") && pricing_for(&lower).is_some());
+    if !known {
+        anyhow::bail!(
+            "Unknown model '{}': expected one of {} or a full id like {} \
+             (use --model {}<name> for a model not listed by --list-models, \
+             or --model {} for the CLI's default)",
+            model,
+            MODEL_ALIASES.join(", "),
+            KNOWN_MODEL_IDS[0],
+            ANY_MODEL_PREFIX,
+            ANY_MODEL
+        );
+    }
+    Ok(model.to_string())
+}
+
+/// Team-defined model names (`fast`, `smart`) mapped to the ids they stand
+/// for, from a JSON object like `{"smart": "claude-opus-4-5"}`.
+pub fn load_model_aliases(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read model aliases from {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| {
+        format!(
+            "Failed to parse model aliases from {} (expected an object of name to model id)",
+            path.display()
+        )
+    })
+}
+
+/// `fmm-bench/models.json` in the user's config dir (`~/.config` on Linux),
+/// if it exists; used when no `--model-alias-file` is given.
+pub fn default_model_alias_file() -> Option<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("fmm-bench").join("models.json"))
+        .filter(|path| path.is_file())
+}
+
+/// Estimated cost of a run's token usage at the model's rates.
+pub fn estimate_cost(model: &str, metrics: &RunMetrics) -> Option<f64> {
+    let p = pricing_for(model)?;
+    let per_token = |tokens: u64, rate: f64| tokens as f64 * rate / 1_000_000.0;
+    Some(
+        per_token(metrics.input_tokens, p.input)
+            + per_token(metrics.output_tokens, p.output)
+            + per_token(metrics.cache_creation_tokens, p.cache_write)
+            + per_token(metrics.cache_read_tokens, p.cache_read),
+    )
+}
+
+/// Estimate cost only when the CLI reported none but tokens were used.
+///
+/// Prices the model the CLI actually ran (`actual_model`), falling back to
+/// the requested `model` when that's unknown or unpriced. Returns `None` when
+/// the reported cost should be kept (or neither model is priced).
+pub fn estimate_missing_cost(model: &str, metrics: &RunMetrics) -> Option<f64> {
+    let tokens = metrics.input_tokens
+        + metrics.output_tokens
+        + metrics.cache_creation_tokens
+        + metrics.cache_read_tokens;
+    if metrics.cost_usd > 0.0 || tokens == 0 {
+        return None;
+    }
+    metrics
+        .actual_model
+        .as_deref()
+        .and_then(|actual| estimate_cost(actual, metrics))
+        .or_else(|| estimate_cost(model, metrics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_model() {
+        assert_eq!(resolve_model("sonnet").unwrap(), "sonnet");
+        assert_eq!(
+            resolve_model("claude-opus-4-1-20250805").unwrap(),
+            "claude-opus-4-1-20250805"
+        );
+        assert_eq!(resolve_model("any:claude-next").unwrap(), "claude-next");
+        assert_eq!(resolve_model("any").unwrap(), ANY_MODEL);
+        assert_eq!(resolve_model(" ANY ").unwrap(), ANY_MODEL);
+        for bad in ["sonnett", "gpt-4o", "claude-", "any:"] {
+            assert!(resolve_model(bad).is_err(), "{} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn test_zero_cost_with_tokens_is_estimated() {
+        let metrics = RunMetrics {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_tokens: 200_000,
+            cache_read_tokens: 1_000_000,
+            cost_usd: 0.0,
+            ..Default::default()
+        };
+
+        // 3.00 + 1.50 + 0.75 + 0.30
+        let cost = estimate_missing_cost("sonnet", &metrics).unwrap();
+        assert!((cost - 5.55).abs() < 1e-9, "cost {}", cost);
+    }
+
+    #[test]
+    fn test_missing_cost_uses_actual_model() {
+        let metrics = |actual: Option<&str>| RunMetrics {
+            input_tokens: 1_000_000,
+            actual_model: actual.map(str::to_string),
+            ..Default::default()
+        };
+        // Asked for sonnet, the CLI ran opus: priced as opus
+        let cost = estimate_missing_cost("sonnet", &metrics(Some("claude-opus-4-5"))).unwrap();
+        assert!((cost - 5.0).abs() < 1e-9, "cost {}", cost);
+        // Unknown or unpriced actual model: the requested one
+        for actual in [None, Some("mystery-model")] {
+            let cost = estimate_missing_cost("sonnet", &metrics(actual)).unwrap();
+            assert!((cost - 3.0).abs() < 1e-9, "cost {}", cost);
+        }
+    }
+
+    #[test]
+    fn test_reported_cost_is_kept() {
+        let metrics = RunMetrics {
+            input_tokens: 1000,
+            cost_usd: 0.42,
+            ..Default::default()
+        };
+        assert_eq!(estimate_missing_cost("sonnet", &metrics), None);
+        // No tokens: nothing to estimate from
+        assert_eq!(
+            estimate_missing_cost("sonnet", &RunMetrics::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pricing_lookup() {
+        assert_eq!(pricing_for("claude-sonnet-4-5").unwrap().input, 3.0);
+        assert_eq!(pricing_for("HAIKU").unwrap().output, 5.0);
+        assert_eq!(pricing_for("claude-opus-4-1-20250805"), Some(OPUS_LEGACY));
+        assert_eq!(pricing_for("opus").unwrap().input, 5.0);
+        assert_eq!(pricing_for("gpt-4"), None);
+    }
+}
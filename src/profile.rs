@@ -0,0 +1,120 @@
+//! Wall-clock timing of pipeline stages (`--profile`)
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Accumulated time for one pipeline stage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration_ms: u64,
+    /// Times the stage ran (e.g. once per run, or per issue in a batch)
+    pub count: u32,
+}
+
+/// Per-stage timing breakdown, in the order stages first ran
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub stages: Vec<StageTiming>,
+}
+
+impl Profile {
+    /// Add `elapsed` to `stage`.
+    pub fn record(&mut self, stage: &str, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        match self.stages.iter_mut().find(|s| s.stage == stage) {
+            Some(s) => {
+                s.duration_ms += ms;
+                s.count += 1;
+            }
+            None => self.stages.push(StageTiming {
+                stage: stage.to_string(),
+                duration_ms: ms,
+                count: 1,
+            }),
+        }
+    }
+
+    /// Fold another profile into this one (batch totals across issues).
+    pub fn merge(&mut self, other: &Profile) {
+        for s in &other.stages {
+            match self.stages.iter_mut().find(|t| t.stage == s.stage) {
+                Some(t) => {
+                    t.duration_ms += s.duration_ms;
+                    t.count += s.count;
+                }
+                None => self.stages.push(s.clone()),
+            }
+        }
+    }
+
+    pub fn total_ms(&self) -> u64 {
+        self.stages.iter().map(|s| s.duration_ms).sum()
+    }
+
+    /// Plain-text breakdown: one line per stage with its share of the total.
+    pub fn to_table(&self) -> String {
+        let total = self.total_ms().max(1) as f64;
+        let width = self.stages.iter().map(|s| s.stage.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for s in &self.stages {
+            out.push_str(&format!(
+                "  {:<width$}  {:>9.1}s  {:>5.1}%  (x{})\n",
+                s.stage,
+                s.duration_ms as f64 / 1000.0,
+                s.duration_ms as f64 / total * 100.0,
+                s.count,
+                width = width
+            ));
+        }
+        out.push_str(&format!(
+            "  {:<width$}  {:>9.1}s\n",
+            "total",
+            self.total_ms() as f64 / 1000.0,
+            width = width
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn records_stage_durations() {
+        let mut profile = Profile::default();
+        for stage in ["clone", "sidecar-gen", "run:control", "run:fmm", "eval"] {
+            let start = Instant::now();
+            std::thread::sleep(Duration::from_millis(5));
+            profile.record(stage, start.elapsed());
+        }
+        profile.record("run:control", Duration::from_millis(20));
+
+        assert_eq!(profile.stages.len(), 5);
+        assert!(profile.stages.iter().all(|s| s.duration_ms > 0));
+        let control = &profile.stages[2];
+        assert_eq!(control.count, 2);
+        assert!(control.duration_ms >= 25);
+
+        let table = profile.to_table();
+        assert!(table.contains("sidecar-gen"));
+        assert!(table.lines().last().unwrap().contains("total"));
+    }
+
+    #[test]
+    fn merge_sums_matching_stages() {
+        let mut a = Profile::default();
+        a.record("clone", Duration::from_millis(100));
+        let mut b = Profile::default();
+        b.record("clone", Duration::from_millis(50));
+        b.record("eval", Duration::from_millis(10));
+
+        a.merge(&b);
+        assert_eq!(a.stages[0].duration_ms, 150);
+        assert_eq!(a.stages[0].count, 2);
+        assert_eq!(a.stages[1].stage, "eval");
+        assert_eq!(a.total_ms(), 160);
+    }
+}
@@ -0,0 +1,116 @@
+//! Request pacing for `gh` calls shared across worker threads
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket: `burst` requests up front, then one every `interval`.
+///
+/// Time is passed in explicitly so pacing can be tested without sleeping.
+#[derive(Debug)]
+struct TokenBucket {
+    interval: Duration,
+    burst: u32,
+    /// When the bucket would next be full again; reservations push it forward.
+    full_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(interval: Duration, burst: u32, now: Instant) -> Self {
+        Self {
+            interval,
+            burst: burst.max(1),
+            full_at: now,
+        }
+    }
+
+    /// Reserve one request at `now`, returning how long to wait before sending it.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let window = self.interval * self.burst;
+        // An idle bucket refills, but never beyond `burst` tokens.
+        let start = self.full_at.max(now);
+        self.full_at = start + self.interval;
+        (self.full_at.saturating_duration_since(now)).saturating_sub(window)
+    }
+}
+
+/// Thread-safe wrapper around a [`TokenBucket`] on the real clock.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bucket: Mutex<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Allow `per_second` requests per second, with bursts of up to `burst`.
+    pub(crate) fn new(per_second: u32, burst: u32) -> Self {
+        let interval = Duration::from_secs(1) / per_second.max(1);
+        Self {
+            bucket: Mutex::new(TokenBucket::new(interval, burst, Instant::now())),
+        }
+    }
+
+    /// Block until the next request may be sent.
+    pub(crate) fn acquire(&self) {
+        let wait = self
+            .bucket
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .reserve(Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// Delay before retry number `attempt` (0-based) after a rate-limit response:
+/// exponential from `base`, capped at one minute.
+pub(crate) fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(1 << attempt.min(16))
+        .min(Duration::from_secs(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_bucket_paces_after_burst() {
+        let t0 = Instant::now();
+        let mut bucket = TokenBucket::new(ms(100), 3, t0);
+
+        // The burst goes out immediately
+        assert_eq!(bucket.reserve(t0), Duration::ZERO);
+        assert_eq!(bucket.reserve(t0), Duration::ZERO);
+        assert_eq!(bucket.reserve(t0), Duration::ZERO);
+        // Then one request per interval
+        assert_eq!(bucket.reserve(t0), ms(100));
+        assert_eq!(bucket.reserve(t0), ms(200));
+
+        // Reserved slots are honoured for a caller arriving later
+        assert_eq!(bucket.reserve(t0 + ms(150)), ms(150));
+    }
+
+    #[test]
+    fn test_bucket_refills_only_up_to_burst() {
+        let t0 = Instant::now();
+        let mut bucket = TokenBucket::new(ms(100), 2, t0);
+        bucket.reserve(t0);
+        bucket.reserve(t0);
+
+        // Long idle: refills to 2 tokens, not more
+        let later = t0 + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), ms(100));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(ms(500), 0), ms(500));
+        assert_eq!(backoff_delay(ms(500), 2), ms(2000));
+        assert_eq!(backoff_delay(ms(500), 30), Duration::from_secs(60));
+    }
+}
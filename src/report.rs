@@ -1,12 +1,19 @@
 //! Comparison report generation - JSON and Markdown formats
 
+use anyhow::Context;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
+use crate::aggregate::{metric_spread, MetricSpread, Spend, SpendRate};
+use crate::cache::write_atomic;
 use crate::evaluator::EvalScores;
+use crate::profile::Profile;
 use crate::runner::RunResult;
+use crate::sandbox::{SettingsScope, SettingsTemplate, SidecarGeneration};
 use crate::tasks::Task;
 
 /// Format for report output
@@ -18,9 +25,65 @@ pub enum ReportFormat {
     Both,
 }
 
+impl ReportFormat {
+    /// Format implied by an output file extension (`.json` / `.md`), if any.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(ReportFormat::Json),
+            "md" | "markdown" => Some(ReportFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Resolve the format for `--output`: a file path implies its own format,
+    /// a directory falls back to `requested` (or `Both`).
+    pub fn resolve(output: Option<&Path>, requested: Option<Self>) -> anyhow::Result<Self> {
+        let implied = output.and_then(Self::from_extension);
+        match (implied, requested) {
+            (None, requested) => Ok(requested.unwrap_or_default()),
+            (Some(implied), None) => Ok(implied),
+            (Some(implied), Some(requested)) if implied == requested => Ok(implied),
+            (Some(_), Some(requested)) => anyhow::bail!(
+                "--format {:?} cannot be written to single file {}; use a directory for --output",
+                requested,
+                output.map(|p| p.display().to_string()).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Version of the on-disk report and cache formats written by this build.
+///
+/// Files written before versioning existed deserialize as v0; every field
+/// added since then is defaulted, so v0 migrates by stamping the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Check a stored `found` schema version against this build.
+///
+/// Returns a note when the file needs migrating, and an error when it was
+/// written by a newer, incompatible fmm-bench.
+pub(crate) fn check_schema_version(found: u32, what: &str) -> anyhow::Result<Option<String>> {
+    match found {
+        SCHEMA_VERSION => Ok(None),
+        0 => Ok(Some(format!(
+            "{} has no schema version (treated as v0); migrating to v{}",
+            what, SCHEMA_VERSION
+        ))),
+        _ => anyhow::bail!(
+            "{} from incompatible version: schema v{}, this fmm-bench reads up to v{}",
+            what,
+            found,
+            SCHEMA_VERSION
+        ),
+    }
+}
+
 /// Complete comparison report
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComparisonReport {
+    /// On-disk format version (see [`SCHEMA_VERSION`]); 0 when absent
+    #[serde(default)]
+    pub schema_version: u32,
     /// Job ID
     pub job_id: String,
     /// Repository URL
@@ -35,6 +98,227 @@ pub struct ComparisonReport {
     pub task_results: Vec<TaskComparison>,
     /// Aggregated metrics
     pub summary: ComparisonSummary,
+    /// FMM sidecar generation outcome (attempts, fmm stderr, sidecar count)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fmm_setup: Option<SidecarGeneration>,
+    /// Seeded task shuffle applied to this run (both conditions share the order)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_order: Option<TaskOrder>,
+    /// GitHub labels of the benchmarked issue (issue runs only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Estimated tokens of the issue body as sent in the prompt (issue runs
+    /// only; after any `--max-body-tokens` truncation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_body_tokens: Option<usize>,
+    /// Bytes appended to the control condition's system prompt (ablation)
+    #[serde(default)]
+    pub control_context_bytes: usize,
+    /// Bytes appended to the FMM condition's system prompt (sidecar instructions)
+    #[serde(default)]
+    pub fmm_context_bytes: usize,
+    /// Tasks whose CLI run failed outright (after retries), left out of the
+    /// results and summary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errored_tasks: Vec<ErroredTask>,
+    /// `--allowedTools` of the control runner, for checking fairness
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub control_allowed_tools: Vec<String>,
+    /// `--allowedTools` of the FMM runner (may add `mcp__*` tools)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fmm_allowed_tools: Vec<String>,
+    /// Per-stage wall-clock timings (`--profile`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+    /// The job hit its `--max-wall-secs` deadline; remaining tasks never ran
+    #[serde(default)]
+    pub deadline_truncated: bool,
+    /// Settings template merged into the workspaces (`--settings-template`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings_template: Option<SettingsTemplate>,
+    /// Model id both conditions ran with (after alias expansion)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub model: String,
+    /// `--model` name the id was expanded from via `--model-alias-file`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_alias: Option<String>,
+    /// Wall-clock time of the whole job, from clone to the last task
+    #[serde(default)]
+    pub wall_ms: u64,
+    /// What the job's fresh runs spent (None for reports saved before this
+    /// was tracked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spent: Option<Spend>,
+}
+
+/// The same issue run against two fmm builds (`--fmm-bin-a`/`--fmm-bin-b`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FmmRegressionReport {
+    pub bin_a: String,
+    pub bin_b: String,
+    /// Comparison run with `bin_a`
+    pub a: ComparisonReport,
+    /// Comparison run with `bin_b`
+    pub b: ComparisonReport,
+}
+
+impl FmmRegressionReport {
+    pub fn new(bin_a: &Path, bin_b: &Path, a: ComparisonReport, b: ComparisonReport) -> Self {
+        Self {
+            bin_a: bin_a.display().to_string(),
+            bin_b: bin_b.display().to_string(),
+            a,
+            b,
+        }
+    }
+
+    /// Change in tool-call reduction from A to B, in percentage points
+    /// (positive = B saved more).
+    pub fn tool_call_delta(&self) -> f64 {
+        self.b.tool_call_reduction() - self.a.tool_call_reduction()
+    }
+
+    /// Change in cost reduction from A to B, in percentage points.
+    pub fn cost_delta(&self) -> f64 {
+        self.b.cost_reduction() - self.a.cost_reduction()
+    }
+
+    /// Whether B's fmm produced better tool-call savings than A's.
+    pub fn b_improved(&self) -> bool {
+        self.tool_call_delta() > 0.0
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        md.push_str("# fmm Regression\n\n");
+        md.push_str(&format!("**A:** `{}`\n", self.bin_a));
+        md.push_str(&format!("**B:** `{}`\n\n", self.bin_b));
+        md.push_str("| Metric | A | B | Change |\n");
+        md.push_str("|--------|---|---|--------|\n");
+        md.push_str(&format!(
+            "| Tool call reduction | {:.1}% | {:.1}% | {:+.1} pp |\n",
+            self.a.tool_call_reduction(),
+            self.b.tool_call_reduction(),
+            self.tool_call_delta()
+        ));
+        md.push_str(&format!(
+            "| Cost reduction | {:.1}% | {:.1}% | {:+.1} pp |\n",
+            self.a.cost_reduction(),
+            self.b.cost_reduction(),
+            self.cost_delta()
+        ));
+        md.push_str(&format!(
+            "| FMM win rate | {:.0}% | {:.0}% | |\n\n",
+            self.a.fmm_win_rate() * 100.0,
+            self.b.fmm_win_rate() * 100.0
+        ));
+        md.push_str(if self.b_improved() {
+            "**B improved tool-call savings over A.**\n"
+        } else {
+            "**B did not improve tool-call savings over A.**\n"
+        });
+        md
+    }
+}
+
+/// Expected FMM tool-call reduction (%) per task id, committed as e.g.
+/// `baseline.json` to gate CI on FMM savings (`--compare-with-baseline-json`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReductionBaseline(pub BTreeMap<String, f64>);
+
+/// A baseline task whose reduction fell more than the tolerance short.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRegression {
+    pub task_id: String,
+    pub expected_pct: f64,
+    /// Observed reduction (mean over repeated runs); None if the task is
+    /// missing from the report
+    pub actual_pct: Option<f64>,
+}
+
+impl ReductionBaseline {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline JSON: {}", path.display()))
+    }
+
+    /// Baseline tasks whose tool-call reduction in `report` is more than
+    /// `tolerance_pct` percentage points below the expected value. Tasks
+    /// missing from the report count as regressed; tasks the baseline
+    /// doesn't list are ignored.
+    pub fn regressions(
+        &self,
+        report: &ComparisonReport,
+        tolerance_pct: f64,
+    ) -> Vec<TaskRegression> {
+        self.0
+            .iter()
+            .filter_map(|(task_id, &expected_pct)| {
+                let observed: Vec<f64> = report
+                    .task_results
+                    .iter()
+                    .filter(|t| &t.task_id == task_id)
+                    .map(|t| t.savings.tool_calls_reduction_pct)
+                    .collect();
+                let actual_pct = (!observed.is_empty())
+                    .then(|| observed.iter().sum::<f64>() / observed.len() as f64);
+                let regressed = match actual_pct {
+                    Some(actual) => actual < expected_pct - tolerance_pct,
+                    None => true,
+                };
+                regressed.then(|| TaskRegression {
+                    task_id: task_id.clone(),
+                    expected_pct,
+                    actual_pct,
+                })
+            })
+            .collect()
+    }
+}
+
+impl TaskRegression {
+    /// One-line description, e.g. "issue-12: 18.0% vs expected 40.0%".
+    pub fn describe(&self) -> String {
+        match self.actual_pct {
+            Some(actual) => format!(
+                "{}: {:.1}% vs expected {:.1}%",
+                self.task_id, actual, self.expected_pct
+            ),
+            None => format!(
+                "{}: missing from report (expected {:.1}%)",
+                self.task_id, self.expected_pct
+            ),
+        }
+    }
+}
+
+/// A task excluded from the comparison because a condition errored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErroredTask {
+    pub task_id: String,
+    /// Condition that failed ("control" or "fmm")
+    pub variant: String,
+    pub error: String,
+}
+
+/// Seed and resulting task order for a shuffled run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOrder {
+    pub seed: u64,
+    pub task_ids: Vec<String>,
+}
+
+/// A stored grade that changed on re-grading (`regrade`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeChange {
+    pub task_id: String,
+    /// "control" or "fmm"
+    pub variant: String,
+    pub before: String,
+    pub after: String,
 }
 
 /// Comparison for a single task
@@ -56,6 +340,104 @@ pub struct TaskComparison {
     /// Post-run evaluation of FMM variant
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub fmm_eval: Option<EvalScores>,
+    /// Either condition did no real work (see `RunResult::is_degenerate`)
+    #[serde(default)]
+    pub degenerate: bool,
+    /// Control and FMM received different prompts — the comparison is invalid
+    #[serde(default)]
+    pub prompt_mismatch: bool,
+    /// Either condition was cut off by the turn cap
+    #[serde(default)]
+    pub turn_limited: bool,
+    /// Sidecars were generated but the FMM run read none and made no fmm
+    /// MCP calls, so this pair says nothing about fmm
+    #[serde(default)]
+    pub treatment_unused: bool,
+    /// Spread across `task_repeats` runs, when the task was repeated; the
+    /// `control`/`fmm` results are then the repeats' averages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeats: Option<RepeatSpread>,
+}
+
+/// Run-to-run spread of a task repeated within one comparison.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepeatSpread {
+    pub runs: usize,
+    pub control_tool_calls: MetricSpread,
+    pub fmm_tool_calls: MetricSpread,
+    pub control_cost: MetricSpread,
+    pub fmm_cost: MetricSpread,
+}
+
+impl RepeatSpread {
+    pub fn from_runs(control: &[RunResult], fmm: &[RunResult]) -> Self {
+        let tools = |runs: &[RunResult]| {
+            metric_spread(&runs.iter().map(|r| r.tool_calls as f64).collect::<Vec<_>>())
+        };
+        let cost = |runs: &[RunResult]| {
+            metric_spread(&runs.iter().map(|r| r.total_cost_usd).collect::<Vec<_>>())
+        };
+        Self {
+            runs: control.len(),
+            control_tool_calls: tools(control),
+            fmm_tool_calls: tools(fmm),
+            control_cost: cost(control),
+            fmm_cost: cost(fmm),
+        }
+    }
+}
+
+impl TaskComparison {
+    /// Pair a task's control and FMM results, computing savings and flags.
+    pub fn new(
+        task: Task,
+        control: RunResult,
+        fmm: RunResult,
+        control_eval: Option<EvalScores>,
+        fmm_eval: Option<EvalScores>,
+    ) -> Self {
+        let savings = calculate_savings(&control, &fmm);
+        let degenerate = is_degenerate_run(&control, control_eval.as_ref())
+            || is_degenerate_run(&fmm, fmm_eval.as_ref());
+        // Only system prompt / MCP may differ between conditions.
+        // Empty hashes come from results recorded before hashing existed.
+        let prompt_mismatch = !control.prompt_hash.is_empty()
+            && !fmm.prompt_hash.is_empty()
+            && control.prompt_hash != fmm.prompt_hash;
+        let turn_limited = control.hit_turn_limit || fmm.hit_turn_limit;
+        Self {
+            task_id: task.id,
+            task_name: task.name,
+            control,
+            fmm,
+            savings,
+            control_eval,
+            fmm_eval,
+            degenerate,
+            prompt_mismatch,
+            turn_limited,
+            treatment_unused: false,
+            repeats: None,
+        }
+    }
+
+    /// Append this comparison as one JSON line to `path` (created if absent),
+    /// so a crashed run still leaves the tasks it finished.
+    pub fn append_ndjson(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to append to {}", path.display()))?;
+        Ok(())
+    }
 }
 
 /// Savings metrics for a task
@@ -90,6 +472,25 @@ pub struct ComparisonSummary {
     pub fmm_totals: AggregateMetrics,
     /// Overall savings
     pub overall_savings: OverallSavings,
+    /// Degenerate tasks left out of this summary (with `--exclude-degenerate`)
+    #[serde(default)]
+    pub degenerate_excluded: u32,
+    /// Unused-treatment tasks left out of this summary (with
+    /// `--require-treatment-used`)
+    #[serde(default)]
+    pub treatment_unused_excluded: u32,
+}
+
+impl ComparisonSummary {
+    /// Total spend on the control condition (USD)
+    pub fn control_spend(&self) -> f64 {
+        self.control_totals.total_cost_usd
+    }
+
+    /// Total spend on the FMM condition (USD)
+    pub fn fmm_spend(&self) -> f64 {
+        self.fmm_totals.total_cost_usd
+    }
 }
 
 /// Aggregated metrics across all tasks
@@ -138,22 +539,14 @@ impl ComparisonReport {
         let task_results: Vec<TaskComparison> = results
             .into_iter()
             .map(|(task, control, fmm, control_eval, fmm_eval)| {
-                let savings = calculate_savings(&control, &fmm);
-                TaskComparison {
-                    task_id: task.id,
-                    task_name: task.name,
-                    control,
-                    fmm,
-                    savings,
-                    control_eval,
-                    fmm_eval,
-                }
+                TaskComparison::new(task, control, fmm, control_eval, fmm_eval)
             })
             .collect();
 
         let summary = Self::calculate_summary(&task_results);
 
         Self {
+            schema_version: SCHEMA_VERSION,
             job_id,
             repo_url,
             commit_sha,
@@ -161,9 +554,94 @@ impl ComparisonReport {
             timestamp,
             task_results,
             summary,
+            fmm_setup: None,
+            task_order: None,
+            labels: vec![],
+            issue_body_tokens: None,
+            control_context_bytes: 0,
+            fmm_context_bytes: 0,
+            errored_tasks: vec![],
+            control_allowed_tools: vec![],
+            fmm_allowed_tools: vec![],
+            profile: None,
+            deadline_truncated: false,
+            settings_template: None,
+            model: String::new(),
+            model_alias: None,
+            wall_ms: 0,
+            spent: None,
         }
     }
 
+    /// Recompute the summary without degenerate tasks.
+    ///
+    /// The tasks stay in `task_results` (flagged) so the report still shows them.
+    pub fn exclude_degenerate(&mut self) {
+        let unused = self.summary.treatment_unused_excluded > 0;
+        self.summarize_excluding(true, unused);
+    }
+
+    /// Flag tasks whose FMM run never touched the treatment: sidecars were
+    /// generated (per `fmm_setup`), yet it read none and made no fmm MCP
+    /// calls. Returns the flagged task ids.
+    pub fn flag_unused_treatment(&mut self) -> Vec<String> {
+        let generated = self
+            .fmm_setup
+            .as_ref()
+            .is_some_and(|setup| setup.sidecar_count > 0);
+        let mut flagged = vec![];
+        for task in &mut self.task_results {
+            let usage = &task.fmm.fmm_usage;
+            task.treatment_unused =
+                generated && usage.sidecars_read == 0 && usage.mcp_tool_calls == 0;
+            if task.treatment_unused {
+                flagged.push(task.task_id.clone());
+            }
+        }
+        flagged
+    }
+
+    /// Recompute the summary without unused-treatment tasks (see
+    /// `flag_unused_treatment`); like `exclude_degenerate`, they stay listed.
+    pub fn exclude_unused_treatment(&mut self) {
+        let degenerate = self.summary.degenerate_excluded > 0;
+        self.summarize_excluding(degenerate, true);
+    }
+
+    /// Tasks the summary covers: `task_results` minus any excluded by
+    /// `exclude_degenerate` or `exclude_unused_treatment`.
+    pub fn summarized_tasks(&self) -> impl Iterator<Item = &TaskComparison> {
+        let degenerate = self.summary.degenerate_excluded > 0;
+        let treatment_unused = self.summary.treatment_unused_excluded > 0;
+        self.task_results.iter().filter(move |t| {
+            !(degenerate && t.degenerate || treatment_unused && t.treatment_unused)
+        })
+    }
+
+    /// Summarize the tasks left after the requested exclusions. A count of 0
+    /// means nothing was excluded for that reason, so earlier exclusions are
+    /// kept by re-applying any with a non-zero count.
+    fn summarize_excluding(&mut self, degenerate: bool, treatment_unused: bool) {
+        let drop_degenerate = |t: &TaskComparison| degenerate && t.degenerate;
+        let drop =
+            |t: &TaskComparison| drop_degenerate(t) || treatment_unused && t.treatment_unused;
+        let kept: Vec<TaskComparison> = self
+            .task_results
+            .iter()
+            .filter(|t| !drop(t))
+            .cloned()
+            .collect();
+        let degenerate_excluded = self
+            .task_results
+            .iter()
+            .filter(|t| drop_degenerate(t))
+            .count();
+        let excluded = self.task_results.len() - kept.len();
+        self.summary = Self::calculate_summary(&kept);
+        self.summary.degenerate_excluded = degenerate_excluded as u32;
+        self.summary.treatment_unused_excluded = (excluded - degenerate_excluded) as u32;
+    }
+
     fn calculate_summary(task_results: &[TaskComparison]) -> ComparisonSummary {
         let tasks_run = task_results.len() as u32;
 
@@ -259,51 +737,224 @@ impl ComparisonReport {
             control_totals,
             fmm_totals,
             overall_savings,
+            degenerate_excluded: 0,
+            treatment_unused_excluded: 0,
         }
     }
 
-    /// Print summary to stdout
+    /// Print summary to stdout (or stderr when console output is routed there)
     pub fn print_summary(&self) {
+        let result = if crate::console::is_routed_to_stderr() {
+            self.write_summary(&mut std::io::stderr())
+        } else {
+            self.write_summary(&mut std::io::stdout())
+        };
+        if let Err(e) = result {
+            eprintln!("Warning: Failed to print summary: {}", e);
+        }
+    }
+
+    /// Write the end-of-run output: the summary (and stage timings) wherever
+    /// `summaryln!` would print it, then with `json_stdout` the JSON report to
+    /// `stdout`. With `--stdout` the summary is routed to `stderr`, so stdout
+    /// carries nothing but the JSON.
+    pub fn write_results(
+        &self,
+        stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+        json_stdout: bool,
+        compact: bool,
+    ) -> anyhow::Result<()> {
+        {
+            let w: &mut dyn Write = match crate::console::summary_stream() {
+                crate::console::Stream::Stderr => &mut *stderr,
+                _ => &mut *stdout,
+            };
+            if compact {
+                writeln!(w, "{}", self.compact_summary())?;
+            } else {
+                writeln!(w, "\n{}", "=".repeat(60).dimmed())?;
+                writeln!(w, "{}", "COMPARISON RESULTS".green().bold())?;
+                writeln!(w, "{}", "=".repeat(60).dimmed())?;
+                self.write_summary(w)?;
+                if let Some(rate) = self.spend_rate() {
+                    writeln!(w, "\n  Spend rate: {}", rate.describe())?;
+                }
+            }
+            if let Some(ref profile) = self.profile {
+                writeln!(w, "\n{}", "Stage timings".bold())?;
+                writeln!(w, "{}", profile.to_table().trim_end())?;
+            }
+        }
+
+        if json_stdout {
+            writeln!(stdout, "{}", self.to_json()?)?;
+        }
+        Ok(())
+    }
+
+    /// Overall tool-call reduction, in percent (positive = FMM used fewer).
+    pub fn tool_call_reduction(&self) -> f64 {
+        self.summary.overall_savings.tool_calls_reduction_pct
+    }
+
+    /// Overall cost reduction, in percent (positive = FMM was cheaper).
+    pub fn cost_reduction(&self) -> f64 {
+        self.summary.overall_savings.cost_reduction_pct
+    }
+
+    /// Share of tasks FMM won, from 0.0 to 1.0 (0.0 when no tasks ran).
+    pub fn fmm_win_rate(&self) -> f64 {
+        if self.summary.tasks_run == 0 {
+            0.0
+        } else {
+            self.summary.fmm_wins as f64 / self.summary.tasks_run as f64
+        }
+    }
+
+    /// Summarized task with the largest tool-call reduction.
+    pub fn best_task(&self) -> Option<&TaskComparison> {
+        self.summarized_tasks().max_by(|a, b| {
+            a.savings
+                .tool_calls_reduction_pct
+                .total_cmp(&b.savings.tool_calls_reduction_pct)
+        })
+    }
+
+    /// Summarized task with the smallest (most negative) tool-call reduction.
+    pub fn worst_task(&self) -> Option<&TaskComparison> {
+        self.summarized_tasks().min_by(|a, b| {
+            a.savings
+                .tool_calls_reduction_pct
+                .total_cmp(&b.savings.tool_calls_reduction_pct)
+        })
+    }
+
+    /// One dense line for scanning many runs: job, tasks, FMM wins,
+    /// tool-call and cost reduction, and control→FMM grades per task.
+    pub fn compact_summary(&self) -> String {
+        let s = &self.summary;
+        let grades: Vec<String> = self
+            .task_results
+            .iter()
+            .map(|t| {
+                format!(
+                    "{}→{}",
+                    t.control_eval.as_ref().map_or("-", |e| &e.grade),
+                    t.fmm_eval.as_ref().map_or("-", |e| &e.grade)
+                )
+            })
+            .collect();
+        let mut line = format!(
+            "{} | {} task(s) | FMM wins {}/{} | tools {:+.1}% | cost {:+.1}% | grades {}",
+            self.job_id,
+            s.tasks_run,
+            s.fmm_wins,
+            s.tasks_run,
+            -s.overall_savings.tool_calls_reduction_pct,
+            -s.overall_savings.cost_reduction_pct,
+            grades.join(" ")
+        );
+        if !self.prompt_mismatches().is_empty() {
+            line.push_str(" | PROMPT MISMATCH");
+        }
+        line
+    }
+
+    /// Tasks whose control and FMM prompts diverged.
+    pub fn prompt_mismatches(&self) -> Vec<&str> {
+        self.task_results
+            .iter()
+            .filter(|t| t.prompt_mismatch)
+            .map(|t| t.task_id.as_str())
+            .collect()
+    }
+
+    /// Fresh-run cost and tool calls (see `Spend`) per wall-clock
+    /// hour/minute of the job.
+    pub fn spend_rate(&self) -> Option<SpendRate> {
+        self.spent?.rate(self.wall_ms)
+    }
+
+    /// Tasks where a condition hit the turn cap.
+    pub fn turn_limited(&self) -> Vec<&str> {
+        self.task_results
+            .iter()
+            .filter(|t| t.turn_limited)
+            .map(|t| t.task_id.as_str())
+            .collect()
+    }
+
+    /// Write the human-readable summary to `w`
+    pub fn write_summary(&self, w: &mut dyn Write) -> std::io::Result<()> {
         let s = &self.summary;
 
-        println!("\n{}", "Summary".yellow().bold());
-        println!(
+        let mismatches = self.prompt_mismatches();
+        if !mismatches.is_empty() {
+            writeln!(
+                w,
+                "\n{} {}",
+                "!!".red().bold(),
+                format!(
+                    "PROMPT MISMATCH: control and FMM received different prompts for {}; results are not comparable",
+                    mismatches.join(", ")
+                )
+                .red()
+                .bold()
+            )?;
+        }
+
+        writeln!(w, "\n{}", "Summary".yellow().bold())?;
+        writeln!(
+            w,
             "  Tasks run: {} | FMM wins: {} | Control wins: {} | Ties: {}",
             s.tasks_run.to_string().white().bold(),
             s.fmm_wins.to_string().green().bold(),
             s.control_wins.to_string().red(),
             s.ties.to_string().dimmed()
-        );
+        )?;
 
-        println!("\n{}", "Tool Calls".yellow().bold());
-        println!(
+        writeln!(w, "\n{}", "Tool Calls".yellow().bold())?;
+        writeln!(
+            w,
             "  Control: {} | FMM: {} | Reduction: {}",
             s.control_totals.total_tool_calls.to_string().white(),
             s.fmm_totals.total_tool_calls.to_string().green(),
             format!("{:.1}%", s.overall_savings.tool_calls_reduction_pct)
                 .green()
                 .bold()
-        );
+        )?;
 
-        println!("\n{}", "Cost".yellow().bold());
-        println!(
+        writeln!(w, "\n{}", "Cost".yellow().bold())?;
+        writeln!(
+            w,
             "  Control: ${:.4} | FMM: ${:.4} | Savings: {}",
             s.control_totals.total_cost_usd,
             s.fmm_totals.total_cost_usd,
             format!("{:.1}%", s.overall_savings.cost_reduction_pct)
                 .green()
                 .bold()
-        );
+        )?;
+        writeln!(
+            w,
+            "  Spend: control ${:.4} + fmm ${:.4} = {}",
+            s.control_spend(),
+            s.fmm_spend(),
+            format!("${:.4}", s.control_spend() + s.fmm_spend())
+                .white()
+                .bold()
+        )?;
 
-        println!("\n{}", "Per Task Breakdown".yellow().bold());
-        println!(
+        writeln!(w, "\n{}", "Per Task Breakdown".yellow().bold())?;
+        writeln!(
+            w,
             "  {:20} {:>10} {:>10} {:>12}",
             "Task".dimmed(),
             "Control".dimmed(),
             "FMM".dimmed(),
             "Reduction".dimmed()
-        );
-        println!("  {}", "-".repeat(54).dimmed());
+        )?;
+        writeln!(w, "  {}", "-".repeat(54).dimmed())?;
 
         for task in &self.task_results {
             let reduction = if task.savings.tool_calls_reduction_pct > 0.0 {
@@ -318,32 +969,130 @@ impl ComparisonReport {
                 "0%".dimmed().to_string()
             };
 
-            println!(
+            writeln!(
+                w,
                 "  {:20} {:>10} {:>10} {:>12}",
                 truncate(&task.task_name, 20),
                 task.control.tool_calls,
                 task.fmm.tool_calls,
                 reduction
-            );
+            )?;
         }
+
+        if !self.errored_tasks.is_empty() {
+            let ids: Vec<&str> = self
+                .errored_tasks
+                .iter()
+                .map(|t| t.task_id.as_str())
+                .collect();
+            writeln!(
+                w,
+                "\n  {} Errored (excluded): {}",
+                "!".red(),
+                ids.join(", ")
+            )?;
+        }
+
+        if self.deadline_truncated {
+            writeln!(
+                w,
+                "\n  {} Stopped at the wall-clock deadline; results are partial",
+                "!".yellow()
+            )?;
+        }
+
+        let turn_limited = self.turn_limited();
+        if !turn_limited.is_empty() {
+            writeln!(
+                w,
+                "\n  {} Hit the turn limit (unfinished runs): {}",
+                "!".yellow(),
+                turn_limited.join(", ")
+            )?;
+        }
+
+        Ok(())
     }
 
-    /// Save report to file(s)
-    pub fn save(&self, output_dir: &Path, format: ReportFormat) -> anyhow::Result<Vec<String>> {
+    /// Re-grade every stored evaluation with `rubric` (e.g.
+    /// `evaluator::standard_grade` after the grading logic changed), from the
+    /// recorded diff stats and test/build exit codes, without re-running
+    /// anything. Returns the grades that changed.
+    pub fn regrade_with(&mut self, rubric: impl Fn(&EvalScores) -> String) -> Vec<GradeChange> {
+        let mut changes = vec![];
+        for task in &mut self.task_results {
+            for (variant, eval) in [
+                ("control", &mut task.control_eval),
+                ("fmm", &mut task.fmm_eval),
+            ] {
+                let Some(eval) = eval else {
+                    continue;
+                };
+                let before = eval.grade.clone();
+                eval.regrade_with(&rubric);
+                if eval.grade != before {
+                    changes.push(GradeChange {
+                        task_id: task.task_id.clone(),
+                        variant: variant.to_string(),
+                        before,
+                        after: eval.grade.clone(),
+                    });
+                }
+            }
+        }
+        changes
+    }
+
+    /// Serialize the report as pretty-printed JSON
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a JSON report, migrating older schema versions and rejecting
+    /// reports written by a newer fmm-bench.
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        let mut report: ComparisonReport = serde_json::from_str(content)?;
+        let what = format!("Report {}", report.job_id);
+        if let Some(note) = check_schema_version(report.schema_version, &what)? {
+            eprintln!("{} {}", "Note:".yellow(), note);
+            report.schema_version = SCHEMA_VERSION;
+        }
+        Ok(report)
+    }
+
+    /// Save report to file(s).
+    ///
+    /// `output` is a directory (files named by job id) unless it has a `.json`
+    /// or `.md` extension, in which case the report is written to exactly that
+    /// path in the matching format.
+    pub fn save(&self, output: &Path, format: ReportFormat) -> anyhow::Result<Vec<String>> {
+        if ReportFormat::from_extension(output).is_some() {
+            let format = ReportFormat::resolve(Some(output), Some(format))?;
+            if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            let content = match format {
+                ReportFormat::Json => self.to_json()?,
+                _ => self.to_markdown(),
+            };
+            write_atomic(output, content.as_bytes())?;
+            return Ok(vec![output.display().to_string()]);
+        }
+
+        let output_dir = output;
         fs::create_dir_all(output_dir)?;
         let mut saved_files = vec![];
 
         if format == ReportFormat::Json || format == ReportFormat::Both {
             let json_path = output_dir.join(format!("{}.json", self.job_id));
-            let json = serde_json::to_string_pretty(self)?;
-            fs::write(&json_path, json)?;
+            write_atomic(&json_path, self.to_json()?.as_bytes())?;
             saved_files.push(json_path.display().to_string());
         }
 
         if format == ReportFormat::Markdown || format == ReportFormat::Both {
             let md_path = output_dir.join(format!("{}.md", self.job_id));
             let markdown = self.to_markdown();
-            fs::write(&md_path, markdown)?;
+            write_atomic(&md_path, markdown.as_bytes())?;
             saved_files.push(md_path.display().to_string());
         }
 
@@ -361,6 +1110,95 @@ impl ComparisonReport {
         md.push_str(&format!("**Branch:** {}\n", self.branch));
         md.push_str(&format!("**Timestamp:** {}\n\n", self.timestamp));
 
+        let mismatches = self.prompt_mismatches();
+        if !mismatches.is_empty() {
+            md.push_str(&format!(
+                "> **Warning: prompt mismatch.** Control and FMM received different prompts for: {}. \
+                 These comparisons are invalid.\n\n",
+                mismatches.join(", ")
+            ));
+        }
+
+        if !self.labels.is_empty() {
+            md.push_str(&format!("**Labels:** {}\n\n", self.labels.join(", ")));
+        }
+
+        if self.control_context_bytes > 0 || self.fmm_context_bytes > 0 {
+            md.push_str(&format!(
+                "**Appended system prompt:** control {} bytes, FMM {} bytes\n\n",
+                self.control_context_bytes, self.fmm_context_bytes
+            ));
+        }
+
+        if !self.control_allowed_tools.is_empty() || !self.fmm_allowed_tools.is_empty() {
+            if self.control_allowed_tools == self.fmm_allowed_tools {
+                md.push_str(&format!(
+                    "**Allowed tools (both):** {}\n\n",
+                    self.control_allowed_tools.join(", ")
+                ));
+            } else {
+                md.push_str(&format!(
+                    "**Allowed tools:** control {} | FMM {}\n\n",
+                    self.control_allowed_tools.join(", "),
+                    self.fmm_allowed_tools.join(", ")
+                ));
+            }
+        }
+
+        if !self.model.is_empty() {
+            match self.model_alias {
+                Some(ref alias) => md.push_str(&format!(
+                    "**Model:** {} (alias `{}`)\n\n",
+                    self.model, alias
+                )),
+                None => md.push_str(&format!("**Model:** {}\n\n", self.model)),
+            }
+        }
+
+        if let Some(ref order) = self.task_order {
+            md.push_str(&format!(
+                "**Task order:** seed {} ({})\n\n",
+                order.seed,
+                order.task_ids.join(", ")
+            ));
+        }
+
+        if let Some(ref template) = self.settings_template {
+            let scope = match template.scope {
+                SettingsScope::Fmm => "FMM only",
+                SettingsScope::Both => "both conditions",
+            };
+            md.push_str(&format!(
+                "**Settings template:** `{}` ({})\n\n",
+                template.path.display(),
+                scope
+            ));
+        }
+
+        if let Some(ref setup) = self.fmm_setup {
+            md.push_str(&format!(
+                "**Sidecars:** {} (fmm generate attempts: {})\n\n",
+                setup.sidecar_count, setup.attempts
+            ));
+            if setup.sidecar_count == 0 {
+                md.push_str("> **Warning:** no sidecars were generated; the FMM condition ran without them.\n\n");
+            }
+            if !setup.missing_artifacts.is_empty() {
+                md.push_str(&format!(
+                    "> **Warning:** fmm init did not install {}; the FMM condition ran with a partial treatment.\n\n",
+                    setup
+                        .missing_artifacts
+                        .iter()
+                        .map(|a| format!("`{}`", a))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            for stderr in &setup.stderr {
+                md.push_str(&format!("> fmm stderr: {}\n\n", stderr.replace('\n', " ")));
+            }
+        }
+
         md.push_str("## Summary\n\n");
         md.push_str("| Metric | Control | FMM | Reduction |\n");
         md.push_str("|--------|---------|-----|----------|\n");
@@ -398,11 +1236,76 @@ impl ComparisonReport {
             "**FMM Wins:** {} / {} tasks ({:.0}%)\n\n",
             s.fmm_wins, s.tasks_run, win_percentage
         ));
+        if s.degenerate_excluded > 0 {
+            md.push_str(&format!(
+                "*{} degenerate task(s) excluded from the summary.*\n\n",
+                s.degenerate_excluded
+            ));
+        }
+        let unused = self
+            .task_results
+            .iter()
+            .filter(|t| t.treatment_unused)
+            .count();
+        if s.treatment_unused_excluded > 0 {
+            md.push_str(&format!(
+                "*{} task(s) whose FMM run never used the treatment excluded from the summary.*\n\n",
+                s.treatment_unused_excluded
+            ));
+        } else if unused > 0 {
+            md.push_str(&format!(
+                "*{} task(s) whose FMM run never used the treatment (no sidecar reads or fmm MCP calls) are included; `--require-treatment-used` leaves them out.*\n\n",
+                unused
+            ));
+        }
+        if !self.errored_tasks.is_empty() {
+            md.push_str(&format!(
+                "*{} task(s) errored and were excluded from the results:*\n\n",
+                self.errored_tasks.len()
+            ));
+            for t in &self.errored_tasks {
+                md.push_str(&format!(
+                    "- {} ({}): {}\n",
+                    t.task_id,
+                    t.variant,
+                    t.error.lines().next().unwrap_or("")
+                ));
+            }
+            md.push('\n');
+        }
+        if self.deadline_truncated {
+            md.push_str("*Stopped at the wall-clock deadline (`--max-wall-secs`); results are partial.*\n\n");
+        }
+        let turn_limited = self.turn_limited();
+        if !turn_limited.is_empty() {
+            md.push_str(&format!(
+                "*{} task(s) hit the turn limit; their metrics describe unfinished runs: {}.*\n\n",
+                turn_limited.len(),
+                turn_limited.join(", ")
+            ));
+        }
 
         md.push_str("## Task Details\n\n");
 
         for task in &self.task_results {
             md.push_str(&format!("### {}\n\n", task.task_name));
+            if task.degenerate {
+                md.push_str("> **Degenerate:** a condition succeeded without doing any work (no tool calls, no response or diff).\n\n");
+            }
+            if task.treatment_unused {
+                md.push_str("> **Treatment unused:** the FMM run read no sidecars and made no fmm MCP calls.\n\n");
+            }
+            if task.turn_limited {
+                let which = match (task.control.hit_turn_limit, task.fmm.hit_turn_limit) {
+                    (true, true) => "Both conditions",
+                    (true, false) => "Control",
+                    _ => "FMM",
+                };
+                md.push_str(&format!(
+                    "> **Turn limit:** {} hit the turn cap before finishing.\n\n",
+                    which
+                ));
+            }
             md.push_str("| Metric | Control | FMM |\n");
             md.push_str("|--------|---------|-----|\n");
             md.push_str(&format!(
@@ -414,8 +1317,38 @@ impl ComparisonReport {
                 task.control.read_calls, task.fmm.read_calls
             ));
             md.push_str(&format!(
-                "| Cost | ${:.4} | ${:.4} |\n",
-                task.control.total_cost_usd, task.fmm.total_cost_usd
+                "| Cost | ${:.4}{} | ${:.4}{} |\n",
+                task.control.total_cost_usd,
+                if task.control.cost_estimated {
+                    " (est.)"
+                } else {
+                    ""
+                },
+                task.fmm.total_cost_usd,
+                if task.fmm.cost_estimated {
+                    " (est.)"
+                } else {
+                    ""
+                }
+            ));
+            md.push_str(&format!(
+                "| Tokens (in / out / cache) | {} / {} / {} | {} / {} / {} |\n",
+                task.control.input_tokens,
+                task.control.output_tokens,
+                task.control.cache_read_tokens,
+                task.fmm.input_tokens,
+                task.fmm.output_tokens,
+                task.fmm.cache_read_tokens
+            ));
+            let hit_rate = |r: &RunResult| {
+                r.cache_hit_rate()
+                    .map(|rate| format!("{:.0}%", rate * 100.0))
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            md.push_str(&format!(
+                "| Cache Hit Rate | {} | {} |\n",
+                hit_rate(&task.control),
+                hit_rate(&task.fmm)
             ));
             md.push_str(&format!(
                 "| Duration | {}ms | {}ms |\n",
@@ -427,7 +1360,17 @@ impl ComparisonReport {
             let fn_ = &task.fmm.navigation;
             md.push_str(&format!(
                 "| Files Read | {} | {} |\n",
-                cn.unique_files_read, fn_.unique_files_read
+                task.control.unique_files_accessed(),
+                task.fmm.unique_files_accessed()
+            ));
+            md.push_str(&format!(
+                "| Repeat Reads | {} | {} |\n",
+                task.control.repeat_reads(),
+                task.fmm.repeat_reads()
+            ));
+            md.push_str(&format!(
+                "| Repeated Tool Calls | {} | {} |\n",
+                task.control.repeated_tool_invocations, task.fmm.repeated_tool_invocations
             ));
             md.push_str(&format!(
                 "| Files Edited | {} | {} |\n",
@@ -455,6 +1398,22 @@ impl ComparisonReport {
                 cn.implementation_turns, fn_.implementation_turns
             ));
 
+            // Tool call categories
+            let cc = &task.control.tool_categories;
+            let fc = &task.fmm.tool_categories;
+            md.push_str(&format!(
+                "| Navigation Calls | {} | {} |\n",
+                cc.navigation, fc.navigation
+            ));
+            md.push_str(&format!(
+                "| Mutation Calls | {} | {} |\n",
+                cc.mutation, fc.mutation
+            ));
+            md.push_str(&format!(
+                "| Verification Calls | {} | {} |\n",
+                cc.verification, fc.verification
+            ));
+
             // FMM usage (only if non-zero)
             let fu = &task.fmm.fmm_usage;
             if fu.sidecars_read > 0 || fu.mcp_tool_calls > 0 {
@@ -468,9 +1427,7 @@ impl ComparisonReport {
 
             if !task.control.tools_by_name.is_empty() {
                 md.push_str("**Control Tools Used:**\n");
-                let mut tools: Vec<_> = task.control.tools_by_name.iter().collect();
-                tools.sort_by(|a, b| b.1.cmp(a.1));
-                for (tool, count) in tools {
+                for (tool, count) in sorted_tool_counts(&task.control.tools_by_name) {
                     md.push_str(&format!("- {}: {}\n", tool, count));
                 }
                 md.push('\n');
@@ -478,9 +1435,7 @@ impl ComparisonReport {
 
             if !task.fmm.tools_by_name.is_empty() {
                 md.push_str("**FMM Tools Used:**\n");
-                let mut tools: Vec<_> = task.fmm.tools_by_name.iter().collect();
-                tools.sort_by(|a, b| b.1.cmp(a.1));
-                for (tool, count) in tools {
+                for (tool, count) in sorted_tool_counts(&task.fmm.tools_by_name) {
                     md.push_str(&format!("- {}: {}\n", tool, count));
                 }
                 md.push('\n');
@@ -502,18 +1457,31 @@ impl ComparisonReport {
                 ));
                 md.push_str(&format!(
                     "| Tests Exist | {} | {} |\n",
-                    eval_bool(ce.map(|e| e.tests_existed)),
-                    eval_bool(fe.map(|e| e.tests_existed)),
+                    eval_check(ce, |e| e.tests_skipped, |e| e.tests_existed),
+                    eval_check(fe, |e| e.tests_skipped, |e| e.tests_existed),
                 ));
+                let tests_pass = |eval: Option<&EvalScores>| {
+                    let cell = eval_check(eval, |e| e.tests_skipped, |e| e.tests_pass);
+                    if eval.is_some_and(|e| e.flaky) {
+                        format!("{} (flaky)", cell)
+                    } else {
+                        cell.to_string()
+                    }
+                };
                 md.push_str(&format!(
                     "| Tests Pass | {} | {} |\n",
-                    eval_bool(ce.map(|e| e.tests_pass)),
-                    eval_bool(fe.map(|e| e.tests_pass)),
+                    tests_pass(ce),
+                    tests_pass(fe),
+                ));
+                md.push_str(&format!(
+                    "| Tests Added | {} | {} |\n",
+                    ce.map_or("-".to_string(), |e| e.tests_added.to_string()),
+                    fe.map_or("-".to_string(), |e| e.tests_added.to_string()),
                 ));
                 md.push_str(&format!(
                     "| Build Passes | {} | {} |\n",
-                    eval_bool(ce.map(|e| e.build_passes)),
-                    eval_bool(fe.map(|e| e.build_passes)),
+                    eval_check(ce, |e| e.build_skipped, |e| e.build_passes),
+                    eval_check(fe, |e| e.build_skipped, |e| e.build_passes),
                 ));
                 md.push_str(&format!(
                     "| Diff | {} | {} |\n",
@@ -525,6 +1493,12 @@ impl ComparisonReport {
                     ce.map_or("-", |e| &e.grade),
                     fe.map_or("-", |e| &e.grade),
                 ));
+
+                for (label, eval) in [("Control", ce), ("FMM", fe)] {
+                    if let Some(diff) = eval.and_then(|e| e.diff.as_deref()) {
+                        push_diff(&mut md, label, diff);
+                    }
+                }
             }
         }
 
@@ -532,6 +1506,29 @@ impl ComparisonReport {
     }
 }
 
+/// Degenerate run: no tools and either no response, or (for evaluated issue
+/// tasks) no diff.
+fn is_degenerate_run(result: &RunResult, eval: Option<&EvalScores>) -> bool {
+    result.is_degenerate()
+        || (result.success && result.tool_calls == 0 && eval.is_some_and(|e| !e.has_commit))
+}
+
+/// Append a diff as a collapsible block (`<details>` folds it on GitHub/HTML).
+fn push_diff(md: &mut String, label: &str, diff: &str) {
+    md.push_str(&format!("<details>\n<summary>{} diff</summary>\n\n", label));
+    if diff.trim().is_empty() {
+        md.push_str("*No changes.*\n");
+    } else {
+        md.push_str("```diff\n");
+        md.push_str(diff);
+        if !diff.ends_with('\n') {
+            md.push('\n');
+        }
+        md.push_str("```\n");
+    }
+    md.push_str("\n</details>\n\n");
+}
+
 fn eval_bool(val: Option<bool>) -> &'static str {
     match val {
         Some(true) => "Yes",
@@ -540,6 +1537,18 @@ fn eval_bool(val: Option<bool>) -> &'static str {
     }
 }
 
+/// An evaluator check's cell: "Skipped" when turned off, else Yes/No.
+fn eval_check(
+    eval: Option<&EvalScores>,
+    skipped: fn(&EvalScores) -> bool,
+    value: fn(&EvalScores) -> bool,
+) -> &'static str {
+    match eval {
+        Some(e) if skipped(e) => "Skipped",
+        _ => eval_bool(eval.map(value)),
+    }
+}
+
 fn eval_diff(eval: Option<&EvalScores>) -> String {
     match eval {
         Some(e) if e.has_commit => format!("+{}/-{}", e.diff_lines_added, e.diff_lines_removed),
@@ -578,6 +1587,13 @@ fn calculate_reduction_pct(control: f64, fmm: f64) -> f64 {
     }
 }
 
+/// Tool counts by count (descending), then name, so markdown output is stable.
+fn sorted_tool_counts(tools: &HashMap<String, u32>) -> Vec<(&String, &u32)> {
+    let mut sorted: Vec<_> = tools.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    sorted
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.chars().count() <= max_len {
         s.to_string()
@@ -587,9 +1603,22 @@ fn truncate(s: &str, max_len: usize) -> String {
     }
 }
 
+/// A report on `test/repo` at `main` built from `rows`, for tests.
+#[cfg(test)]
+pub(crate) fn test_report(rows: Vec<TaskResultRow>) -> ComparisonReport {
+    ComparisonReport::new(
+        "test-job".to_string(),
+        "https://github.com/test/repo".to_string(),
+        "abc123".to_string(),
+        "main".to_string(),
+        rows,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tasks::test_task;
     use std::collections::HashMap;
 
     #[test]
@@ -629,14 +1658,22 @@ mod tests {
             output_tokens: 500,
             cache_read_tokens: 0,
             total_cost_usd: 0.01,
+            cost_estimated: false,
+            prompt_hash: String::new(),
             duration_ms: 1000,
             num_turns: 2,
             response: "test".to_string(),
+            transcript: String::new(),
             success: true,
             error: None,
+            session_id: None,
+            actual_model: None,
+            hit_turn_limit: false,
+            repeated_tool_invocations: 0,
             tool_details: HashMap::new(),
             navigation: Default::default(),
             fmm_usage: Default::default(),
+            tool_categories: Default::default(),
         }
     }
 
@@ -652,6 +1689,8 @@ mod tests {
             expected_patterns: vec![],
             max_turns: 10,
             max_budget_usd: 1.0,
+            max_tool_calls: None,
+            setup_commands: vec![],
         };
 
         let control = create_test_run_result("test_task", "control", 10);
@@ -673,4 +1712,557 @@ mod tests {
             50.0
         );
     }
+
+    #[test]
+    fn test_task_detail_token_rows() {
+        let task = test_task("t1");
+        let control = create_test_run_result("t1", "control", 10);
+        let mut fmm = create_test_run_result("t1", "fmm", 5);
+        fmm.input_tokens = 250;
+        fmm.output_tokens = 300;
+        fmm.cache_read_tokens = 750;
+
+        let report = test_report(vec![(task, control, fmm, None, None)]);
+        let md = report.to_markdown();
+        assert!(md.contains("| Tokens (in / out / cache) | 1000 / 500 / 0 | 250 / 300 / 750 |"));
+        assert!(md.contains("| Cache Hit Rate | 0% | 75% |"));
+    }
+
+    #[test]
+    fn test_spend_split_sums_to_total() {
+        let mut c1 = create_test_run_result("a", "control", 10);
+        c1.total_cost_usd = 0.30;
+        let mut f1 = create_test_run_result("a", "fmm", 5);
+        f1.total_cost_usd = 0.10;
+        let mut c2 = create_test_run_result("b", "control", 8);
+        c2.total_cost_usd = 0.25;
+        let mut f2 = create_test_run_result("b", "fmm", 4);
+        f2.total_cost_usd = 0.05;
+
+        let report = test_report(vec![
+            (test_task("a"), c1, f1, None, None),
+            (test_task("b"), c2, f2, None, None),
+        ]);
+
+        let total: f64 = report
+            .task_results
+            .iter()
+            .map(|t| t.control.total_cost_usd + t.fmm.total_cost_usd)
+            .sum();
+        let s = &report.summary;
+        assert!((s.control_spend() - 0.55).abs() < 1e-9);
+        assert!((s.fmm_spend() - 0.15).abs() < 1e-9);
+        assert!((s.control_spend() + s.fmm_spend() - total).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_spend_rate_uses_fresh_spend() {
+        let mut report = test_report(vec![(
+            test_task("a"),
+            create_test_run_result("a", "control", 10),
+            create_test_run_result("a", "fmm", 5),
+            None,
+            None,
+        )]);
+        report.wall_ms = 60_000;
+        // Saved before spend tracking: rows may be averaged or cached
+        assert_eq!(report.spend_rate(), None);
+
+        // Three repeats' worth of fresh runs, not the averaged row
+        report.spent = Some(Spend {
+            cost_usd: 0.5,
+            tool_calls: 45,
+        });
+        let rate = report.spend_rate().unwrap();
+        assert!((rate.cost_per_hour - 30.0).abs() < 1e-9);
+        assert!((rate.tool_calls_per_minute - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stdout_json_and_stderr_summary() {
+        use crate::tasks::Task;
+
+        let task = Task {
+            name: "Pipe Task".to_string(),
+            ..test_task("t")
+        };
+        let mut report = test_report(vec![(
+            task,
+            create_test_run_result("t", "control", 10),
+            create_test_run_result("t", "fmm", 4),
+            None,
+            None,
+        )]);
+        report.profile = Some(Profile::default());
+
+        // --stdout routes progress and summaries to stderr
+        let routing = crate::console::RoutingGuard::set(false, true);
+        let (mut stdout, mut stderr): (Vec<u8>, Vec<u8>) = (vec![], vec![]);
+        report
+            .write_results(&mut stdout, &mut stderr, true, false)
+            .unwrap();
+        drop(routing);
+
+        // stdout stream: JSON only
+        let stdout = String::from_utf8(stdout).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(parsed["summary"]["tasks_run"], 1);
+        assert_eq!(parsed["job_id"], "test-job");
+
+        // stderr stream: human summary
+        let stderr = String::from_utf8(stderr).unwrap();
+        assert!(stderr.contains("COMPARISON RESULTS"));
+        assert!(stderr.contains("Summary"));
+        assert!(stderr.contains("Pipe Task"));
+        assert!(stderr.contains("Stage timings"));
+        assert!(serde_json::from_str::<serde_json::Value>(&stderr).is_err());
+    }
+
+    #[test]
+    fn test_exclude_degenerate_from_summary() {
+        let mut refused = create_test_run_result("b", "fmm", 0);
+        refused.response = String::new();
+
+        let mut report = test_report(vec![
+            (
+                test_task("a"),
+                create_test_run_result("a", "control", 10),
+                create_test_run_result("a", "fmm", 5),
+                None,
+                None,
+            ),
+            (
+                test_task("b"),
+                create_test_run_result("b", "control", 10),
+                refused,
+                None,
+                None,
+            ),
+        ]);
+
+        assert!(!report.task_results[0].degenerate);
+        assert!(report.task_results[1].degenerate);
+        assert_eq!(report.summary.tasks_run, 2);
+
+        report.exclude_degenerate();
+        assert_eq!(report.summary.tasks_run, 1);
+        assert_eq!(report.summary.degenerate_excluded, 1);
+        assert_eq!(report.summary.fmm_totals.total_tool_calls, 5);
+        assert_eq!(report.task_results.len(), 2);
+    }
+
+    #[test]
+    fn test_unused_treatment_flagged_and_excluded() {
+        let mut used = create_test_run_result("a", "fmm", 5);
+        used.fmm_usage.sidecars_read = 2;
+        let mut via_mcp = create_test_run_result("b", "fmm", 6);
+        via_mcp.fmm_usage.mcp_tool_calls = 1;
+        let ignored = create_test_run_result("c", "fmm", 9);
+
+        let mut report = test_report(
+            [("a", used), ("b", via_mcp), ("c", ignored)]
+                .into_iter()
+                .map(|(id, fmm)| {
+                    (
+                        test_task(id),
+                        create_test_run_result(id, "control", 10),
+                        fmm,
+                        None,
+                        None,
+                    )
+                })
+                .collect(),
+        );
+
+        // No sidecars generated: nothing to have used
+        assert!(report.flag_unused_treatment().is_empty());
+
+        report.fmm_setup = Some(SidecarGeneration {
+            sidecar_count: 4,
+            ..Default::default()
+        });
+        assert_eq!(report.flag_unused_treatment(), ["c"]);
+        assert!(report.task_results[2].treatment_unused);
+        assert!(report.to_markdown().contains("**Treatment unused:**"));
+
+        report.exclude_unused_treatment();
+        assert_eq!(report.summary.tasks_run, 2);
+        assert_eq!(report.summary.treatment_unused_excluded, 1);
+        assert_eq!(report.summary.fmm_totals.total_tool_calls, 11);
+        assert_eq!(report.task_results.len(), 3);
+        let kept: Vec<_> = report.summarized_tasks().map(|t| &t.task_id).collect();
+        assert_eq!(kept, ["a", "b"]);
+
+        // A later degenerate exclusion keeps this one
+        report.exclude_degenerate();
+        assert_eq!(report.summary.tasks_run, 2);
+        assert_eq!(report.summary.treatment_unused_excluded, 1);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let pair = |id: &str, control: u32, fmm: u32| {
+            (
+                test_task(id),
+                create_test_run_result(id, "control", control),
+                create_test_run_result(id, "fmm", fmm),
+                None,
+                None,
+            )
+        };
+
+        let report = test_report(vec![pair("a", 10, 5), pair("b", 10, 15), pair("c", 10, 2)]);
+
+        // 30 -> 22 tool calls
+        assert!((report.tool_call_reduction() - 26.666).abs() < 0.01);
+        assert_eq!(report.cost_reduction(), 0.0);
+        assert!((report.fmm_win_rate() - 2.0 / 3.0).abs() < 1e-10);
+        assert_eq!(report.best_task().unwrap().task_id, "c");
+        assert_eq!(report.worst_task().unwrap().task_id, "b");
+
+        // A task the summary leaves out is never the best or worst
+        let mut refused = create_test_run_result("d", "fmm", 0);
+        refused.response = String::new();
+        let mut report = test_report(vec![
+            pair("a", 10, 5),
+            pair("c", 10, 2),
+            (
+                test_task("d"),
+                create_test_run_result("d", "control", 10),
+                refused,
+                None,
+                None,
+            ),
+        ]);
+        assert_eq!(report.best_task().unwrap().task_id, "d");
+        report.exclude_degenerate();
+        assert_eq!(report.best_task().unwrap().task_id, "c");
+
+        let empty = empty_report();
+        assert_eq!(empty.fmm_win_rate(), 0.0);
+        assert!(empty.best_task().is_none());
+        assert!(empty.worst_task().is_none());
+    }
+
+    #[test]
+    fn test_tool_lists_ordered_deterministically() {
+        let report = |names: &[(&str, u32)]| {
+            let task = test_task("t");
+            let mut control = create_test_run_result("t", "control", 8);
+            // Fresh maps get fresh hash seeds, so iteration order varies
+            control.tools_by_name = names.iter().map(|(n, c)| (n.to_string(), *c)).collect();
+            let mut fmm = create_test_run_result("t", "fmm", 4);
+            fmm.tools_by_name = control.tools_by_name.clone();
+            let mut report = test_report(vec![(task, control, fmm, None, None)]);
+            report.timestamp = "2025-01-01T00:00:00Z".to_string();
+            report
+        };
+        let tools = [
+            ("Read", 3),
+            ("Grep", 1),
+            ("Glob", 1),
+            ("Bash", 3),
+            ("Edit", 1),
+        ];
+        let md = report(&tools).to_markdown();
+        for _ in 0..10 {
+            let mut shuffled = tools;
+            shuffled.reverse();
+            assert_eq!(report(&shuffled).to_markdown(), md);
+            assert_eq!(report(&tools).to_markdown(), md);
+        }
+        assert!(md.contains("- Bash: 3\n- Read: 3\n- Edit: 1\n- Glob: 1\n- Grep: 1\n"));
+    }
+
+    #[test]
+    fn test_fmm_regression_report() {
+        let mut a = empty_report();
+        a.summary.overall_savings.tool_calls_reduction_pct = 20.0;
+        let mut b = empty_report();
+        b.summary.overall_savings.tool_calls_reduction_pct = 35.0;
+        b.summary.overall_savings.cost_reduction_pct = -5.0;
+
+        let regression =
+            FmmRegressionReport::new(Path::new("/opt/fmm-old"), Path::new("/opt/fmm-new"), a, b);
+        assert!((regression.tool_call_delta() - 15.0).abs() < 1e-10);
+        assert!((regression.cost_delta() + 5.0).abs() < 1e-10);
+        assert!(regression.b_improved());
+
+        let md = regression.to_markdown();
+        assert!(md.contains("`/opt/fmm-new`"));
+        assert!(md.contains("| Tool call reduction | 20.0% | 35.0% | +15.0 pp |"));
+        assert!(md.contains("B improved"));
+    }
+
+    #[test]
+    fn test_baseline_flags_regressed_task() {
+        let run = |variant: &str, tools: u32| create_test_run_result("t", variant, tools);
+        let report = test_report(vec![
+            // 50% reduction, expected 45%: fine
+            (
+                test_task("a"),
+                run("control", 10),
+                run("fmm", 5),
+                None,
+                None,
+            ),
+            // 20% reduction, expected 40%: regressed
+            (
+                test_task("b"),
+                run("control", 10),
+                run("fmm", 8),
+                None,
+                None,
+            ),
+            // 38% reduction, expected 40%: within tolerance
+            (
+                test_task("c"),
+                run("control", 50),
+                run("fmm", 31),
+                None,
+                None,
+            ),
+            // Not in the baseline: ignored
+            (
+                test_task("d"),
+                run("control", 10),
+                run("fmm", 20),
+                None,
+                None,
+            ),
+        ]);
+        let baseline: ReductionBaseline =
+            serde_json::from_str(r#"{"a": 45.0, "b": 40.0, "c": 40.0, "gone": 10.0}"#).unwrap();
+
+        let regressions = baseline.regressions(&report, 5.0);
+        let ids: Vec<&str> = regressions.iter().map(|r| r.task_id.as_str()).collect();
+        assert_eq!(ids, ["b", "gone"]);
+        assert_eq!(regressions[0].describe(), "b: 20.0% vs expected 40.0%");
+        assert_eq!(regressions[1].actual_pct, None);
+
+        assert_eq!(baseline.regressions(&report, 50.0).len(), 1);
+    }
+
+    fn empty_report() -> ComparisonReport {
+        test_report(vec![])
+    }
+
+    #[test]
+    fn test_save_to_explicit_md_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("report.md");
+
+        let saved = empty_report().save(&path, ReportFormat::Markdown).unwrap();
+
+        assert_eq!(saved, vec![path.display().to_string()]);
+        assert!(path.is_file());
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# FMM Comparison Report"));
+        // No job-id named files alongside it
+        assert!(!dir.path().join("nested").join("test-job.json").exists());
+    }
+
+    #[test]
+    fn test_save_both_to_single_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.md");
+        assert!(empty_report().save(&path, ReportFormat::Both).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_resolve_report_format() {
+        let md = Path::new("out/report.md");
+        let json = Path::new("report.JSON");
+        let dir = Path::new("results");
+
+        assert_eq!(
+            ReportFormat::resolve(Some(md), None).unwrap(),
+            ReportFormat::Markdown
+        );
+        assert_eq!(
+            ReportFormat::resolve(Some(json), None).unwrap(),
+            ReportFormat::Json
+        );
+        assert_eq!(
+            ReportFormat::resolve(Some(dir), None).unwrap(),
+            ReportFormat::Both
+        );
+        assert_eq!(
+            ReportFormat::resolve(None, Some(ReportFormat::Json)).unwrap(),
+            ReportFormat::Json
+        );
+        assert!(ReportFormat::resolve(Some(md), Some(ReportFormat::Both)).is_err());
+        assert!(ReportFormat::resolve(Some(md), Some(ReportFormat::Json)).is_err());
+    }
+
+    #[test]
+    fn test_prompt_hash_mismatch_warns() {
+        let task = test_task("t1");
+        let mut control = create_test_run_result("t1", "control", 10);
+        control.prompt_hash = "aaaa".to_string();
+        let mut fmm = create_test_run_result("t1", "fmm", 5);
+        fmm.prompt_hash = "bbbb".to_string();
+
+        let report = test_report(vec![(task.clone(), control.clone(), fmm, None, None)]);
+        assert!(report.task_results[0].prompt_mismatch);
+        assert_eq!(report.prompt_mismatches(), vec!["t1"]);
+        assert!(report.to_markdown().contains("prompt mismatch"));
+        let mut out = Vec::new();
+        report.write_summary(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("PROMPT MISMATCH"));
+
+        // Matching hashes (and legacy empty hashes) don't warn
+        let mut same = control.clone();
+        same.variant = "fmm".to_string();
+        let legacy = create_test_run_result("t1", "fmm", 5);
+        let report = test_report(vec![
+            (task.clone(), control.clone(), same, None, None),
+            (task, control, legacy, None, None),
+        ]);
+        assert!(report.prompt_mismatches().is_empty());
+        assert!(!report.to_markdown().contains("prompt mismatch"));
+    }
+
+    #[test]
+    fn test_turn_limited_tasks_flagged() {
+        let task = test_task("t1");
+        let control = create_test_run_result("t1", "control", 10);
+        let mut fmm = create_test_run_result("t1", "fmm", 5);
+        fmm.hit_turn_limit = true;
+
+        let report = test_report(vec![(task, control, fmm, None, None)]);
+        assert!(report.task_results[0].turn_limited);
+        assert_eq!(report.turn_limited(), vec!["t1"]);
+        let md = report.to_markdown();
+        assert!(md.contains("1 task(s) hit the turn limit"));
+        assert!(md.contains("FMM hit the turn cap"));
+    }
+
+    #[test]
+    fn test_compact_summary_single_line() {
+        let mut report = ComparisonReport::new(
+            "job-42".to_string(),
+            "https://github.com/test/repo".to_string(),
+            "abc123".to_string(),
+            "main".to_string(),
+            vec![],
+        );
+        report.summary.tasks_run = 2;
+        report.summary.fmm_wins = 1;
+        report.summary.overall_savings.tool_calls_reduction_pct = 35.0;
+        report.summary.overall_savings.cost_reduction_pct = -12.5;
+
+        let line = report.compact_summary();
+        assert!(!line.contains('\n'));
+        assert!(line.starts_with("job-42 | 2 task(s) | FMM wins 1/2"));
+        assert!(line.contains("tools -35.0%"));
+        assert!(line.contains("cost +12.5%"));
+    }
+
+    #[test]
+    fn test_report_without_schema_version_is_v0() {
+        let mut value = serde_json::to_value(empty_report()).unwrap();
+        value.as_object_mut().unwrap().remove("schema_version");
+        let json = value.to_string();
+
+        let raw: ComparisonReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(raw.schema_version, 0);
+        let note = check_schema_version(0, "Report test-job").unwrap().unwrap();
+        assert!(note.contains("treated as v0"));
+
+        let migrated = ComparisonReport::from_json(&json).unwrap();
+        assert_eq!(migrated.schema_version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_regrade_stored_report_under_new_rubric() {
+        use crate::tasks::Task;
+
+        let task = Task {
+            name: "Fix it".to_string(),
+            ..test_task("issue-1")
+        };
+        // pytest exits 5 when it collects no tests: graded C as a failure
+        let control_eval = EvalScores {
+            has_commit: true,
+            tests_existed: true,
+            tests_pass: false,
+            build_passes: true,
+            files_touched: 1,
+            test_exit_codes: vec![5],
+            build_exit_code: Some(0),
+            grade: "C".to_string(),
+            ..Default::default()
+        };
+        let fmm_eval = EvalScores {
+            tests_pass: true,
+            test_exit_codes: vec![0],
+            grade: "A".to_string(),
+            ..control_eval.clone()
+        };
+        let report = test_report(vec![(
+            task,
+            create_test_run_result("issue-1", "control", 10),
+            create_test_run_result("issue-1", "fmm", 5),
+            Some(control_eval),
+            Some(fmm_eval),
+        )]);
+        let mut stored = ComparisonReport::from_json(&report.to_json().unwrap()).unwrap();
+
+        // The rubric that graded it reproduces the stored grades
+        assert!(stored
+            .regrade_with(crate::evaluator::standard_grade)
+            .is_empty());
+
+        // A rubric treating "no tests collected" as no test suite
+        let changes = stored.regrade_with(|scores| {
+            if scores.test_exit_codes.iter().all(|&code| code == 5) {
+                let mut untested = scores.clone();
+                untested.tests_existed = false;
+                crate::evaluator::standard_grade(&untested)
+            } else {
+                crate::evaluator::standard_grade(scores)
+            }
+        });
+        assert_eq!(
+            changes,
+            vec![GradeChange {
+                task_id: "issue-1".to_string(),
+                variant: "control".to_string(),
+                before: "C".to_string(),
+                after: "B".to_string(),
+            }]
+        );
+        assert_eq!(
+            stored.task_results[0].control_eval.as_ref().unwrap().grade,
+            "B"
+        );
+    }
+
+    #[test]
+    fn test_report_from_newer_schema_rejected() {
+        let mut value = serde_json::to_value(empty_report()).unwrap();
+        value["schema_version"] = serde_json::json!(SCHEMA_VERSION + 1);
+
+        let err = ComparisonReport::from_json(&value.to_string()).unwrap_err();
+        assert!(err.to_string().contains("incompatible version"));
+    }
+
+    #[test]
+    fn test_allowed_tools_in_markdown() {
+        let mut report = empty_report();
+        report.control_allowed_tools = vec!["Read".to_string(), "Grep".to_string()];
+        report.fmm_allowed_tools = report.control_allowed_tools.clone();
+        assert!(report
+            .to_markdown()
+            .contains("**Allowed tools (both):** Read, Grep"));
+
+        report
+            .fmm_allowed_tools
+            .push("mcp__fmm__lookup".to_string());
+        assert!(report
+            .to_markdown()
+            .contains("control Read, Grep | FMM Read, Grep, mcp__fmm__lookup"));
+    }
 }
@@ -3,11 +3,14 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
-use std::process::Command;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::time::Instant;
 
 use crate::metrics;
+use crate::pricing;
 use crate::tasks::Task;
 
 /// Result of a single benchmark run
@@ -23,11 +26,33 @@ pub struct RunResult {
     pub output_tokens: u64,
     pub cache_read_tokens: u64,
     pub total_cost_usd: f64,
+    /// `total_cost_usd` was estimated from tokens (the CLI reported no cost).
+    #[serde(default)]
+    pub cost_estimated: bool,
     pub duration_ms: u64,
     pub num_turns: u32,
+    /// Hash of the exact prompt sent (must match across conditions).
+    #[serde(default)]
+    pub prompt_hash: String,
+    /// Final assistant message.
     pub response: String,
+    /// All assistant text blocks joined in order (plan through final answer).
+    #[serde(default)]
+    pub transcript: String,
     pub success: bool,
     pub error: Option<String>,
+    /// CLI session id, for correlating with Claude's own logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model reported by the CLI (may differ from the requested alias).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_model: Option<String>,
+    /// Cut off by the turn cap; its metrics describe an unfinished run.
+    #[serde(default)]
+    pub hit_turn_limit: bool,
+    /// Tool calls repeating the previous call exactly (a "stuck" signal).
+    #[serde(default)]
+    pub repeated_tool_invocations: u32,
 
     /// Per-tool detail with args (files, patterns, commands).
     #[serde(default)]
@@ -38,11 +63,50 @@ pub struct RunResult {
     /// FMM-specific usage tracking.
     #[serde(default)]
     pub fmm_usage: metrics::FmmUsage,
+    /// Tool calls bucketed by category (navigation, mutation, verification).
+    #[serde(default)]
+    pub tool_categories: metrics::ToolCategoryCounts,
 }
 
 impl RunResult {
+    /// A "successful" run that did no work: zero tool calls and no response.
+    ///
+    /// Such runs (refusals, trivial answers) make reductions meaningless —
+    /// 0 tool calls looks like a 100% reduction but is really a non-answer.
+    pub fn is_degenerate(&self) -> bool {
+        self.success && self.tool_calls == 0 && self.response.trim().is_empty()
+    }
+
+    /// The CLI failed before doing any work (crash, spawn failure, API outage):
+    /// zero turns, so its zero metrics mean nothing. Runs stopped by a budget
+    /// or turn cap did real work and are not errored.
+    pub fn is_errored(&self) -> bool {
+        !self.success && self.num_turns == 0 && self.tool_calls == 0
+    }
+
+    /// Distinct files read. `files_accessed` keeps every read (a file read
+    /// three times appears three times), which measures repetition, not breadth.
+    pub fn unique_files_accessed(&self) -> usize {
+        self.files_accessed
+            .iter()
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    }
+
+    /// Reads of a file that had already been read (thrashing).
+    pub fn repeat_reads(&self) -> usize {
+        self.files_accessed.len() - self.unique_files_accessed()
+    }
+
+    /// Share of prompt tokens served from the prompt cache; None when the
+    /// run reported no input tokens at all.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let prompt = self.input_tokens + self.cache_read_tokens;
+        (prompt > 0).then(|| self.cache_read_tokens as f64 / prompt as f64)
+    }
+
     /// Create a RunResult from shared RunMetrics plus context identifiers.
-    fn from_metrics(
+    pub(crate) fn from_metrics(
         m: metrics::RunMetrics,
         response: String,
         task_id: &str,
@@ -59,24 +123,139 @@ impl RunResult {
             output_tokens: m.output_tokens,
             cache_read_tokens: m.cache_read_tokens,
             total_cost_usd: m.cost_usd,
+            cost_estimated: false,
             duration_ms: m.duration_ms,
             num_turns: m.turns,
+            prompt_hash: String::new(),
             response,
+            transcript: String::new(),
             success: m.success,
             error: m.error,
+            session_id: m.session_id,
+            actual_model: m.actual_model,
+            hit_turn_limit: m.hit_turn_limit,
+            repeated_tool_invocations: m.repeated_tool_invocations,
             tool_details: m.tool_details,
             navigation: m.navigation,
             fmm_usage: m.fmm_usage,
+            tool_categories: m.tool_categories,
         }
     }
 }
 
+/// Whether the model the CLI reported is the one requested.
+///
+/// Aliases like `sonnet` match any full id containing them
+/// (`claude-sonnet-4-5-20250929`); `--model any` matches whatever ran.
+fn model_matches(requested: &str, actual: &str) -> bool {
+    requested == pricing::ANY_MODEL
+        || actual
+            .to_ascii_lowercase()
+            .contains(&requested.to_ascii_lowercase())
+}
+
+/// Stable 64-bit FNV-1a hash of a prompt, hex encoded.
+///
+/// Stable across builds so cached results stay comparable.
+pub(crate) fn prompt_hash(prompt: &str) -> String {
+    bytes_hash(prompt.as_bytes())
+}
+
+/// `prompt_hash` of arbitrary bytes (e.g. a binary's contents).
+pub(crate) fn bytes_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Create the raw stream-json dump for one run.
+///
+/// Task ids like `owner/repo#12` are flattened to path-safe names; an existing
+/// dump (a previous run of the same task) gets a numeric suffix, not overwritten.
+fn create_raw_export(dir: &Path, job_id: &str, task_id: &str, variant: &str) -> Result<File> {
+    create_run_file(dir, job_id, task_id, variant, "jsonl")
+}
+
+/// Create `<dir>/<job>-<task>-<variant>.<ext>`, numbering repeat runs of the
+/// same task (`-2`, `-3`, ...) instead of overwriting.
+fn create_run_file(
+    dir: &Path,
+    job_id: &str,
+    task_id: &str,
+    variant: &str,
+    ext: &str,
+) -> Result<File> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let stem = format!(
+        "{}-{}-{}",
+        path_safe(job_id),
+        path_safe(task_id),
+        path_safe(variant)
+    );
+    let mut path = dir.join(format!("{}.{}", stem, ext));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.{}", stem, n, ext));
+        n += 1;
+    }
+    File::create(&path).with_context(|| format!("Failed to create {}", path.display()))
+}
+
+/// Replace anything but alphanumerics, `-`, `_` and `.` with `_`.
+fn path_safe(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// Copies everything read from `inner` to the raw export, if any. A failed
+/// export write drops the export with a warning rather than failing the parse
+/// of a run that is already being paid for.
+struct TeeReader<R> {
+    inner: R,
+    raw: Option<File>,
+}
+
+impl<R: Read> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(raw) = &mut self.raw {
+            if let Err(e) = raw.write_all(&buf[..n]) {
+                eprintln!("Warning: raw stream-json export failed, stopping it: {}", e);
+                self.raw = None;
+            }
+        }
+        Ok(n)
+    }
+}
+
 /// Claude CLI runner with instrumentation
 pub struct ClaudeRunner {
     allowed_tools: Vec<String>,
     model: String,
+    /// Pass `--dangerously-skip-permissions`; when off, runs use
+    /// [`Self::PERMISSION_MODE`] instead (`--require-permissions`)
     skip_permissions: bool,
     enable_local_settings: bool,
+    max_output_bytes: u64,
+    /// Fail on malformed stream-json instead of skipping it (`--strict-json`)
+    strict_json: bool,
+    /// Directory and job id for raw stream-json dumps (`--export-raw`).
+    export_raw: Option<(PathBuf, String)>,
+    /// Directory and job id for readable transcripts (`--keep-claude-logs`).
+    keep_logs: Option<(PathBuf, String)>,
 }
 
 impl Default for ClaudeRunner {
@@ -100,6 +279,10 @@ impl ClaudeRunner {
             model: "sonnet".to_string(),
             skip_permissions: true,
             enable_local_settings: false,
+            max_output_bytes: metrics::DEFAULT_MAX_OUTPUT_BYTES,
+            strict_json: false,
+            export_raw: None,
+            keep_logs: None,
         }
     }
 
@@ -111,41 +294,62 @@ impl ClaudeRunner {
         }
     }
 
+    /// Load the workspace's local settings (`--setting-sources local`), e.g.
+    /// for a control run sharing the FMM run's settings template.
+    pub fn set_local_settings(&mut self, enable: bool) {
+        self.enable_local_settings = enable;
+    }
+
+    /// Tools passed to `--allowedTools`.
+    pub fn allowed_tools(&self) -> &[String] {
+        &self.allowed_tools
+    }
+
     /// Set the model for this runner.
     pub fn set_model(&mut self, model: &str) {
         self.model = model.to_string();
     }
 
-    const MAX_PROMPT_SIZE: usize = 100 * 1024;
-    const MAX_CONTEXT_SIZE: usize = 500 * 1024;
+    /// Cap the stream-json bytes parsed per run; beyond it the run is
+    /// recorded with `error = "output_too_large"`.
+    pub fn set_max_output_bytes(&mut self, max_bytes: u64) {
+        self.max_output_bytes = max_bytes;
+    }
 
-    /// Run a task and collect metrics
-    pub fn run_task(
-        &self,
-        task: &Task,
-        working_dir: &Path,
-        variant: &str,
-        fmm_context: Option<&str>,
-    ) -> Result<RunResult> {
-        if task.prompt.len() > Self::MAX_PROMPT_SIZE {
-            anyhow::bail!(
-                "Task prompt exceeds size limit ({} > {} bytes)",
-                task.prompt.len(),
-                Self::MAX_PROMPT_SIZE
-            );
-        }
-        if let Some(ctx) = fmm_context {
-            if ctx.len() > Self::MAX_CONTEXT_SIZE {
-                anyhow::bail!(
-                    "FMM context exceeds size limit ({} > {} bytes)",
-                    ctx.len(),
-                    Self::MAX_CONTEXT_SIZE
-                );
-            }
-        }
+    /// Error on malformed stream-json lines and warn on unknown event types,
+    /// instead of silently skipping them.
+    pub fn set_strict_json(&mut self, strict: bool) {
+        self.strict_json = strict;
+    }
 
-        let start = Instant::now();
+    /// Save each run's raw stream-json to `<dir>/<job>-<task>-<variant>.jsonl`.
+    pub fn set_export_raw(&mut self, dir: &Path, job_id: &str) {
+        self.export_raw = Some((dir.to_path_buf(), job_id.to_string()));
+    }
+
+    /// Save each run's readable transcript (text and tool calls in order) to
+    /// `<dir>/<job>-<task>-<variant>.log`.
+    pub fn set_keep_logs(&mut self, dir: &Path, job_id: &str) {
+        self.keep_logs = Some((dir.to_path_buf(), job_id.to_string()));
+    }
+
+    /// Run under the CLI's permission checks instead of skipping them
+    /// (shared or locked-down machines). Only `--allowedTools` and file edits
+    /// are pre-approved, so a run that needs anything else is denied or
+    /// waits on a prompt nobody answers until a cap stops it.
+    pub fn set_require_permissions(&mut self, require: bool) {
+        self.skip_permissions = !require;
+    }
+
+    /// `--permission-mode` used when permissions are required.
+    const PERMISSION_MODE: &'static str = "acceptEdits";
 
+    pub(crate) const MAX_PROMPT_SIZE: usize = 100 * 1024;
+    const MAX_CONTEXT_SIZE: usize = 500 * 1024;
+
+    /// Assemble the CLI invocation. `context` is appended to the system
+    /// prompt (fmm instructions, or a neutral ablation prompt for control).
+    fn command(&self, task: &Task, working_dir: &Path, context: Option<&str>) -> Command {
         let mut cmd = Command::new("claude");
 
         cmd.arg("-p").arg(&task.prompt);
@@ -154,7 +358,10 @@ impl ClaudeRunner {
         cmd.arg("--max-turns").arg(task.max_turns.to_string());
         cmd.arg("--max-budget-usd")
             .arg(task.max_budget_usd.to_string());
-        cmd.arg("--model").arg(&self.model);
+        // `--model any`: leave the choice to the CLI
+        if self.model != pricing::ANY_MODEL {
+            cmd.arg("--model").arg(&self.model);
+        }
 
         if !self.allowed_tools.is_empty() {
             cmd.arg("--allowedTools").arg(self.allowed_tools.join(","));
@@ -166,26 +373,97 @@ impl ClaudeRunner {
             cmd.arg("--setting-sources").arg("");
         }
 
-        if let Some(context) = fmm_context {
+        if let Some(context) = context {
             cmd.arg("--append-system-prompt").arg(context);
         }
 
         if self.skip_permissions {
             cmd.arg("--dangerously-skip-permissions");
+        } else {
+            cmd.arg("--permission-mode").arg(Self::PERMISSION_MODE);
         }
 
         cmd.arg("--no-session-persistence");
         cmd.current_dir(working_dir);
+        cmd
+    }
+
+    /// Run a task and collect metrics
+    pub fn run_task(
+        &self,
+        task: &Task,
+        working_dir: &Path,
+        variant: &str,
+        fmm_context: Option<&str>,
+    ) -> Result<RunResult> {
+        if task.prompt.len() > Self::MAX_PROMPT_SIZE {
+            anyhow::bail!(
+                "Task prompt exceeds size limit ({} > {} bytes)",
+                task.prompt.len(),
+                Self::MAX_PROMPT_SIZE
+            );
+        }
+        if let Some(ctx) = fmm_context {
+            if ctx.len() > Self::MAX_CONTEXT_SIZE {
+                anyhow::bail!(
+                    "Appended context for {} exceeds size limit ({} > {} bytes)",
+                    variant,
+                    ctx.len(),
+                    Self::MAX_CONTEXT_SIZE
+                );
+            }
+        }
 
-        let output = cmd.output().context("Failed to execute claude CLI")?;
+        let start = Instant::now();
+
+        let mut cmd = self.command(task, working_dir, fmm_context);
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut child = cmd.spawn().context("Failed to execute claude CLI")?;
+
+        // Drain stderr on a thread so a chatty CLI can't block on a full pipe
+        // while stdout is being parsed.
+        let mut stderr_pipe = child.stderr.take().context("claude stderr not captured")?;
+        let stderr_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stderr_pipe.read_to_end(&mut buf);
+            buf
+        });
+
+        // Parse stdout as it streams, never holding the whole output in memory.
+        let stdout = child.stdout.take().context("claude stdout not captured")?;
+        let raw = match &self.export_raw {
+            Some((dir, job_id)) => Some(create_raw_export(dir, job_id, &task.id, variant)?),
+            None => None,
+        };
+        // Stopping mid-stream (output or tool-call cap) kills the CLI rather
+        // than leaving it blocked on a full pipe.
+        let parsed = metrics::parse_stream_json_limited(
+            BufReader::new(TeeReader { inner: stdout, raw }),
+            std::time::Duration::ZERO,
+            self.max_output_bytes,
+            task.max_tool_calls,
+            self.strict_json,
+            || {
+                let _ = child.kill();
+            },
+        );
+        if parsed.is_err() {
+            let _ = child.kill();
+        }
+        let status = child.wait().context("Failed to wait for claude CLI")?;
+        let stderr_bytes = stderr_reader.join().unwrap_or_default();
+        let mut parsed = parsed?;
 
         let duration = start.elapsed();
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let cli_success = output.status.success();
+        let stderr = String::from_utf8_lossy(&stderr_bytes);
+        let cli_success = status.success();
+
+        if parsed.metrics.duration_ms == 0 {
+            parsed.metrics.duration_ms = duration.as_millis() as u64;
+        }
 
-        if !cli_success && stdout.is_empty() {
-            return Ok(RunResult::from_metrics(
+        if !cli_success && parsed.bytes_read == 0 {
+            let mut result = RunResult::from_metrics(
                 metrics::RunMetrics {
                     duration_ms: duration.as_millis() as u64,
                     error: Some(stderr.to_string()),
@@ -194,19 +472,59 @@ impl ClaudeRunner {
                 String::new(),
                 &task.id,
                 variant,
-            ));
+            );
+            result.prompt_hash = prompt_hash(&task.prompt);
+            return Ok(result);
+        }
+
+        for (kind, line) in &parsed.unknown_event_types {
+            eprintln!(
+                "Warning: unrecognized stream-json event type '{}' (line {}, {} {})",
+                kind, line, task.id, variant
+            );
+        }
+
+        if let Some(actual) = &parsed.metrics.actual_model {
+            if !model_matches(&self.model, actual) {
+                eprintln!(
+                    "Warning: requested model '{}' but the CLI ran '{}' ({} {})",
+                    self.model, actual, task.id, variant
+                );
+            }
         }
 
-        let parsed = metrics::parse_stream_json(&stdout, duration)?;
+        // The run is already paid for: a failed log write costs the log only
+        if let Some((dir, job_id)) = &self.keep_logs {
+            let written =
+                create_run_file(dir, job_id, &task.id, variant, "log").and_then(|mut log| {
+                    log.write_all(parsed.full_transcript().as_bytes())
+                        .context("Failed to write transcript log")
+                });
+            if let Err(e) = written {
+                eprintln!(
+                    "Warning: transcript log not kept ({} {}): {:#}",
+                    task.id, variant, e
+                );
+            }
+        }
+
+        let transcript = parsed.transcript();
+        let estimated_cost = pricing::estimate_missing_cost(&self.model, &parsed.metrics);
         let mut result =
             RunResult::from_metrics(parsed.metrics, parsed.response_text, &task.id, variant);
+        result.transcript = transcript;
+        result.prompt_hash = prompt_hash(&task.prompt);
+        if let Some(cost) = estimated_cost {
+            result.total_cost_usd = cost;
+            result.cost_estimated = true;
+        }
 
         if !cli_success {
             result.success = false;
             if result.error.is_none() {
                 result.error = Some(format!(
                     "CLI exited with status {}",
-                    output.status.code().unwrap_or(-1)
+                    status.code().unwrap_or(-1)
                 ));
             }
         }
@@ -217,6 +535,7 @@ impl ClaudeRunner {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tasks::test_task;
 
     #[test]
     fn test_runner_creation() {
@@ -356,6 +675,8 @@ mod tests {
             expected_patterns: vec![],
             max_turns: 1,
             max_budget_usd: 0.01,
+            max_tool_calls: None,
+            setup_commands: vec![],
         };
 
         let err = runner
@@ -375,12 +696,222 @@ mod tests {
             expected_patterns: vec![],
             max_turns: 1,
             max_budget_usd: 0.01,
+            max_tool_calls: None,
+            setup_commands: vec![],
         };
         let big_context = "y".repeat(ClaudeRunner::MAX_CONTEXT_SIZE + 1);
 
-        let err = runner
-            .run_task(&task, Path::new("/tmp"), "fmm", Some(&big_context))
-            .unwrap_err();
-        assert!(err.to_string().contains("FMM context exceeds size limit"));
+        for variant in ["fmm", "control"] {
+            let err = runner
+                .run_task(&task, Path::new("/tmp"), variant, Some(&big_context))
+                .unwrap_err()
+                .to_string();
+            assert!(
+                err.contains(&format!("context for {} exceeds size limit", variant)),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_command_appends_context() {
+        let runner = ClaudeRunner::new();
+        let task = test_task("ctx");
+        let args = |cmd: Command| -> Vec<String> {
+            cmd.get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let with = args(runner.command(&task, Path::new("/tmp"), Some("neutral text")));
+        let i = with
+            .iter()
+            .position(|a| a == "--append-system-prompt")
+            .unwrap();
+        assert_eq!(with[i + 1], "neutral text");
+
+        let without = args(runner.command(&task, Path::new("/tmp"), None));
+        assert!(!without.iter().any(|a| a == "--append-system-prompt"));
+    }
+
+    #[test]
+    fn test_command_any_model_leaves_model_to_cli() {
+        let task = test_task("m");
+        let args = |runner: &ClaudeRunner| -> Vec<String> {
+            runner
+                .command(&task, Path::new("/tmp"), None)
+                .get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let mut runner = ClaudeRunner::new();
+        assert!(args(&runner).contains(&"--model".to_string()));
+        runner.set_model(pricing::ANY_MODEL);
+        assert!(!args(&runner).contains(&"--model".to_string()));
+        assert!(model_matches(pricing::ANY_MODEL, "claude-opus-4-5"));
+    }
+
+    #[test]
+    fn test_command_requires_permissions() {
+        let task = test_task("perm");
+        let args = |runner: &ClaudeRunner| -> Vec<String> {
+            runner
+                .command(&task, Path::new("/tmp"), None)
+                .get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let mut runner = ClaudeRunner::new();
+        assert!(args(&runner).contains(&"--dangerously-skip-permissions".to_string()));
+
+        runner.set_require_permissions(true);
+        let required = args(&runner);
+        assert!(!required.contains(&"--dangerously-skip-permissions".to_string()));
+        let i = required
+            .iter()
+            .position(|a| a == "--permission-mode")
+            .unwrap();
+        assert_eq!(required[i + 1], "acceptEdits");
+    }
+
+    #[test]
+    fn test_is_degenerate() {
+        let base = RunResult::from_metrics(
+            metrics::RunMetrics {
+                success: true,
+                ..Default::default()
+            },
+            String::new(),
+            "t",
+            "control",
+        );
+        // Success, no tools, no response
+        assert!(base.is_degenerate());
+
+        // Whitespace-only response still counts as empty
+        let mut blank = base.clone();
+        blank.response = "  \n".to_string();
+        assert!(blank.is_degenerate());
+
+        // Answered without tools (e.g. a knowledge question)
+        let mut answered = base.clone();
+        answered.response = "The entry point is main.rs".to_string();
+        assert!(!answered.is_degenerate());
+
+        // Used tools
+        let mut worked = base.clone();
+        worked.tool_calls = 3;
+        assert!(!worked.is_degenerate());
+
+        // Failed runs are failures, not degenerate successes
+        let mut failed = base.clone();
+        failed.success = false;
+        assert!(!failed.is_degenerate());
+    }
+
+    #[test]
+    fn test_raw_export_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = "{\"type\":\"system\",\"subtype\":\"init\",\"session_id\":\"s1\"}\n\
+{\"type\":\"result\",\"is_error\":false,\"usage\":{\"input_tokens\":10,\"output_tokens\":5},\"total_cost_usd\":0.001,\"num_turns\":1}\n";
+
+        for _ in 0..2 {
+            let raw = create_raw_export(dir.path(), "job-1", "owner/repo#12", "control").unwrap();
+            let parsed = metrics::parse_stream_json_reader(
+                BufReader::new(TeeReader {
+                    inner: output.as_bytes(),
+                    raw: Some(raw),
+                }),
+                dur(0),
+                metrics::DEFAULT_MAX_OUTPUT_BYTES,
+            )
+            .unwrap();
+            assert!(parsed.metrics.success);
+        }
+
+        let first = dir.path().join("job-1-owner_repo_12-control.jsonl");
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), output);
+        // A second run of the same task doesn't clobber the first
+        let second = dir.path().join("job-1-owner_repo_12-control-2.jsonl");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), output);
+    }
+
+    #[test]
+    fn test_failed_raw_export_write_keeps_parsing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("raw.jsonl");
+        File::create(&path).unwrap();
+        // Opened read-only, so every export write fails
+        let raw = File::open(&path).unwrap();
+        let output = "{\"type\":\"result\",\"is_error\":false,\"result\":\"Done\"}\n";
+
+        let parsed = metrics::parse_stream_json_reader(
+            BufReader::new(TeeReader {
+                inner: output.as_bytes(),
+                raw: Some(raw),
+            }),
+            dur(0),
+            metrics::DEFAULT_MAX_OUTPUT_BYTES,
+        )
+        .unwrap();
+        assert!(parsed.metrics.success);
+        assert_eq!(parsed.response_text, "Done");
+    }
+
+    #[test]
+    fn test_path_safe() {
+        assert_eq!(path_safe("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(path_safe("fix-bug_1"), "fix-bug_1");
+    }
+
+    #[test]
+    fn test_unique_files_accessed() {
+        let output = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Read","input":{"file_path":"a.rs"}},{"type":"tool_use","name":"Read","input":{"file_path":"b.rs"}}]}}
+{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Read","input":{"file_path":"a.rs"}},{"type":"tool_use","name":"Read","input":{"file_path":"a.rs"}}]}}
+{"type":"result","is_error":false,"result":"Done"}"#;
+        let parsed = metrics::parse_stream_json(output, dur(0)).unwrap();
+        let result = RunResult::from_metrics(parsed.metrics, String::new(), "t", "control");
+
+        assert_eq!(result.files_accessed.len(), 4);
+        assert_eq!(result.unique_files_accessed(), 2);
+        assert_eq!(result.repeat_reads(), 2);
+    }
+
+    #[test]
+    fn test_is_errored() {
+        let mut r = RunResult::from_metrics(
+            metrics::RunMetrics {
+                error: Some("CLI exited with status 1".to_string()),
+                ..Default::default()
+            },
+            String::new(),
+            "t",
+            "control",
+        );
+        assert!(r.is_errored());
+
+        // Failed after real work (e.g. budget exceeded): not errored
+        r.num_turns = 12;
+        r.tool_calls = 20;
+        assert!(!r.is_errored());
+    }
+
+    #[test]
+    fn test_model_matches() {
+        assert!(model_matches("sonnet", "claude-sonnet-4-5-20250929"));
+        assert!(model_matches(
+            "claude-opus-4-1-20250805",
+            "claude-opus-4-1-20250805"
+        ));
+        assert!(!model_matches("opus", "claude-sonnet-4-5-20250929"));
+    }
+
+    #[test]
+    fn test_prompt_hash_stable() {
+        assert_eq!(prompt_hash(""), "cbf29ce484222325");
+        assert_eq!(prompt_hash("same prompt"), prompt_hash("same prompt"));
+        assert_ne!(prompt_hash("prompt a"), prompt_hash("prompt b"));
     }
 }
@@ -4,9 +4,100 @@
 //! checkouts. The fmm variant gets sidecars + CLAUDE.md + MCP config installed.
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Number of times `fmm generate` is attempted before giving up.
+const GENERATE_ATTEMPTS: u32 = 2;
+
+/// Clone depth used unless configured otherwise (`None` = full history).
+pub const DEFAULT_CLONE_DEPTH: Option<u32> = Some(1);
+
+/// How long a single `git clone` may run before it is killed.
+pub const DEFAULT_CLONE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How often a running clone is checked for completion or timeout.
+const CLONE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// fmm infrastructure written into the FMM workspace (sidecars, CLAUDE.md,
+/// skill, MCP config), as git pathspec globs. These are never Claude's work:
+/// they're excluded from diff stats and kept out of commits and resets.
+pub const FMM_INFRA_PATHS: &[&str] = &["*.fmm", ".fmm", ".claude", ".mcp.json"];
+
+/// What `fmm init --all --no-generate` installs in the FMM workspace; any
+/// of these missing afterwards means a partial (weaker) treatment.
+pub const FMM_INIT_ARTIFACTS: &[&str] = &[
+    ".claude/CLAUDE.md",
+    ".mcp.json",
+    ".claude/skills/fmm-navigate.md",
+];
+
+/// Settings file the runners read with `--setting-sources local`, relative
+/// to the workspace; settings templates are merged into it.
+pub const LOCAL_SETTINGS_PATH: &str = ".claude/settings.local.json";
+
+/// Which workspaces a settings template is installed into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsScope {
+    /// The FMM workspace only, on top of what `fmm init` installed
+    #[default]
+    Fmm,
+    /// Both workspaces (the control runner then loads local settings too)
+    Both,
+}
+
+/// A `.claude/settings.json`-style file (`--settings-template`) merged into
+/// the sandbox workspaces before each run, to try custom MCP servers or hooks
+/// without changing `fmm init`. Recorded in the report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsTemplate {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub scope: SettingsScope,
+}
+
+impl SettingsTemplate {
+    /// Read the template, which must be a JSON object.
+    pub fn load(&self) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let content = fs::read_to_string(&self.path).with_context(|| {
+            format!("Failed to read settings template: {}", self.path.display())
+        })?;
+        match serde_json::from_str(&content) {
+            Ok(serde_json::Value::Object(settings)) => Ok(settings),
+            Ok(_) => anyhow::bail!(
+                "Settings template {} is not a JSON object",
+                self.path.display()
+            ),
+            Err(e) => Err(e).with_context(|| {
+                format!("Failed to parse settings template: {}", self.path.display())
+            }),
+        }
+    }
+}
+
+/// Outcome of sidecar generation, recorded in the report's setup metadata.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SidecarGeneration {
+    /// Number of `fmm generate` invocations (1 unless a retry was needed).
+    pub attempts: u32,
+    /// Whether the final invocation exited successfully.
+    pub succeeded: bool,
+    /// Non-empty stderr from each invocation.
+    pub stderr: Vec<String>,
+    /// `.fmm` files present after generation.
+    pub sidecar_count: usize,
+    /// Extra arguments passed to `fmm generate` (e.g. `--lang ts`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// `FMM_INIT_ARTIFACTS` that `fmm init` left out (partial treatment).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_artifacts: Vec<String>,
+}
 
 /// Sandbox for isolated repo comparison
 pub struct Sandbox {
@@ -16,6 +107,18 @@ pub struct Sandbox {
     pub control_dir: PathBuf,
     /// FMM variant directory (with sidecars + CLAUDE.md + MCP)
     pub fmm_dir: PathBuf,
+    /// Extra `fmm generate` arguments (validated; see `set_sidecar_args`)
+    sidecar_args: Vec<String>,
+    /// `fmm` binary to use instead of the `FMM_BIN`/PATH lookup
+    fmm_bin: Option<PathBuf>,
+    /// Root of a [`RepoCache`] to copy default-branch clones from
+    repo_cache: Option<PathBuf>,
+    /// `git clone --depth` (`None` = full history)
+    clone_depth: Option<u32>,
+    /// Kill a `git clone` that runs longer than this
+    clone_timeout: Duration,
+    /// Loaded settings template and where it goes
+    settings_template: Option<(SettingsScope, serde_json::Map<String, serde_json::Value>)>,
     /// Whether to cleanup on drop
     cleanup_on_drop: bool,
 }
@@ -34,10 +137,64 @@ impl Sandbox {
             root,
             control_dir,
             fmm_dir,
+            sidecar_args: vec![],
+            fmm_bin: None,
+            repo_cache: None,
+            clone_depth: DEFAULT_CLONE_DEPTH,
+            clone_timeout: DEFAULT_CLONE_TIMEOUT,
+            settings_template: None,
             cleanup_on_drop: true,
         })
     }
 
+    /// Append `args` to every `fmm generate` invocation (e.g. to scope
+    /// generation by language). Rejects shell metacharacters.
+    pub fn set_sidecar_args(&mut self, args: &[String]) -> Result<()> {
+        validate_sidecar_args(args)?;
+        self.sidecar_args = args.to_vec();
+        Ok(())
+    }
+
+    /// Use `path` as the `fmm` binary for sidecar generation and `fmm init`
+    /// (e.g. to compare two fmm builds).
+    pub fn set_fmm_bin(&mut self, path: &Path) {
+        self.fmm_bin = Some(path.to_path_buf());
+    }
+
+    /// Clone default-branch checkouts from the [`RepoCache`] rooted at
+    /// `root` when it holds the repo, instead of from the network.
+    pub fn set_repo_cache(&mut self, root: &Path) {
+        self.repo_cache = Some(root.to_path_buf());
+    }
+
+    /// Merge `template` into the workspaces' local settings whenever the fmm
+    /// integration is (re)installed.
+    pub fn set_settings_template(&mut self, template: &SettingsTemplate) -> Result<()> {
+        self.settings_template = Some((template.scope, template.load()?));
+        Ok(())
+    }
+
+    /// Clone with `--depth depth`, or the full history when `None`.
+    pub fn set_clone_depth(&mut self, depth: Option<u32>) {
+        self.clone_depth = depth;
+    }
+
+    /// Give up on a clone, or the fetch of a pinned commit, (with a "clone
+    /// timed out" / "fetch timed out" error) after `timeout`.
+    pub fn set_clone_timeout(&mut self, timeout: Duration) {
+        self.clone_timeout = timeout;
+    }
+
+    /// The `fmm` binary this sandbox runs: the `set_fmm_bin` override, else
+    /// `FMM_BIN` or PATH.
+    fn fmm_binary(&self) -> Result<PathBuf> {
+        match &self.fmm_bin {
+            Some(path) if path.exists() => Ok(path.clone()),
+            Some(path) => anyhow::bail!("fmm binary '{}' does not exist", path.display()),
+            None => find_fmm_binary(),
+        }
+    }
+
     /// Clone a repository into the sandbox (both control and fmm dirs).
     pub fn clone_repo(&self, url: &str, branch: Option<&str>) -> Result<()> {
         validate_repo_url(url)?;
@@ -46,11 +203,18 @@ impl Sandbox {
         Ok(())
     }
 
+    /// Clone a repository into the control dir only (control-only runs such
+    /// as `--compare-baselines`).
+    pub fn clone_control(&self, url: &str, branch: Option<&str>) -> Result<()> {
+        validate_repo_url(url)?;
+        self.clone_to_dir(url, branch, &self.control_dir)
+    }
+
     /// Clone a repository at a specific commit SHA.
     ///
-    /// Does a shallow clone then fetches the exact commit (needed for corpus
-    /// pinning where issues are tied to a specific commit). Shallow clones
-    /// only contain one commit, so we must fetch the target commit explicitly.
+    /// Clones then fetches the exact commit (needed for corpus pinning where
+    /// issues are tied to a specific commit). Shallow clones only contain the
+    /// branch tip, so the target commit is fetched explicitly at the same depth.
     pub fn clone_repo_at_commit(
         &self,
         url: &str,
@@ -61,15 +225,12 @@ impl Sandbox {
         for dir in [&self.control_dir, &self.fmm_dir] {
             self.clone_to_dir(url, branch, dir)?;
             // Fetch the exact commit (shallow clones don't have it)
-            let fetch = Command::new("git")
-                .args(["fetch", "--depth=1", "origin", commit])
-                .current_dir(dir)
-                .output()
-                .context("Failed to fetch commit")?;
-            if !fetch.status.success() {
-                let stderr = String::from_utf8_lossy(&fetch.stderr);
-                anyhow::bail!("git fetch {} failed: {}", commit, stderr.trim());
-            }
+            let mut fetch = Command::new("git");
+            fetch
+                .args(fetch_args(self.clone_depth, commit))
+                .current_dir(dir);
+            run_git(fetch, self.clone_timeout, "fetch")
+                .with_context(|| format!("Failed to fetch commit {}", commit))?;
             // Checkout the fetched commit
             let checkout = Command::new("git")
                 .args(["checkout", "FETCH_HEAD"])
@@ -85,26 +246,20 @@ impl Sandbox {
     }
 
     fn clone_to_dir(&self, url: &str, branch: Option<&str>, dir: &Path) -> Result<()> {
-        let mut cmd = Command::new("git");
-        cmd.arg("clone")
-            .arg("--depth")
-            .arg("1")
-            .arg("--single-branch");
-
-        if let Some(b) = branch {
-            cmd.arg("--branch").arg(b);
-        }
-
-        cmd.arg(url).arg(dir);
-
-        let output = cmd.output().context("Failed to execute git clone")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Git clone failed: {}", stderr);
+        // Cached checkouts are shallow, so they only stand in for depth-1 clones
+        if branch.is_none() && self.clone_depth == Some(1) {
+            if let Some(cached) = self.repo_cache.as_deref().map(|r| cached_repo_dir(r, url)) {
+                if cached.join(".git").is_dir() {
+                    return clone_from_cache(&cached, url, dir);
+                }
+            }
         }
 
-        Ok(())
+        let mut cmd = Command::new("git");
+        cmd.args(clone_args(self.clone_depth, branch))
+            .arg(url)
+            .arg(dir);
+        run_git(cmd, self.clone_timeout, "clone")
     }
 
     /// Get the current commit SHA from a directory
@@ -120,27 +275,93 @@ impl Sandbox {
             anyhow::bail!("Git rev-parse failed");
         }
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        parse_commit_sha(&output.stdout)
     }
 
     /// Generate FMM sidecars for the FMM variant using the `fmm` binary.
     ///
     /// Uses `fmm generate` which smartly creates new, updates stale, and
-    /// skips unchanged sidecars.
-    pub fn generate_fmm_sidecars(&self) -> Result<()> {
-        let fmm_path = find_fmm_binary()?;
+    /// skips unchanged sidecars. When `src_path` is given, generation is
+    /// scoped to that subdirectory (for monorepos). A failed invocation is
+    /// retried once; fmm's stderr is captured for the report.
+    pub fn generate_fmm_sidecars(&self, src_path: Option<&str>) -> Result<SidecarGeneration> {
+        let fmm_path = self.fmm_binary()?;
+        self.generate_fmm_sidecars_with(&fmm_path, src_path)
+    }
 
-        let output = Command::new(&fmm_path)
-            .arg("generate")
-            .current_dir(&self.fmm_dir)
-            .output()
-            .context("Failed to run `fmm generate`")?;
+    fn generate_fmm_sidecars_with(
+        &self,
+        fmm_path: &Path,
+        src_path: Option<&str>,
+    ) -> Result<SidecarGeneration> {
+        let mut generation = SidecarGeneration::default();
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            eprintln!("Warning: fmm generate had issues: {}", stderr.trim());
+        for _ in 0..GENERATE_ATTEMPTS {
+            generation.attempts += 1;
+
+            let mut cmd = Command::new(fmm_path);
+            cmd.arg("generate");
+            if let Some(sub) = src_path {
+                cmd.arg(sub);
+            }
+            cmd.args(&self.sidecar_args);
+
+            let output = cmd
+                .current_dir(&self.fmm_dir)
+                .output()
+                .context("Failed to run `fmm generate`")?;
+
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if !stderr.is_empty() {
+                generation.stderr.push(stderr.clone());
+            }
+
+            if output.status.success() {
+                generation.succeeded = true;
+                break;
+            }
+            eprintln!(
+                "Warning: fmm generate failed (attempt {}/{}): {}",
+                generation.attempts, GENERATE_ATTEMPTS, stderr
+            );
         }
 
+        generation.sidecar_count = self.count_sidecars();
+        generation.args = self.sidecar_args.clone();
+        Ok(generation)
+    }
+
+    /// Count `.fmm` sidecar files in the FMM variant workspace.
+    pub fn count_sidecars(&self) -> usize {
+        walkdir::WalkDir::new(&self.fmm_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("fmm"))
+            .count()
+    }
+
+    /// Validate that `src_path` is a relative directory inside both clones.
+    pub fn validate_src_path(&self, src_path: &str) -> Result<()> {
+        let rel = Path::new(src_path);
+        if rel.is_absolute()
+            || rel.components().any(|c| {
+                !matches!(
+                    c,
+                    std::path::Component::Normal(_) | std::path::Component::CurDir
+                )
+            })
+        {
+            anyhow::bail!(
+                "Source path must be a relative path inside the repository: {}",
+                src_path
+            );
+        }
+        // A control-only sandbox (`clone_control`) has no fmm dir to check
+        for dir in [&self.control_dir, &self.fmm_dir] {
+            if dir.exists() && !dir.join(rel).is_dir() {
+                anyhow::bail!("Source path '{}' does not exist in the clone", src_path);
+            }
+        }
         Ok(())
     }
 
@@ -153,10 +374,17 @@ impl Sandbox {
     ///
     /// The --no-generate flag skips sidecar generation since we already did it.
     /// Exp14 proved LLMs don't discover .fmm organically — this init is critical.
-    pub fn setup_fmm_integration(&self) -> Result<()> {
-        let fmm_path = find_fmm_binary()?;
+    /// A settings template, if set, is merged in afterwards.
+    ///
+    /// Init can exit 0 having installed only some of these, so returns the
+    /// `FMM_INIT_ARTIFACTS` that are missing (empty when complete).
+    pub fn setup_fmm_integration(&self) -> Result<Vec<String>> {
+        let fmm_path = self.fmm_binary()?;
+        self.setup_fmm_integration_with(&fmm_path)
+    }
 
-        let output = Command::new(&fmm_path)
+    fn setup_fmm_integration_with(&self, fmm_path: &Path) -> Result<Vec<String>> {
+        let output = Command::new(fmm_path)
             .args(["init", "--all", "--no-generate"])
             .current_dir(&self.fmm_dir)
             .output()
@@ -166,22 +394,73 @@ impl Sandbox {
             let stderr = String::from_utf8_lossy(&output.stderr);
             anyhow::bail!("fmm init --all failed: {}", stderr.trim());
         }
+        let missing = FMM_INIT_ARTIFACTS
+            .iter()
+            .filter(|artifact| !self.fmm_dir.join(artifact).is_file())
+            .map(|artifact| artifact.to_string())
+            .collect();
 
+        self.install_settings_template()?;
+        self.exclude_fmm_infra()?;
+        Ok(missing)
+    }
+
+    /// Merge the settings template into `LOCAL_SETTINGS_PATH` of each
+    /// workspace in its scope. In the control workspace the file is also
+    /// hidden from git, as fmm's infrastructure is in the FMM one.
+    fn install_settings_template(&self) -> Result<()> {
+        let Some((scope, ref template)) = self.settings_template else {
+            return Ok(());
+        };
+        let dirs: &[&PathBuf] = match scope {
+            SettingsScope::Fmm => &[&self.fmm_dir],
+            SettingsScope::Both => &[&self.control_dir, &self.fmm_dir],
+        };
+        for dir in dirs {
+            let path = dir.join(LOCAL_SETTINGS_PATH);
+            let mut settings = match fs::read_to_string(&path) {
+                Ok(content) => serde_json::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?,
+                Err(_) => serde_json::Value::Object(Default::default()),
+            };
+            merge_settings(&mut settings, template);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).context("Failed to create .claude")?;
+            }
+            fs::write(&path, serde_json::to_string_pretty(&settings)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        if scope == SettingsScope::Both {
+            exclude_from_git(&self.control_dir, &[LOCAL_SETTINGS_PATH])?;
+        }
         Ok(())
     }
 
+    /// Hide fmm infrastructure from git in the FMM workspace.
+    ///
+    /// Writes `FMM_INFRA_PATHS` to `.git/info/exclude` (local only, not a
+    /// tracked change), so Claude's `git add -A` won't commit them and
+    /// `git clean -fd` in `reset_git_state` leaves them in place.
+    pub fn exclude_fmm_infra(&self) -> Result<()> {
+        exclude_from_git(&self.fmm_dir, FMM_INFRA_PATHS)
+    }
+
     /// Reset git state in both sandbox dirs (between repeated runs).
-    pub fn reset_git_state(&self) -> Result<()> {
+    ///
+    /// Hard-resets to `commit_sha` (the clone's commit) rather than just
+    /// discarding working-tree edits, so commits made during a run are
+    /// undone too.
+    pub fn reset_git_state(&self, commit_sha: &str) -> Result<()> {
         for dir in [&self.control_dir, &self.fmm_dir] {
             if dir.exists() {
                 let output = Command::new("git")
-                    .args(["checkout", "."])
+                    .args(["reset", "--hard", "-q", commit_sha])
                     .current_dir(dir)
                     .output()
                     .context("Failed to reset git state")?;
                 if !output.status.success() {
                     let stderr = String::from_utf8_lossy(&output.stderr);
-                    anyhow::bail!("git checkout . failed: {}", stderr);
+                    anyhow::bail!("git reset --hard {} failed: {}", commit_sha, stderr);
                 }
                 let output = Command::new("git")
                     .args(["clean", "-fd"])
@@ -197,6 +476,33 @@ impl Sandbox {
         Ok(())
     }
 
+    /// Reset both sandboxes and re-establish the fmm treatment for the next run.
+    ///
+    /// `reset_git_state` reverts tracked files (which may include fmm-installed
+    /// ones), and Claude may have deleted or edited sidecars during the run, so
+    /// sidecars and integration are regenerated to give every run an equal
+    /// treatment.
+    pub fn reset_for_next_run(
+        &self,
+        commit_sha: &str,
+        src_path: Option<&str>,
+    ) -> Result<SidecarGeneration> {
+        let fmm_path = self.fmm_binary()?;
+        self.reset_for_next_run_with(&fmm_path, commit_sha, src_path)
+    }
+
+    fn reset_for_next_run_with(
+        &self,
+        fmm_path: &Path,
+        commit_sha: &str,
+        src_path: Option<&str>,
+    ) -> Result<SidecarGeneration> {
+        self.reset_git_state(commit_sha)?;
+        let mut generation = self.generate_fmm_sidecars_with(fmm_path, src_path)?;
+        generation.missing_artifacts = self.setup_fmm_integration_with(fmm_path)?;
+        Ok(generation)
+    }
+
     /// Disable cleanup on drop (for debugging/testing)
     #[cfg(test)]
     pub fn keep_on_drop(&mut self) {
@@ -219,6 +525,221 @@ impl Drop for Sandbox {
     }
 }
 
+/// Shallow default-branch clones shared by a batch's sandboxes, so a repo
+/// used by several corpus issues is fetched from the network once.
+///
+/// Lives in a per-batch temp directory removed on drop, so it never serves
+/// a stale checkout to a later batch.
+pub struct RepoCache {
+    root: PathBuf,
+    clone_timeout: Duration,
+}
+
+impl RepoCache {
+    /// Create an empty cache for a job
+    pub fn new(job_id: &str) -> Result<Self> {
+        validate_job_id(job_id)?;
+        let root = std::env::temp_dir().join(format!("fmm-repos-{}", job_id));
+        fs::create_dir_all(&root).context("Failed to create repo cache")?;
+        Ok(Self {
+            root,
+            clone_timeout: DEFAULT_CLONE_TIMEOUT,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Give up on a clone after `timeout` (see [`Sandbox::set_clone_timeout`]).
+    pub fn set_clone_timeout(&mut self, timeout: Duration) {
+        self.clone_timeout = timeout;
+    }
+
+    /// Clone `url` into the cache unless it is already there.
+    pub fn ensure(&self, url: &str) -> Result<PathBuf> {
+        validate_repo_url(url)?;
+        let dir = cached_repo_dir(&self.root, url);
+        if dir.join(".git").is_dir() {
+            return Ok(dir);
+        }
+        let mut cmd = Command::new("git");
+        cmd.args(["clone", "--depth", "1", "--single-branch"])
+            .arg(url)
+            .arg(&dir);
+        if let Err(e) = run_git(cmd, self.clone_timeout, "clone") {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+        Ok(dir)
+    }
+}
+
+/// Run a network `git` command (`action` is `clone` or `fetch`), killing it if
+/// it is still running after `timeout` so a huge or stalled transfer can't
+/// hang the job.
+fn run_git(mut cmd: Command, timeout: Duration, action: &str) -> Result<()> {
+    let mut child = cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to execute git {}", action))?;
+
+    // Drain stderr as it arrives so a chatty clone can't block on a full pipe
+    let mut stderr = child
+        .stderr
+        .take()
+        .with_context(|| format!("git {} stderr not piped", action))?;
+    let reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| format!("Failed to wait for git {}", action))?
+        {
+            break status;
+        }
+        if start.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!(
+                "Git {} timed out after {}s (see --clone-timeout)",
+                action,
+                timeout.as_secs_f32()
+            );
+        }
+        std::thread::sleep(CLONE_POLL_INTERVAL);
+    };
+
+    if !status.success() {
+        let stderr = reader.join().unwrap_or_default();
+        anyhow::bail!("Git {} failed: {}", action, stderr.trim());
+    }
+    Ok(())
+}
+
+impl Drop for RepoCache {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.root) {
+            eprintln!("Warning: Failed to cleanup repo cache: {}", e);
+        }
+    }
+}
+
+/// Where the cache rooted at `root` keeps `url` (`https://github.com/o/r`
+/// becomes `github.com_o_r`).
+pub(crate) fn cached_repo_dir(root: &Path, url: &str) -> PathBuf {
+    let name: String = url
+        .trim_start_matches("https://")
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    root.join(name)
+}
+
+/// Append `patterns` (once each) to `dir`'s `.git/info/exclude`.
+fn exclude_from_git(dir: &Path, patterns: &[&str]) -> Result<()> {
+    let info_dir = dir.join(".git").join("info");
+    fs::create_dir_all(&info_dir).context("Failed to create .git/info")?;
+    let exclude_path = info_dir.join("exclude");
+
+    let mut exclude = fs::read_to_string(&exclude_path).unwrap_or_default();
+    for pattern in patterns {
+        if !exclude.lines().any(|l| l.trim() == *pattern) {
+            if !exclude.is_empty() && !exclude.ends_with('\n') {
+                exclude.push('\n');
+            }
+            exclude.push_str(pattern);
+            exclude.push('\n');
+        }
+    }
+    fs::write(&exclude_path, exclude).context("Failed to write .git/info/exclude")
+}
+
+/// Merge `template` into `settings`: nested objects merge key by key, and any
+/// other template value (including arrays) replaces the installed one.
+fn merge_settings(
+    settings: &mut serde_json::Value,
+    template: &serde_json::Map<String, serde_json::Value>,
+) {
+    let serde_json::Value::Object(installed) = settings else {
+        *settings = serde_json::Value::Object(template.clone());
+        return;
+    };
+    for (key, value) in template {
+        match (installed.get_mut(key), value) {
+            (Some(existing @ serde_json::Value::Object(_)), serde_json::Value::Object(nested)) => {
+                merge_settings(existing, nested)
+            }
+            _ => {
+                installed.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// `git clone` arguments (before the url and target dir) for a clone of
+/// `depth` commits, or the full history of every branch when `None`.
+pub(crate) fn clone_args(depth: Option<u32>, branch: Option<&str>) -> Vec<String> {
+    let mut args = vec!["clone".to_string()];
+    if let Some(depth) = depth {
+        args.extend(["--depth".to_string(), depth.to_string()]);
+        args.push("--single-branch".to_string());
+    }
+    if let Some(b) = branch {
+        args.extend(["--branch".to_string(), b.to_string()]);
+    }
+    args
+}
+
+/// `git fetch` arguments for pinning `commit`, at `depth` when shallow.
+fn fetch_args(depth: Option<u32>, commit: &str) -> Vec<String> {
+    let mut args = vec!["fetch".to_string()];
+    if let Some(depth) = depth {
+        args.push(format!("--depth={}", depth));
+    }
+    args.extend(["origin".to_string(), commit.to_string()]);
+    args
+}
+
+/// Copy a cached checkout into `dir`, pointing `origin` back at `url` so
+/// later fetches (e.g. of a pinned commit) still reach the real remote.
+fn clone_from_cache(cached: &Path, url: &str, dir: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .args(["clone", "--depth", "1", "--no-local"])
+        .arg(format!("file://{}", cached.display()))
+        .arg(dir)
+        .output()
+        .context("Failed to execute git clone")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Git clone from repo cache failed: {}", stderr.trim());
+    }
+    let output = Command::new("git")
+        .args(["remote", "set-url", "origin", url])
+        .current_dir(dir)
+        .output()
+        .context("Failed to execute git remote")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git remote set-url failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
 /// Find the `fmm` binary in PATH or a well-known location.
 fn find_fmm_binary() -> Result<PathBuf> {
     // Check FMM_BIN env var first (for testing / custom installs)
@@ -257,6 +778,79 @@ fn find_fmm_binary() -> Result<PathBuf> {
     )
 }
 
+/// Source extensions mapped to the language they indicate.
+const LANGUAGE_EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("go", "go"),
+    ("py", "python"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("java", "java"),
+    ("rb", "ruby"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+];
+
+/// Directories skipped when counting source files.
+const IGNORED_DIRS: &[&str] = &[".git", "node_modules", "target", "vendor", "dist", "build"];
+
+/// Detect the dominant language of a checkout.
+///
+/// Counts source files by extension (skipping vendored/build dirs), falling
+/// back to manifest files (`Cargo.toml`, `go.mod`, `package.json`, ...) when
+/// no recognized sources are found.
+pub fn detect_primary_language(dir: &Path) -> Option<String> {
+    let mut counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+
+    let walker = walkdir::WalkDir::new(dir).into_iter().filter_entry(|e| {
+        !(e.file_type().is_dir()
+            && IGNORED_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+    });
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if let Some(lang) = path_language(entry.path()) {
+            *counts.entry(lang).or_insert(0) += 1;
+        }
+    }
+
+    // Highest count wins; ties broken by name for determinism
+    if let Some((lang, _)) = counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+    {
+        return Some(lang.to_string());
+    }
+
+    let manifests = [
+        ("Cargo.toml", "rust"),
+        ("go.mod", "go"),
+        ("pyproject.toml", "python"),
+        ("setup.py", "python"),
+        ("tsconfig.json", "typescript"),
+        ("package.json", "javascript"),
+    ];
+    manifests
+        .iter()
+        .find(|(file, _)| dir.join(file).exists())
+        .map(|(_, lang)| lang.to_string())
+}
+
+/// Language of a source file, by extension (see [`LANGUAGE_EXTENSIONS`]).
+pub(crate) fn path_language(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?;
+    LANGUAGE_EXTENSIONS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, lang)| *lang)
+}
+
 /// Validate job_id contains only safe path characters
 fn validate_job_id(job_id: &str) -> Result<()> {
     if job_id.is_empty() {
@@ -274,6 +868,40 @@ fn validate_job_id(job_id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Validate extra `fmm generate` arguments. They're passed as argv (never
+/// through a shell), but metacharacters signal a mistake like a quoted
+/// pipeline, so they're rejected outright.
+pub fn validate_sidecar_args(args: &[String]) -> Result<()> {
+    const META: &[char] = &[
+        ';', '&', '|', '$', '`', '<', '>', '(', ')', '{', '}', '*', '?', '!', '\\', '"', '\'',
+        '\n', '\0',
+    ];
+    for arg in args {
+        if arg.is_empty() || arg.contains(META) || arg.contains(char::is_whitespace) {
+            anyhow::bail!("Invalid fmm generate argument: {:?}", arg);
+        }
+    }
+    Ok(())
+}
+
+/// Validate `git rev-parse` output as a full SHA-1 or SHA-256 commit id.
+///
+/// The SHA ends up in cache keys, report paths, and `git diff` arguments, so
+/// anything else (non-UTF-8 bytes, stray output) is an error rather than
+/// something to pass along.
+pub(crate) fn parse_commit_sha(stdout: &[u8]) -> Result<String> {
+    let text = std::str::from_utf8(stdout).context("git rev-parse output is not valid UTF-8")?;
+    let sha = text.trim();
+    let valid_len = sha.len() == 40 || sha.len() == 64;
+    if !valid_len || !sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+        anyhow::bail!(
+            "git rev-parse returned an invalid commit SHA: {:?} (expected 40 or 64 hex characters)",
+            sha
+        );
+    }
+    Ok(sha.to_string())
+}
+
 /// Validate repository URL is a safe HTTPS git URL
 fn validate_repo_url(url: &str) -> Result<()> {
     if !url.starts_with("https://") {
@@ -304,6 +932,22 @@ mod tests {
         assert!(!sandbox.root.exists());
     }
 
+    #[test]
+    fn test_commit_sha_validated() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            parse_commit_sha(format!("{}\n", sha).as_bytes()).unwrap(),
+            sha
+        );
+
+        let err = parse_commit_sha(b"not-a-sha-at-all-but-exactly-forty-chars\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid commit SHA"), "{err}");
+        assert!(parse_commit_sha(b"abc123\n").is_err());
+        assert!(parse_commit_sha(b"0123456789abcdef0123\xff\xfe456789abcdef012345").is_err());
+    }
+
     #[test]
     fn test_sandbox_rejects_traversal_job_id() {
         assert!(Sandbox::new("../escape").is_err());
@@ -401,4 +1045,444 @@ mod tests {
         assert!(result.is_err());
         std::env::remove_var("FMM_BIN");
     }
+
+    #[cfg(unix)]
+    fn write_fmm_shim(dir: &Path, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("fmm");
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fmm_bin_override() {
+        let bins = tempfile::tempdir().unwrap();
+        let log = bins.path().join("calls.log");
+        let shim = write_fmm_shim(bins.path(), &format!("echo \"$0\" >> {}", log.display()));
+
+        let mut sandbox = Sandbox::new("fmm-bin-override-001").unwrap();
+        fs::create_dir_all(&sandbox.fmm_dir).unwrap();
+        sandbox.set_fmm_bin(&shim);
+        sandbox.generate_fmm_sidecars(None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&log).unwrap().trim(),
+            shim.to_str().unwrap()
+        );
+
+        sandbox.set_fmm_bin(&bins.path().join("missing"));
+        let err = sandbox.generate_fmm_sidecars(None).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_settings_template_merged_into_fmm_workspace() {
+        let mut sandbox = Sandbox::new("settings-template-001").unwrap();
+        fs::create_dir_all(&sandbox.fmm_dir).unwrap();
+        fs::create_dir_all(&sandbox.control_dir).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        // Stand-in for `fmm init` installing its own local settings
+        let shim = write_fmm_shim(
+            dir.path(),
+            r#"mkdir -p .claude && echo '{"enabledMcpjsonServers":["fmm"],"permissions":{"allow":["Read"]}}' > .claude/settings.local.json"#,
+        );
+        let template = dir.path().join("settings.json");
+        fs::write(
+            &template,
+            r#"{"hooks":{"Stop":[]},"permissions":{"deny":["WebFetch"]}}"#,
+        )
+        .unwrap();
+
+        sandbox
+            .set_settings_template(&SettingsTemplate {
+                path: template.clone(),
+                scope: SettingsScope::Fmm,
+            })
+            .unwrap();
+        sandbox.setup_fmm_integration_with(&shim).unwrap();
+
+        let installed: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(sandbox.fmm_dir.join(LOCAL_SETTINGS_PATH)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(installed["enabledMcpjsonServers"][0], "fmm");
+        assert_eq!(installed["permissions"]["allow"][0], "Read");
+        assert_eq!(installed["permissions"]["deny"][0], "WebFetch");
+        assert!(installed["hooks"]["Stop"].is_array());
+        assert!(!sandbox.control_dir.join(LOCAL_SETTINGS_PATH).exists());
+
+        sandbox
+            .set_settings_template(&SettingsTemplate {
+                path: template,
+                scope: SettingsScope::Both,
+            })
+            .unwrap();
+        sandbox.setup_fmm_integration_with(&shim).unwrap();
+        let control = fs::read_to_string(sandbox.control_dir.join(LOCAL_SETTINGS_PATH)).unwrap();
+        assert!(control.contains("WebFetch"));
+        assert!(!control.contains("enabledMcpjsonServers"));
+        let exclude = fs::read_to_string(sandbox.control_dir.join(".git/info/exclude")).unwrap();
+        assert!(exclude.lines().any(|l| l == LOCAL_SETTINGS_PATH));
+
+        fs::write(dir.path().join("bad.json"), "[1, 2]").unwrap();
+        let err = sandbox
+            .set_settings_template(&SettingsTemplate {
+                path: dir.path().join("bad.json"),
+                scope: SettingsScope::Fmm,
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("not a JSON object"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_partial_fmm_init_reports_missing_artifacts() {
+        let sandbox = Sandbox::new("partial-init-001").unwrap();
+        fs::create_dir_all(&sandbox.fmm_dir).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        // Exits 0 having installed CLAUDE.md only
+        let shim = write_fmm_shim(
+            dir.path(),
+            "mkdir -p .claude && echo fmm > .claude/CLAUDE.md",
+        );
+
+        let missing = sandbox.setup_fmm_integration_with(&shim).unwrap();
+        assert_eq!(missing, [".mcp.json", ".claude/skills/fmm-navigate.md"]);
+
+        let shim = write_fmm_shim(
+            dir.path(),
+            "mkdir -p .claude/skills && touch .claude/CLAUDE.md .mcp.json .claude/skills/fmm-navigate.md",
+        );
+        assert!(sandbox
+            .setup_fmm_integration_with(&shim)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_generate_scoped_to_src_path() {
+        let sandbox = Sandbox::new("src-path-gen-001").unwrap();
+        fs::create_dir_all(sandbox.fmm_dir.join("packages/core")).unwrap();
+        let shim_dir = tempfile::tempdir().unwrap();
+        let log = shim_dir.path().join("args.log");
+        let shim = write_fmm_shim(
+            shim_dir.path(),
+            &format!("echo \"$@\" >> {}", log.display()),
+        );
+
+        let generation = sandbox
+            .generate_fmm_sidecars_with(&shim, Some("packages/core"))
+            .unwrap();
+        assert_eq!(generation.attempts, 1);
+
+        let args = fs::read_to_string(&log).unwrap();
+        assert_eq!(args.trim(), "generate packages/core");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_generate_passes_sidecar_args() {
+        let mut sandbox = Sandbox::new("sidecar-args-001").unwrap();
+        fs::create_dir_all(sandbox.fmm_dir.join("src")).unwrap();
+        let shim_dir = tempfile::tempdir().unwrap();
+        let log = shim_dir.path().join("args.log");
+        let shim = write_fmm_shim(
+            shim_dir.path(),
+            &format!("echo \"$@\" >> {}", log.display()),
+        );
+
+        let extra = vec!["--lang".to_string(), "ts".to_string()];
+        sandbox.set_sidecar_args(&extra).unwrap();
+        let generation = sandbox
+            .generate_fmm_sidecars_with(&shim, Some("src"))
+            .unwrap();
+
+        let args = fs::read_to_string(&log).unwrap();
+        assert_eq!(args.trim(), "generate src --lang ts");
+        assert_eq!(generation.args, extra);
+    }
+
+    #[test]
+    fn test_validate_sidecar_args() {
+        let ok = |a: &[&str]| {
+            validate_sidecar_args(&a.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+        };
+        assert!(ok(&["--lang", "ts", "--ext=.tsx"]).is_ok());
+        assert!(ok(&[]).is_ok());
+        assert!(ok(&["--lang; rm -rf /"]).is_err());
+        assert!(ok(&["$(whoami)"]).is_err());
+        assert!(ok(&["a|b"]).is_err());
+        assert!(ok(&["--lang ts"]).is_err());
+        assert!(ok(&[""]).is_err());
+    }
+
+    #[test]
+    fn test_validate_src_path() {
+        let sandbox = Sandbox::new("src-path-val-001").unwrap();
+        for dir in [&sandbox.control_dir, &sandbox.fmm_dir] {
+            fs::create_dir_all(dir.join("packages/core")).unwrap();
+        }
+
+        assert!(sandbox.validate_src_path("packages/core").is_ok());
+        assert!(sandbox.validate_src_path("packages/missing").is_err());
+        assert!(sandbox.validate_src_path("../outside").is_err());
+        assert!(sandbox.validate_src_path("/etc").is_err());
+    }
+
+    #[test]
+    fn test_detect_primary_language_rust() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        fs::write(dir.path().join("build.py"), "").unwrap();
+
+        assert_eq!(detect_primary_language(dir.path()).as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_detect_primary_language_skips_vendored_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        for i in 0..5 {
+            fs::write(dir.path().join(format!("node_modules/pkg/{}.js", i)), "").unwrap();
+        }
+        fs::write(dir.path().join("main.go"), "package main").unwrap();
+
+        assert_eq!(detect_primary_language(dir.path()).as_deref(), Some("go"));
+    }
+
+    #[test]
+    fn test_detect_primary_language_manifest_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(
+            detect_primary_language(dir.path()).as_deref(),
+            Some("javascript")
+        );
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(detect_primary_language(empty.path()).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_generate_retries_once_and_captures_stderr() {
+        let sandbox = Sandbox::new("gen-retry-001").unwrap();
+        fs::create_dir_all(&sandbox.fmm_dir).unwrap();
+        let shim_dir = tempfile::tempdir().unwrap();
+        let marker = shim_dir.path().join("called");
+        let shim = write_fmm_shim(
+            shim_dir.path(),
+            &format!(
+                "if [ -f {m} ]; then touch main.rs.fmm; exit 0; fi\n\
+                 touch {m}\necho 'transient lock error' >&2\nexit 1",
+                m = marker.display()
+            ),
+        );
+
+        let generation = sandbox.generate_fmm_sidecars_with(&shim, None).unwrap();
+        assert_eq!(generation.attempts, 2);
+        assert!(generation.succeeded);
+        assert_eq!(generation.stderr, vec!["transient lock error"]);
+        assert_eq!(generation.sidecar_count, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_generate_gives_up_after_retry() {
+        let sandbox = Sandbox::new("gen-retry-002").unwrap();
+        fs::create_dir_all(&sandbox.fmm_dir).unwrap();
+        let shim_dir = tempfile::tempdir().unwrap();
+        let shim = write_fmm_shim(shim_dir.path(), "echo 'boom' >&2\nexit 1");
+
+        let generation = sandbox.generate_fmm_sidecars_with(&shim, None).unwrap();
+        assert_eq!(generation.attempts, 2);
+        assert!(!generation.succeeded);
+        assert_eq!(generation.sidecar_count, 0);
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_clone_args_follow_depth() {
+        assert_eq!(
+            clone_args(Some(1), None),
+            ["clone", "--depth", "1", "--single-branch"]
+        );
+        assert_eq!(
+            clone_args(Some(50), Some("dev")),
+            [
+                "clone",
+                "--depth",
+                "50",
+                "--single-branch",
+                "--branch",
+                "dev"
+            ]
+        );
+        // Full history: every branch, no depth limit
+        assert_eq!(clone_args(None, Some("dev")), ["clone", "--branch", "dev"]);
+
+        assert_eq!(
+            fetch_args(Some(10), "abc123"),
+            ["fetch", "--depth=10", "origin", "abc123"]
+        );
+        assert_eq!(fetch_args(None, "abc123"), ["fetch", "origin", "abc123"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_slow_clone_killed_at_timeout() {
+        use std::os::unix::fs::PermissionsExt;
+        let bins = tempfile::tempdir().unwrap();
+        let git = bins.path().join("git");
+        fs::write(&git, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        fs::set_permissions(&git, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let started = Instant::now();
+        let err = run_git(Command::new(&git), Duration::from_millis(300), "clone").unwrap_err();
+        assert!(err.to_string().contains("clone timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // A pinned commit's fetch gets the same limit
+        let started = Instant::now();
+        let err = run_git(Command::new(&git), Duration::from_millis(300), "fetch").unwrap_err();
+        assert!(err.to_string().contains("fetch timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        fs::write(
+            &git,
+            "#!/bin/sh\necho 'repository not found' >&2\nexit 128\n",
+        )
+        .unwrap();
+        let err = run_git(Command::new(&git), Duration::from_secs(10), "clone").unwrap_err();
+        assert_eq!(err.to_string(), "Git clone failed: repository not found");
+    }
+
+    #[test]
+    fn test_clone_copies_from_repo_cache() {
+        let cache = tempfile::tempdir().unwrap();
+        let url = "https://github.com/o/r";
+        let cached = cached_repo_dir(cache.path(), url);
+        fs::create_dir_all(&cached).unwrap();
+        git(&cached, &["init", "-q"]);
+        fs::write(cached.join("lib.rs"), "fn a() {}\n").unwrap();
+        git(&cached, &["add", "-A"]);
+        git(&cached, &["commit", "-qm", "init"]);
+
+        // No network: the repo only exists in the cache
+        let mut sandbox = Sandbox::new("repo-cache-clone-001").unwrap();
+        sandbox.set_repo_cache(cache.path());
+        sandbox.clone_repo(url, None).unwrap();
+
+        for dir in [&sandbox.control_dir, &sandbox.fmm_dir] {
+            assert!(dir.join("lib.rs").exists());
+            let origin = Command::new("git")
+                .args(["remote", "get-url", "origin"])
+                .current_dir(dir)
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&origin.stdout).trim(), url);
+        }
+    }
+
+    #[test]
+    fn test_fmm_infra_not_counted_and_survives_reset() {
+        let sandbox = Sandbox::new("fmm-infra-exclude-001").unwrap();
+        let dir = &sandbox.fmm_dir;
+        fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "-q"]);
+        fs::write(dir.join("lib.rs"), "fn a() {}\n").unwrap();
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-qm", "init"]);
+        let base = sandbox.get_commit_sha(dir).unwrap();
+
+        // fmm setup
+        fs::write(dir.join("lib.rs.fmm"), "exports: [a]\n").unwrap();
+        fs::create_dir_all(dir.join(".claude")).unwrap();
+        fs::write(dir.join(".claude/CLAUDE.md"), "use fmm\n").unwrap();
+        fs::write(dir.join(".mcp.json"), "{}\n").unwrap();
+        sandbox.exclude_fmm_infra().unwrap();
+        sandbox.exclude_fmm_infra().unwrap(); // idempotent
+
+        // Claude edits one file and commits everything
+        fs::write(dir.join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-qm", "fix"]);
+
+        let eval = crate::evaluator::evaluate(dir).unwrap();
+        assert_eq!(eval.files_touched, 1);
+        assert_eq!(eval.diff_lines_added, 1);
+
+        sandbox.reset_git_state(&base).unwrap();
+        assert!(dir.join("lib.rs.fmm").exists());
+        assert!(dir.join(".claude/CLAUDE.md").exists());
+        assert!(dir.join(".mcp.json").exists());
+
+        let exclude = fs::read_to_string(dir.join(".git/info/exclude")).unwrap();
+        assert_eq!(exclude.lines().filter(|l| *l == ".mcp.json").count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fmm_treatment_restored_before_second_run() {
+        let sandbox = Sandbox::new("fmm-reset-runs-001").unwrap();
+        let dir = &sandbox.fmm_dir;
+        fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "-q"]);
+        fs::write(dir.join("lib.rs"), "fn a() {}\n").unwrap();
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-qm", "init"]);
+        let base = sandbox.get_commit_sha(dir).unwrap();
+
+        let shim_dir = tempfile::tempdir().unwrap();
+        let shim = write_fmm_shim(
+            shim_dir.path(),
+            "case \"$1\" in\n\
+             generate) echo 'exports: [a]' > lib.rs.fmm ;;\n\
+             init) mkdir -p .claude && echo fmm > .claude/CLAUDE.md && echo '{}' > .mcp.json ;;\n\
+             esac",
+        );
+
+        let runs = 2;
+        let mut sidecars = sandbox.generate_fmm_sidecars_with(&shim, None).unwrap();
+        sandbox.setup_fmm_integration_with(&shim).unwrap();
+
+        for run_idx in 0..runs {
+            // Every measured run starts with the full treatment in place
+            assert_eq!(sidecars.sidecar_count, 1, "run {}", run_idx + 1);
+            assert!(dir.join("lib.rs.fmm").exists(), "run {}", run_idx + 1);
+            assert!(dir.join(".claude/CLAUDE.md").exists());
+            assert!(dir.join(".mcp.json").exists());
+            assert_eq!(
+                fs::read_to_string(dir.join("lib.rs")).unwrap(),
+                "fn a() {}\n"
+            );
+            assert_eq!(sandbox.get_commit_sha(dir).unwrap(), base);
+
+            // Simulated run: Claude edits source, commits, and removes fmm files
+            fs::write(dir.join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+            git(dir, &["commit", "-qam", "fix"]);
+            fs::remove_file(dir.join("lib.rs.fmm")).unwrap();
+            fs::remove_dir_all(dir.join(".claude")).unwrap();
+
+            if run_idx + 1 < runs {
+                sidecars = sandbox.reset_for_next_run_with(&shim, &base, None).unwrap();
+            }
+        }
+    }
 }
@@ -0,0 +1,169 @@
+//! SQLite export of batch results for tracking FMM's effect across batches
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use crate::aggregate::AggregateReport;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_id TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    model TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    issue INTEGER,
+    language TEXT NOT NULL,
+    size TEXT NOT NULL,
+    control_tool_calls REAL NOT NULL,
+    fmm_tool_calls REAL NOT NULL,
+    control_tokens REAL,
+    fmm_tokens REAL,
+    control_cost REAL NOT NULL,
+    fmm_cost REAL NOT NULL,
+    control_duration_ms REAL,
+    fmm_duration_ms REAL,
+    control_read_calls REAL,
+    fmm_read_calls REAL,
+    delta_pct REAL NOT NULL,
+    control_grade TEXT NOT NULL,
+    fmm_grade TEXT NOT NULL
+)";
+
+/// Append one `runs` row per issue of `aggregate`, creating the database and
+/// table if needed. Returns the number of rows inserted.
+pub fn export_aggregate(
+    path: &Path,
+    aggregate: &AggregateReport,
+    job_id: &str,
+    timestamp: &str,
+) -> Result<usize> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open SQLite database {}", path.display()))?;
+    conn.execute(SCHEMA, [])
+        .context("Failed to create runs table")?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO runs (
+                job_id, timestamp, model, repo, issue, language, size,
+                control_tool_calls, fmm_tool_calls, control_tokens, fmm_tokens,
+                control_cost, fmm_cost, control_duration_ms, fmm_duration_ms,
+                control_read_calls, fmm_read_calls, delta_pct, control_grade, fmm_grade
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+        )?;
+        for r in &aggregate.per_issue {
+            let (repo, issue) = split_issue_id(&r.id);
+            insert.execute(params![
+                job_id,
+                timestamp,
+                aggregate.model,
+                repo,
+                issue,
+                r.language,
+                r.size,
+                r.control_tool_calls,
+                r.fmm_tool_calls,
+                r.control_tokens,
+                r.fmm_tokens,
+                r.control_cost,
+                r.fmm_cost,
+                r.control_duration_ms,
+                r.fmm_duration_ms,
+                r.control_read_calls,
+                r.fmm_read_calls,
+                r.delta_pct,
+                r.control_grade,
+                r.fmm_grade,
+            ])?;
+        }
+    }
+    tx.commit().context("Failed to write runs to SQLite")?;
+
+    Ok(aggregate.per_issue.len())
+}
+
+/// Split a corpus id (`owner/repo#123`) into repo and issue number.
+fn split_issue_id(id: &str) -> (&str, Option<u64>) {
+    match id.rsplit_once('#') {
+        Some((repo, number)) => match number.parse() {
+            Ok(n) => (repo, Some(n)),
+            Err(_) => (id, None),
+        },
+        None => (id, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::IssueResult;
+
+    #[test]
+    fn export_appends_rows_per_issue() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("bench.db");
+
+        let mut agg = AggregateReport::from_reports(vec![], "sonnet", 1, 2);
+        for (id, fmm_tools) in [("o/r#1", 4.0), ("o/r#2", 8.0)] {
+            agg.per_issue.push(IssueResult {
+                id: id.to_string(),
+                language: "rust".to_string(),
+                size: "medium".to_string(),
+                control_tool_calls: 10.0,
+                fmm_tool_calls: fmm_tools,
+                control_grade: "B".to_string(),
+                fmm_grade: "A".to_string(),
+                ..Default::default()
+            });
+        }
+
+        assert_eq!(
+            export_aggregate(&db, &agg, "job-1", "2026-01-01T00:00:00Z").unwrap(),
+            2
+        );
+        // A second batch accumulates in the same table
+        assert_eq!(
+            export_aggregate(&db, &agg, "job-2", "2026-01-08T00:00:00Z").unwrap(),
+            2
+        );
+
+        let conn = Connection::open(&db).unwrap();
+        let rows: Vec<(String, String, i64, f64, String)> = conn
+            .prepare("SELECT job_id, repo, issue, fmm_tool_calls, fmm_grade FROM runs ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[0],
+            (
+                "job-1".to_string(),
+                "o/r".to_string(),
+                1,
+                4.0,
+                "A".to_string()
+            )
+        );
+        assert_eq!(rows[3].0, "job-2");
+        assert_eq!(rows[3].2, 2);
+    }
+
+    #[test]
+    fn split_issue_id_forms() {
+        assert_eq!(split_issue_id("owner/repo#12"), ("owner/repo", Some(12)));
+        assert_eq!(split_issue_id("custom-id"), ("custom-id", None));
+    }
+}
@@ -1,5 +1,6 @@
 //! Benchmark task definitions
 
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
 /// A benchmark task to run against a repository
@@ -22,6 +23,25 @@ pub struct Task {
     /// Maximum budget for this task in USD
     #[serde(default = "default_max_budget")]
     pub max_budget_usd: f64,
+    /// Abort the run once it makes more tool calls than this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<u32>,
+    /// Commands (argv) run in both sandboxes before each run, e.g. `["npm", "ci"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup_commands: Vec<Vec<String>>,
+}
+
+impl Task {
+    /// Scope this task's prompt to a subdirectory of the repository.
+    ///
+    /// Applied identically to both conditions.
+    pub fn scoped_to(mut self, src_path: &str) -> Self {
+        self.prompt = format!(
+            "Focus on the `{}` directory of this repository.\n\n{}",
+            src_path, self.prompt
+        );
+        self
+    }
 }
 
 fn default_max_turns() -> u32 {
@@ -57,6 +77,23 @@ impl std::fmt::Display for TaskCategory {
     }
 }
 
+impl std::str::FromStr for TaskCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "exploration" => Ok(TaskCategory::Exploration),
+            "understanding" => Ok(TaskCategory::Understanding),
+            "dependencies" => Ok(TaskCategory::Dependencies),
+            "exports" => Ok(TaskCategory::Exports),
+            other => Err(format!(
+                "unknown task category '{}' (expected exploration, understanding, dependencies, or exports)",
+                other
+            )),
+        }
+    }
+}
+
 /// A set of tasks for benchmarking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskSet {
@@ -69,6 +106,68 @@ pub struct TaskSet {
 }
 
 impl TaskSet {
+    /// Load a custom task set from a JSON file (`--tasks <path>`).
+    pub fn from_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to load custom tasks from {}", path))?;
+
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse custom tasks from {}", path))
+    }
+
+    /// Structural problems that would waste a run: duplicate or empty ids,
+    /// empty prompts or ones over `max_prompt_bytes`, and non-positive
+    /// budgets, turn caps, or tool-call caps. Unknown categories already fail
+    /// to parse.
+    pub fn problems(&self, max_prompt_bytes: usize) -> Vec<String> {
+        let mut problems = vec![];
+        if self.tasks.is_empty() {
+            problems.push("Task set has no tasks".to_string());
+        }
+        let mut seen: Vec<&str> = vec![];
+        for (i, task) in self.tasks.iter().enumerate() {
+            let label = if task.id.trim().is_empty() {
+                problems.push(format!("Task {} has an empty id", i + 1));
+                format!("Task {}", i + 1)
+            } else {
+                if let Some(first) = seen.iter().position(|id| *id == task.id) {
+                    problems.push(format!(
+                        "Duplicate task id '{}' (tasks {} and {})",
+                        task.id,
+                        first + 1,
+                        i + 1
+                    ));
+                }
+                format!("Task '{}'", task.id)
+            };
+            seen.push(&task.id);
+
+            if task.prompt.trim().is_empty() {
+                problems.push(format!("{}: prompt is empty", label));
+            } else if task.prompt.len() > max_prompt_bytes {
+                problems.push(format!(
+                    "{}: prompt is {} bytes (max {})",
+                    label,
+                    task.prompt.len(),
+                    max_prompt_bytes
+                ));
+            }
+            if !(task.max_budget_usd > 0.0 && task.max_budget_usd.is_finite()) {
+                problems.push(format!(
+                    "{}: max_budget_usd must be positive (got {})",
+                    label, task.max_budget_usd
+                ));
+            }
+            if task.max_turns == 0 {
+                problems.push(format!("{}: max_turns must be positive", label));
+            }
+            if task.max_tool_calls == Some(0) {
+                problems.push(format!("{}: max_tool_calls must be positive", label));
+            }
+        }
+        problems
+    }
+
     /// Load the standard task set for generic repository benchmarking
     pub fn standard() -> Self {
         Self {
@@ -89,6 +188,8 @@ impl TaskSet {
                     ],
                     max_turns: 10,
                     max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
                 Task {
                     id: "architecture".to_string(),
@@ -104,6 +205,8 @@ impl TaskSet {
                     ],
                     max_turns: 15,
                     max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
                 Task {
                     id: "find_export".to_string(),
@@ -115,6 +218,8 @@ impl TaskSet {
                     expected_patterns: vec!["export".to_string(), "public".to_string()],
                     max_turns: 10,
                     max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
                 Task {
                     id: "dependencies".to_string(),
@@ -130,6 +235,8 @@ impl TaskSet {
                     ],
                     max_turns: 15,
                     max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
                 Task {
                     id: "file_count".to_string(),
@@ -145,6 +252,8 @@ impl TaskSet {
                     ],
                     max_turns: 10,
                     max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
             ],
         }
@@ -170,6 +279,8 @@ impl TaskSet {
                     ],
                     max_turns: 10,
                     max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
                 Task {
                     id: "architecture".to_string(),
@@ -185,10 +296,329 @@ impl TaskSet {
                     ],
                     max_turns: 15,
                     max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+            ],
+        }
+    }
+
+    /// Load the Rust navigation task set (crates, traits, module tree)
+    pub fn rust_navigation() -> Self {
+        Self {
+            name: "rust-navigation".to_string(),
+            description: "Navigation tasks tuned for Rust crates and workspaces".to_string(),
+            tasks: vec![
+                Task {
+                    id: "rust_crate_layout".to_string(),
+                    name: "Crate Layout".to_string(),
+                    prompt: "What crates and modules make up this project? \
+                             Describe the module tree starting from lib.rs or main.rs."
+                        .to_string(),
+                    category: TaskCategory::Understanding,
+                    expected_patterns: vec![
+                        "mod".to_string(),
+                        "crate".to_string(),
+                        "lib.rs".to_string(),
+                    ],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "rust_trait_impls".to_string(),
+                    name: "Trait Implementations".to_string(),
+                    prompt: "Find the most important public trait in this codebase. \
+                             Which types implement it and where are those impls?"
+                        .to_string(),
+                    category: TaskCategory::Exploration,
+                    expected_patterns: vec!["trait".to_string(), "impl".to_string()],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "rust_public_api".to_string(),
+                    name: "Public API Surface".to_string(),
+                    prompt: "What does this crate re-export from its root? \
+                             List the `pub use` items and the modules they come from."
+                        .to_string(),
+                    category: TaskCategory::Exports,
+                    expected_patterns: vec!["pub use".to_string(), "pub mod".to_string()],
+                    max_turns: 10,
+                    max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "rust_error_types".to_string(),
+                    name: "Error Handling".to_string(),
+                    prompt: "How are errors defined and propagated in this codebase? \
+                             Name the error types and the modules that return them."
+                        .to_string(),
+                    category: TaskCategory::Dependencies,
+                    expected_patterns: vec!["error".to_string(), "Result".to_string()],
+                    max_turns: 10,
+                    max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+            ],
+        }
+    }
+
+    /// Load the web frontend task set (components, state, routing)
+    pub fn web_frontend() -> Self {
+        Self {
+            name: "web-frontend".to_string(),
+            description: "Navigation tasks tuned for JavaScript/TypeScript frontends".to_string(),
+            tasks: vec![
+                Task {
+                    id: "web_entry".to_string(),
+                    name: "App Entry Point".to_string(),
+                    prompt: "Where does this application bootstrap? \
+                             Identify the entry file and the root component it renders."
+                        .to_string(),
+                    category: TaskCategory::Exploration,
+                    expected_patterns: vec![
+                        "index".to_string(),
+                        "App".to_string(),
+                        "render".to_string(),
+                    ],
+                    max_turns: 10,
+                    max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "web_components".to_string(),
+                    name: "Component Hierarchy".to_string(),
+                    prompt: "Describe the main UI component hierarchy. \
+                             Which components are composed by the root and where are they defined?"
+                        .to_string(),
+                    category: TaskCategory::Understanding,
+                    expected_patterns: vec!["component".to_string(), "props".to_string()],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "web_state".to_string(),
+                    name: "State Management".to_string(),
+                    prompt: "How is application state managed? \
+                             Find the stores, contexts, or hooks that hold shared state."
+                        .to_string(),
+                    category: TaskCategory::Dependencies,
+                    expected_patterns: vec![
+                        "state".to_string(),
+                        "store".to_string(),
+                        "hook".to_string(),
+                    ],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "web_exports".to_string(),
+                    name: "Package Exports".to_string(),
+                    prompt: "What does this package export to consumers? \
+                             List the exports from the package entry point."
+                        .to_string(),
+                    category: TaskCategory::Exports,
+                    expected_patterns: vec!["export".to_string()],
+                    max_turns: 10,
+                    max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+            ],
+        }
+    }
+
+    /// Load the Python navigation task set (packages, classes, imports)
+    pub fn python_navigation() -> Self {
+        Self {
+            name: "python-navigation".to_string(),
+            description: "Navigation tasks tuned for Python packages".to_string(),
+            tasks: vec![
+                Task {
+                    id: "py_package_layout".to_string(),
+                    name: "Package Layout".to_string(),
+                    prompt: "What packages and modules make up this project? \
+                             Describe what each top-level package is responsible for."
+                        .to_string(),
+                    category: TaskCategory::Understanding,
+                    expected_patterns: vec![
+                        "package".to_string(),
+                        "module".to_string(),
+                        "__init__".to_string(),
+                    ],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "py_class_hierarchy".to_string(),
+                    name: "Class Hierarchy".to_string(),
+                    prompt: "Find the central base class in this codebase. \
+                             Which classes subclass it and where are they defined?"
+                        .to_string(),
+                    category: TaskCategory::Exploration,
+                    expected_patterns: vec!["class".to_string()],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "py_public_api".to_string(),
+                    name: "Public API Surface".to_string(),
+                    prompt: "What names does the top-level package expose? \
+                             Check `__init__.py` and `__all__` for the public API."
+                        .to_string(),
+                    category: TaskCategory::Exports,
+                    expected_patterns: vec!["__init__".to_string(), "import".to_string()],
+                    max_turns: 10,
+                    max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+            ],
+        }
+    }
+
+    /// Load the Go navigation task set (packages, interfaces, entry points)
+    pub fn go_navigation() -> Self {
+        Self {
+            name: "go-navigation".to_string(),
+            description: "Navigation tasks tuned for Go modules".to_string(),
+            tasks: vec![
+                Task {
+                    id: "go_package_layout".to_string(),
+                    name: "Package Layout".to_string(),
+                    prompt: "What packages make up this Go module? \
+                             Describe the responsibility of each package under the module root."
+                        .to_string(),
+                    category: TaskCategory::Understanding,
+                    expected_patterns: vec!["package".to_string(), "module".to_string()],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "go_interfaces".to_string(),
+                    name: "Interface Implementations".to_string(),
+                    prompt: "Find the most important interface in this codebase. \
+                             Which types satisfy it and where are they defined?"
+                        .to_string(),
+                    category: TaskCategory::Exploration,
+                    expected_patterns: vec!["interface".to_string(), "func".to_string()],
+                    max_turns: 15,
+                    max_budget_usd: 1.5,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
+                },
+                Task {
+                    id: "go_entry".to_string(),
+                    name: "Command Entry Points".to_string(),
+                    prompt: "Where are the `main` packages in this module? \
+                             List each command and what it does."
+                        .to_string(),
+                    category: TaskCategory::Exploration,
+                    expected_patterns: vec!["main".to_string(), "cmd".to_string()],
+                    max_turns: 10,
+                    max_budget_usd: 1.0,
+                    max_tool_calls: None,
+                    setup_commands: vec![],
                 },
             ],
         }
     }
+
+    /// Names of all built-in task sets.
+    pub const BUILTIN_NAMES: &'static [&'static str] = &[
+        "standard",
+        "quick",
+        "rust-navigation",
+        "web-frontend",
+        "python-navigation",
+        "go-navigation",
+    ];
+
+    /// Shuffle task order deterministically from `seed` (Fisher-Yates over a
+    /// SplitMix64 stream). The same seed always yields the same permutation.
+    pub fn shuffle(&mut self, seed: u64) {
+        let mut rng = SplitMix64(seed);
+        for i in (1..self.tasks.len()).rev() {
+            let j = (rng.next() % (i as u64 + 1)) as usize;
+            self.tasks.swap(i, j);
+        }
+    }
+
+    /// Keep only tasks in one of `categories`.
+    pub fn retain_categories(&mut self, categories: &[TaskCategory]) {
+        self.tasks.retain(|t| categories.contains(&t.category));
+    }
+
+    /// Pick the most relevant built-in task set for a language, falling back
+    /// to the generic standard set.
+    pub fn for_language(language: Option<&str>) -> Self {
+        match language {
+            Some("rust") => Self::rust_navigation(),
+            Some("typescript") | Some("javascript") => Self::web_frontend(),
+            Some("python") => Self::python_navigation(),
+            Some("go") => Self::go_navigation(),
+            _ => Self::standard(),
+        }
+    }
+
+    /// Look up a built-in task set by name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Self::standard()),
+            "quick" => Some(Self::quick()),
+            "rust-navigation" => Some(Self::rust_navigation()),
+            "web-frontend" => Some(Self::web_frontend()),
+            "python-navigation" => Some(Self::python_navigation()),
+            "go-navigation" => Some(Self::go_navigation()),
+            _ => None,
+        }
+    }
+}
+
+/// Minimal seeded PRNG for reproducible task ordering.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// A minimal task for tests: `id` doubles as the name, 10 turns, $1.
+#[cfg(test)]
+pub(crate) fn test_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        name: id.to_string(),
+        prompt: "p".to_string(),
+        category: TaskCategory::Exploration,
+        expected_patterns: vec![],
+        max_turns: 10,
+        max_budget_usd: 1.0,
+        max_tool_calls: None,
+        setup_commands: vec![],
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +638,71 @@ mod tests {
         assert_eq!(tasks.name, "quick");
         assert!(tasks.tasks.len() < TaskSet::standard().tasks.len());
     }
+
+    #[test]
+    fn test_builtin_task_sets_resolve() {
+        for name in TaskSet::BUILTIN_NAMES {
+            let set = TaskSet::builtin(name).unwrap();
+            assert_eq!(set.name, *name);
+            assert!(!set.tasks.is_empty(), "{} has no tasks", name);
+        }
+    }
+
+    #[test]
+    fn test_filter_standard_to_exploration() {
+        let mut set = TaskSet::standard();
+        let total = set.tasks.len();
+        set.retain_categories(&["exploration".parse().unwrap()]);
+        assert!(!set.tasks.is_empty() && set.tasks.len() < total);
+        assert!(set
+            .tasks
+            .iter()
+            .all(|t| t.category == TaskCategory::Exploration));
+
+        assert!("Exports".parse::<TaskCategory>().is_ok());
+        assert!("editing".parse::<TaskCategory>().is_err());
+    }
+
+    #[test]
+    fn test_unknown_builtin_is_none() {
+        assert!(TaskSet::builtin("tasks.json").is_none());
+    }
+
+    #[test]
+    fn test_task_scoped_to_src_path() {
+        let task = TaskSet::quick().tasks.remove(0).scoped_to("packages/core");
+        assert!(task
+            .prompt
+            .starts_with("Focus on the `packages/core` directory"));
+        assert!(task.prompt.contains("main entry point"));
+    }
+
+    #[test]
+    fn test_task_set_for_language() {
+        assert_eq!(TaskSet::for_language(Some("rust")).name, "rust-navigation");
+        assert_eq!(
+            TaskSet::for_language(Some("typescript")).name,
+            "web-frontend"
+        );
+        assert_eq!(TaskSet::for_language(Some("cobol")).name, "standard");
+        assert_eq!(TaskSet::for_language(None).name, "standard");
+    }
+
+    #[test]
+    fn test_seeded_shuffle_is_stable() {
+        let ids = |set: &TaskSet| set.tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+
+        let mut a = TaskSet::standard();
+        let mut b = TaskSet::standard();
+        a.shuffle(42);
+        b.shuffle(42);
+        assert_eq!(ids(&a), ids(&b));
+
+        // Still a permutation of the original tasks
+        let mut sorted = ids(&a);
+        sorted.sort();
+        let mut original = ids(&TaskSet::standard());
+        original.sort();
+        assert_eq!(sorted, original);
+    }
 }